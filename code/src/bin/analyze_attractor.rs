@@ -4,101 +4,7 @@
 //! de baja dimensión, lo que permitiría compresión dramática (30-100x).
 
 use compression_experiment::attractor_analysis::*;
-
-// Copiar funciones de generación de datasets desde main.rs
-fn generate_conversational_drift(n: usize, dim: usize, drift_rate: f64) -> Vec<Vec<f32>> {
-    use rand::Rng;
-    let mut rng = rand::thread_rng();
-    let mut vectors = Vec::new();
-
-    let mut current: Vec<f32> = (0..dim).map(|_| rng.gen::<f32>()).collect();
-    normalize_vector(&mut current);
-    vectors.push(current.clone());
-
-    for _ in 1..n {
-        let drift: Vec<f32> = (0..dim).map(|_| rng.gen::<f32>()).collect();
-        let mut next = vec![0.0; dim];
-
-        for i in 0..dim {
-            next[i] = current[i] * (1.0 - drift_rate as f32) + drift[i] * (drift_rate as f32);
-        }
-
-        normalize_vector(&mut next);
-        vectors.push(next.clone());
-        current = next;
-    }
-
-    vectors
-}
-
-fn generate_temporal_smoothing(n: usize, dim: usize, alpha: f64) -> Vec<Vec<f32>> {
-    use rand::Rng;
-    let mut rng = rand::thread_rng();
-    let mut vectors = Vec::new();
-
-    let mut current: Vec<f32> = (0..dim).map(|_| rng.gen::<f32>()).collect();
-    normalize_vector(&mut current);
-    vectors.push(current.clone());
-
-    for _ in 1..n {
-        let noise: Vec<f32> = (0..dim).map(|_| rng.gen::<f32>()).collect();
-        let mut next = vec![0.0; dim];
-
-        for i in 0..dim {
-            next[i] = current[i] * (alpha as f32) + noise[i] * (1.0 - alpha as f32);
-        }
-
-        normalize_vector(&mut next);
-        vectors.push(next.clone());
-        current = next;
-    }
-
-    vectors
-}
-
-fn generate_clustered_topics(n: usize, dim: usize, cluster_size: usize) -> Vec<Vec<f32>> {
-    use rand::Rng;
-    let mut rng = rand::thread_rng();
-    let mut vectors = Vec::new();
-
-    let num_clusters = (n + cluster_size - 1) / cluster_size;
-    let mut cluster_centers = Vec::new();
-
-    for _ in 0..num_clusters {
-        let center: Vec<f32> = (0..dim).map(|_| rng.gen::<f32>()).collect();
-        cluster_centers.push(center);
-    }
-
-    for cluster_idx in 0..num_clusters {
-        let center = &cluster_centers[cluster_idx];
-
-        for _ in 0..cluster_size.min(n - vectors.len()) {
-            let mut vec = vec![0.0; dim];
-
-            for i in 0..dim {
-                vec[i] = center[i] + (rng.gen::<f32>() - 0.5) * 0.1;
-            }
-
-            normalize_vector(&mut vec);
-            vectors.push(vec);
-
-            if vectors.len() >= n {
-                break;
-            }
-        }
-    }
-
-    vectors
-}
-
-fn normalize_vector(vec: &mut [f32]) {
-    let norm: f32 = vec.iter().map(|&x| x * x).sum::<f32>().sqrt();
-    if norm > 1e-10 {
-        for x in vec.iter_mut() {
-            *x /= norm;
-        }
-    }
-}
+use compression_experiment::datasets::{generate_clustered_topics, generate_conversational_drift, generate_temporal_smoothing};
 
 fn main() {
     println!("================================================================================");
@@ -175,7 +81,7 @@ fn main() {
 
         for (label, analysis) in &results {
             if analysis.is_chaotic_attractor {
-                println!("  {} - {}", label, "⭐ CANDIDATO PARA COMPRESIÓN POR ATRACTOR");
+                println!("  {} - ⭐ CANDIDATO PARA COMPRESIÓN POR ATRACTOR", label);
                 println!("    Dimensión efectiva: {:.2} (vs 768 nominal)", analysis.correlation_dimension);
                 println!("    Potencial: {:.1}x compresión", analysis.compression_potential);
                 println!("    Estrategia: Modelar como trayectoria en atractor + parámetros del modelo");
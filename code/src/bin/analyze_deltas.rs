@@ -31,9 +31,9 @@ fn main() {
     let mut delta_magnitudes = Vec::new();
     let mut delta_values = Vec::new();
 
-    for i in 1..vectors.len() {
-        for j in 0..vectors[i].len() {
-            let delta = vectors[i][j] - vectors[i-1][j];
+    for pair in vectors.windows(2) {
+        for (&curr, &prev) in pair[1].iter().zip(pair[0].iter()) {
+            let delta = curr - prev;
             delta_values.push(delta);
             delta_magnitudes.push(delta.abs());
         }
@@ -86,7 +86,7 @@ fn main() {
 
     // Cuantizar deltas a int8 para calcular entropía
     let delta_int8: Vec<i8> = delta_values.iter()
-        .map(|&d| ((d * 127.0).clamp(-128.0, 127.0) as i8))
+        .map(|&d| (d * 127.0).clamp(-128.0, 127.0) as i8)
         .collect();
 
     let mut histogram: HashMap<i8, usize> = HashMap::new();
@@ -127,9 +127,9 @@ fn main() {
     let delta_compressed_gzip = {
         let mut data = Vec::new();
         data.extend(vectors[0].iter().flat_map(|&f| f.to_le_bytes()));
-        for i in 1..vectors.len() {
-            for j in 0..vectors[i].len() {
-                let delta = vectors[i][j] - vectors[i-1][j];
+        for pair in vectors.windows(2) {
+            for (&curr, &prev) in pair[1].iter().zip(pair[0].iter()) {
+                let delta = curr - prev;
                 data.extend(&delta.to_le_bytes());
             }
         }
@@ -168,13 +168,13 @@ fn main() {
         let mut angles = Vec::with_capacity(n - 1);
         let mut r_squared: f64 = vec.iter().map(|&x| (x as f64) * (x as f64)).sum();
 
-        for i in 0..n-2 {
+        for &x in &vec[..n - 2] {
             if r_squared > 1e-10 {
                 let r = r_squared.sqrt();
-                let cos_theta = (vec[i] as f64) / r;
+                let cos_theta = (x as f64) / r;
                 let theta = cos_theta.clamp(-1.0, 1.0).acos();
                 angles.push(theta as f32);
-                r_squared -= (vec[i] as f64) * (vec[i] as f64);
+                r_squared -= (x as f64) * (x as f64);
             } else {
                 angles.push(0.0);
             }
@@ -193,9 +193,9 @@ fn main() {
         .collect();
 
     let mut angular_deltas = Vec::new();
-    for i in 1..polar_vecs.len() {
-        for j in 0..polar_vecs[i].len() {
-            let mut delta = polar_vecs[i][j] - polar_vecs[i-1][j];
+    for pair in polar_vecs.windows(2) {
+        for (&curr, &prev) in pair[1].iter().zip(pair[0].iter()) {
+            let mut delta = curr - prev;
 
             // Normalizar delta a [-π, π]
             while delta > std::f32::consts::PI {
@@ -43,7 +43,11 @@ fn normalize_vector(v: &mut [f32]) {
 
 /// Genera vectores con DRIFT ACUMULATIVO (similitud consecutiva)
 pub fn generate_conversational_drift(n: usize, dim: usize, drift_rate: f64) -> Vec<Vec<f32>> {
-    let mut rng = rand::thread_rng();
+    generate_conversational_drift_with_rng(n, dim, drift_rate, &mut rand::thread_rng())
+}
+
+/// Como [`generate_conversational_drift`], tomando los números aleatorios de `rng`
+pub fn generate_conversational_drift_with_rng(n: usize, dim: usize, drift_rate: f64, rng: &mut impl Rng) -> Vec<Vec<f32>> {
     let mut vectors = Vec::new();
 
     // Primer vector aleatorio
@@ -70,7 +74,11 @@ pub fn generate_conversational_drift(n: usize, dim: usize, drift_rate: f64) -> V
 
 /// Genera vectores con TEMPORAL SMOOTHING (promedio móvil exponencial)
 pub fn generate_temporal_smoothing(n: usize, dim: usize, alpha: f64) -> Vec<Vec<f32>> {
-    let mut rng = rand::thread_rng();
+    generate_temporal_smoothing_with_rng(n, dim, alpha, &mut rand::thread_rng())
+}
+
+/// Como [`generate_temporal_smoothing`], tomando los números aleatorios de `rng`
+pub fn generate_temporal_smoothing_with_rng(n: usize, dim: usize, alpha: f64, rng: &mut impl Rng) -> Vec<Vec<f32>> {
     let mut vectors = Vec::new();
     let mut current: Vec<f32> = (0..dim).map(|_| rng.gen::<f32>()).collect();
     normalize_vector(&mut current);
@@ -94,9 +102,13 @@ pub fn generate_temporal_smoothing(n: usize, dim: usize, alpha: f64) -> Vec<Vec<
 
 /// Genera vectores CLUSTERIZADOS (cambio de tema cada N vectores)
 pub fn generate_clustered_topics(n: usize, dim: usize, cluster_size: usize) -> Vec<Vec<f32>> {
-    let mut rng = rand::thread_rng();
+    generate_clustered_topics_with_rng(n, dim, cluster_size, &mut rand::thread_rng())
+}
+
+/// Como [`generate_clustered_topics`], tomando los números aleatorios de `rng`
+pub fn generate_clustered_topics_with_rng(n: usize, dim: usize, cluster_size: usize, rng: &mut impl Rng) -> Vec<Vec<f32>> {
 
-    let num_clusters = (n + cluster_size - 1) / cluster_size;
+    let num_clusters = n.div_ceil(cluster_size);
     let mut cluster_centers = Vec::new();
 
     // Generar centros de clusters
@@ -125,3 +137,46 @@ pub fn generate_clustered_topics(n: usize, dim: usize, cluster_size: usize) -> V
 
     vectors
 }
+
+/// Trayectoria del ATRACTOR DE LORENZ (σ = 10, ρ = 28, β = 8/3), integrada
+/// con RK4 de paso `dt` tras descartar un transitorio
+pub fn lorenz_trajectory(n: usize, dt: f64) -> Vec<[f64; 3]> {
+    let (sigma, rho, beta) = (10.0, 28.0, 8.0 / 3.0);
+    let field = |p: [f64; 3]| [sigma * (p[1] - p[0]), p[0] * (rho - p[2]) - p[1], p[0] * p[1] - beta * p[2]];
+    let step = |p: [f64; 3]| {
+        let shift = |p: [f64; 3], d: [f64; 3], h: f64| [p[0] + h * d[0], p[1] + h * d[1], p[2] + h * d[2]];
+        let k1 = field(p);
+        let k2 = field(shift(p, k1, dt / 2.0));
+        let k3 = field(shift(p, k2, dt / 2.0));
+        let k4 = field(shift(p, k3, dt));
+        [0, 1, 2].map(|i| p[i] + dt / 6.0 * (k1[i] + 2.0 * k2[i] + 2.0 * k3[i] + k4[i]))
+    };
+
+    let mut point = [1.0, 1.0, 1.0];
+    for _ in 0..1000 {
+        point = step(point);
+    }
+    (0..n)
+        .map(|_| {
+            let current = point;
+            point = step(point);
+            current
+        })
+        .collect()
+}
+
+/// Genera vectores sobre un ATRACTOR CAÓTICO: trayectoria de Lorenz
+/// proyectada a `dim` dimensiones con una matriz aleatoria tomada de `rng`
+pub fn generate_lorenz_embedding(n: usize, dim: usize, dt: f64, rng: &mut impl Rng) -> Vec<Vec<f32>> {
+    let projection: Vec<[f32; 3]> = (0..dim)
+        .map(|_| [0, 1, 2].map(|_| (rng.gen::<f32>() - 0.5) * 0.1))
+        .collect();
+
+    lorenz_trajectory(n, dt)
+        .into_iter()
+        .map(|p| {
+            let p = p.map(|x| x as f32);
+            projection.iter().map(|w| w[0] * p[0] + w[1] * p[1] + w[2] * p[2]).collect()
+        })
+        .collect()
+}
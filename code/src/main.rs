@@ -4,10 +4,7 @@
 //! **ORCID**: 0009-0008-6093-8267
 //! **Fecha**: 2025-11-21
 
-mod methods;
-
-use methods::*;
-use ndarray::{Array1, Array2};
+use compression_experiment::*;
 use serde::{Serialize, Deserialize};
 use std::time::Instant;
 use std::fs::File;
@@ -70,6 +67,24 @@ fn main() {
     let dim = 768;
     datasets.push(("Synthetic: Clustered Topics (baseline)".to_string(), generate_clustered_topics(n_vectors, dim, 100)));
 
+    // Métodos a evaluar: todos los registrados, o los indicados como
+    // especificaciones `id[:clave=valor,...]` en la línea de comandos
+    let registry = CodecRegistry::with_builtin();
+    let specs: Vec<String> = std::env::args().skip(1).collect();
+    let codecs: Vec<Box<dyn Codec>> = if specs.is_empty() {
+        registry.all()
+    } else {
+        specs.iter()
+            .filter_map(|spec| match registry.create_from_spec(spec) {
+                Ok(codec) => Some(codec),
+                Err(e) => {
+                    eprintln!("⚠️ Ignorando {}: {}", spec, e);
+                    None
+                }
+            })
+            .collect()
+    };
+
     let mut all_results = Vec::new();
 
     for (label, vectors) in datasets {
@@ -92,37 +107,11 @@ fn main() {
 
         let mut results = Vec::new();
 
-        // GZIP Baseline
-        println!("Testing GZIP Baseline...");
-        results.push(test_method("GZIP", &vectors, gzip_compress, gzip_decompress));
-
-        // Int8 Quantization
-        println!("Testing Int8 Quantization...");
-        results.push(test_method("Int8+GZIP", &vectors, int8_compress, int8_decompress));
-
-        // Delta Encoding
-        println!("Testing Delta Encoding...");
-        results.push(test_method("Delta+GZIP", &vectors, delta_compress, delta_decompress));
-
-        // Zstd
-        println!("Testing Zstd...");
-        results.push(test_method("Zstd", &vectors, zstd_compress, zstd_decompress));
-
-        // Polar Delta ⭐ NUEVO
-        println!("Testing Polar Delta Encoding...");
-        results.push(test_method("PolarDelta+GZIP", &vectors, polar_delta_compress, polar_delta_decompress));
-
-        // Delta + ANS ⭐⭐⭐ ESPERADO 15x
-        println!("Testing Delta + ANS...");
-        results.push(test_method("Delta+ANS", &vectors, delta_ans_compress, delta_ans_decompress));
-
-        // Delta Lossless (RLE + GZIP) ⭐⭐⭐⭐ ESPERADO 10-15x SIN pérdida
-        println!("Testing Delta Lossless (RLE+GZIP)...");
-        results.push(test_method("Delta+RLE+GZIP", &vectors, delta_lossless_compress, delta_lossless_decompress));
-
-        // Attractor Compression (PCA + Delta) ⭐⭐⭐⭐⭐ ESPERADO 100-1000x
-        println!("Testing Attractor Compression (PCA+Delta)...");
-        results.push(test_method("Attractor(PCA-10)", &vectors, attractor_compress, attractor_decompress));
+        // Todos los métodos registrados, en orden de registro
+        for codec in &codecs {
+            println!("Testing {}...", codec.name());
+            results.push(test_method(codec.as_ref(), &vectors));
+        }
 
         println!("\n📊 Resultados:");
         for r in &results {
@@ -141,25 +130,26 @@ fn main() {
 
         // Validar hipótesis
         println!("\n🔬 Validación de Hipótesis:");
-        let delta_result = results.iter().find(|r| r.method == "Delta+GZIP").unwrap();
-        let polar_result = results.iter().find(|r| r.method.contains("PolarDelta")).unwrap();
-
         // Validación Delta cartesiano
-        if consec_sim >= 0.90 && delta_result.compression_ratio >= 8.0 {
-            println!("   ✅ Delta Cartesiano: {:.2}x con similitud {:.4}",
-                     delta_result.compression_ratio, consec_sim);
-        } else if consec_sim >= 0.90 {
-            println!("   ❌ Delta Cartesiano: solo {:.2}x (esperaba ≥8x) con similitud {:.4}",
-                     delta_result.compression_ratio, consec_sim);
+        if let Some(delta_result) = results.iter().find(|r| r.method == "Delta+GZIP") {
+            if consec_sim >= 0.90 && delta_result.compression_ratio >= 8.0 {
+                println!("   ✅ Delta Cartesiano: {:.2}x con similitud {:.4}",
+                         delta_result.compression_ratio, consec_sim);
+            } else if consec_sim >= 0.90 {
+                println!("   ❌ Delta Cartesiano: solo {:.2}x (esperaba ≥8x) con similitud {:.4}",
+                         delta_result.compression_ratio, consec_sim);
+            }
         }
 
         // Validación Polar Delta ⭐
-        if consec_sim >= 0.90 && polar_result.compression_ratio >= 8.0 {
-            println!("   ✅ POLAR DELTA VALIDADO: {:.2}x con similitud {:.4} 🎉",
-                     polar_result.compression_ratio, consec_sim);
-        } else if consec_sim >= 0.90 {
-            println!("   ⚠️  Polar Delta: {:.2}x (esperaba ≥8x) con similitud {:.4}",
-                     polar_result.compression_ratio, consec_sim);
+        if let Some(polar_result) = results.iter().find(|r| r.method.contains("PolarDelta")) {
+            if consec_sim >= 0.90 && polar_result.compression_ratio >= 8.0 {
+                println!("   ✅ POLAR DELTA VALIDADO: {:.2}x con similitud {:.4} 🎉",
+                         polar_result.compression_ratio, consec_sim);
+            } else if consec_sim >= 0.90 {
+                println!("   ⚠️  Polar Delta: {:.2}x (esperaba ≥8x) con similitud {:.4}",
+                         polar_result.compression_ratio, consec_sim);
+            }
         }
 
         // Guardar con label y similitud consecutiva
@@ -174,31 +164,31 @@ fn main() {
     print_comparison_table(&all_results);
 }
 
-fn test_method<C, D>(
-    name: &str,
-    vectors: &[Vec<f32>],
-    compress_fn: C,
-    decompress_fn: D,
-) -> ExperimentResults
-where
-    C: Fn(&[Vec<f32>]) -> Vec<u8>,
-    D: Fn(&[u8]) -> Vec<Vec<f32>>,
-{
+fn test_method(codec: &dyn Codec, vectors: &[Vec<f32>]) -> ExperimentResults {
     let original_size = vectors.len() * vectors[0].len() * 4;
 
     let start = Instant::now();
-    let compressed = compress_fn(vectors);
+    let compressed = codec.compress(vectors);
     let comp_time = start.elapsed().as_secs_f64() * 1000.0;
 
     let start = Instant::now();
-    let decompressed = decompress_fn(&compressed);
+    let outcome = compressed.and_then(|c| codec.decompress(&c).map(|d| (c, d)));
     let decomp_time = start.elapsed().as_secs_f64() * 1000.0;
 
-    let ratio = original_size as f64 / compressed.len() as f64;
-    let loss = calculate_accuracy_loss(vectors, &decompressed);
+    let (ratio, loss) = match outcome {
+        Ok((compressed, decompressed)) => (
+            original_size as f64 / compressed.len() as f64,
+            calculate_accuracy_loss(vectors, &decompressed),
+        ),
+        Err(e) => {
+            // Conservar la columna en las tablas aunque el método falle
+            eprintln!("⚠️ {} falló: {}", codec.name(), e);
+            (f64::NAN, f64::NAN)
+        }
+    };
 
     ExperimentResults {
-        method: name.to_string(),
+        method: codec.name(),
         compression_ratio: ratio,
         compression_time_ms: comp_time,
         decompression_time_ms: decomp_time,
@@ -206,153 +196,6 @@ where
     }
 }
 
-fn generate_similar_vectors(n: usize, dim: usize, similarity: f64) -> Vec<Vec<f32>> {
-    use rand::Rng;
-    let mut rng = rand::thread_rng();
-
-    let base: Vec<f32> = (0..dim).map(|_| rng.gen::<f32>()).collect();
-
-    (0..n).map(|_| {
-        base.iter().map(|&v| {
-            if rng.gen::<f64>() < similarity {
-                v + rng.gen::<f32>() * 0.1
-            } else {
-                rng.gen::<f32>()
-            }
-        }).collect()
-    }).collect()
-}
-
-/// NUEVO: Genera vectores con DRIFT ACUMULATIVO (similitud consecutiva)
-/// Esto es lo que Delta Encoding necesita para funcionar bien
-fn generate_conversational_drift(n: usize, dim: usize, drift_rate: f64) -> Vec<Vec<f32>> {
-    use rand::Rng;
-    let mut rng = rand::thread_rng();
-
-    let mut vectors = Vec::new();
-
-    // Primer vector aleatorio
-    let mut current: Vec<f32> = (0..dim).map(|_| rng.gen::<f32>()).collect();
-    normalize_vector(&mut current);
-    vectors.push(current.clone());
-
-    // Siguientes vectores con drift acumulativo
-    for _ in 1..n {
-        let drift: Vec<f32> = (0..dim).map(|_| rng.gen::<f32>()).collect();
-        let mut next = vec![0.0; dim];
-
-        for i in 0..dim {
-            next[i] = current[i] * (1.0 - drift_rate as f32) + drift[i] * (drift_rate as f32);
-        }
-
-        normalize_vector(&mut next);
-        vectors.push(next.clone());
-        current = next;
-    }
-
-    vectors
-}
-
-/// Genera vectores con TEMPORAL SMOOTHING (promedio móvil exponencial)
-fn generate_temporal_smoothing(n: usize, dim: usize, alpha: f64) -> Vec<Vec<f32>> {
-    use rand::Rng;
-    let mut rng = rand::thread_rng();
-
-    let mut vectors = Vec::new();
-    let mut current: Vec<f32> = (0..dim).map(|_| rng.gen::<f32>()).collect();
-    normalize_vector(&mut current);
-    vectors.push(current.clone());
-
-    for _ in 1..n {
-        let noise: Vec<f32> = (0..dim).map(|_| rng.gen::<f32>()).collect();
-        let mut next = vec![0.0; dim];
-
-        for i in 0..dim {
-            next[i] = current[i] * (alpha as f32) + noise[i] * ((1.0 - alpha) as f32);
-        }
-
-        normalize_vector(&mut next);
-        vectors.push(next.clone());
-        current = next;
-    }
-
-    vectors
-}
-
-/// Genera vectores CLUSTERIZADOS (cambio de tema cada N vectores)
-fn generate_clustered_topics(n: usize, dim: usize, cluster_size: usize) -> Vec<Vec<f32>> {
-    use rand::Rng;
-    let mut rng = rand::thread_rng();
-
-    let num_clusters = (n + cluster_size - 1) / cluster_size;
-    let mut cluster_centers = Vec::new();
-
-    // Generar centros de clusters
-    for _ in 0..num_clusters {
-        let mut center: Vec<f32> = (0..dim).map(|_| rng.gen::<f32>()).collect();
-        normalize_vector(&mut center);
-        cluster_centers.push(center);
-    }
-
-    let mut vectors = Vec::new();
-
-    for i in 0..n {
-        let cluster_idx = i / cluster_size;
-        let center = &cluster_centers[cluster_idx];
-
-        let noise: Vec<f32> = (0..dim).map(|_| rng.gen::<f32>()).collect();
-        let mut vector = vec![0.0; dim];
-
-        for j in 0..dim {
-            vector[j] = center[j] * 0.98 + noise[j] * 0.02;
-        }
-
-        normalize_vector(&mut vector);
-        vectors.push(vector);
-    }
-
-    vectors
-}
-
-fn normalize_vector(v: &mut [f32]) {
-    let norm: f32 = v.iter().map(|&x| x * x).sum::<f32>().sqrt();
-    if norm > 1e-10 {
-        for x in v.iter_mut() {
-            *x /= norm;
-        }
-    }
-}
-
-/// MÉTRICA CRÍTICA: Similitud Consecutiva (cosine similarity promedio)
-fn calculate_consecutive_similarity(vectors: &[Vec<f32>]) -> f64 {
-    if vectors.len() < 2 {
-        return 0.0;
-    }
-
-    let mut sum = 0.0;
-
-    for i in 1..vectors.len() {
-        let a = &vectors[i - 1];
-        let b = &vectors[i];
-
-        let mut dot = 0.0_f64;
-        let mut norm_a = 0.0_f64;
-        let mut norm_b = 0.0_f64;
-
-        for j in 0..a.len() {
-            dot += (a[j] as f64) * (b[j] as f64);
-            norm_a += (a[j] as f64) * (a[j] as f64);
-            norm_b += (b[j] as f64) * (b[j] as f64);
-        }
-
-        if norm_a > 1e-10 && norm_b > 1e-10 {
-            sum += dot / (norm_a.sqrt() * norm_b.sqrt());
-        }
-    }
-
-    sum / ((vectors.len() - 1) as f64)
-}
-
 /// Calculate accuracy loss using cosine similarity
 ///
 /// **Note**: Previous version used MSE/variance which produced artificially inflated
@@ -385,7 +228,7 @@ fn calculate_accuracy_loss(original: &[Vec<f32>], decompressed: &[Vec<f32>]) ->
 
     let avg_cosine_sim = total_cosine_sim / count as f64;
     // Return loss as (1 - similarity) * 100%
-    ((1.0 - avg_cosine_sim).max(0.0) * 100.0)
+    (1.0 - avg_cosine_sim).max(0.0) * 100.0
 }
 
 fn print_comparison_table(all_results: &[(String, f64, Vec<ExperimentResults>)]) {
@@ -393,9 +236,17 @@ fn print_comparison_table(all_results: &[(String, f64, Vec<ExperimentResults>)])
     println!("📊 TABLA COMPARATIVA FINAL");
     println!("{}\n", "=".repeat(90));
 
-    println!("{:<30} {:>12} {:>12} {:>12} {:>12} {:>12} {:>15} {:>12}",
-             "Dataset", "Consec.Sim", "GZIP", "Int8+GZIP", "Delta+GZIP", "Zstd", "PolarDelta", "Delta+ANS");
-    println!("{:-<117}", "");
+    // Columnas: métodos en el orden en que se evaluaron
+    let methods: Vec<&str> = all_results.first()
+        .map(|(_, _, results)| results.iter().map(|r| r.method.as_str()).collect())
+        .unwrap_or_default();
+
+    print!("{:<30} {:>12}", "Dataset", "Consec.Sim");
+    for method in &methods {
+        print!(" {:>12}", method);
+    }
+    println!();
+    println!("{:-<width$}", "", width = 43 + 13 * methods.len());
 
     for (label, consec_sim, results) in all_results {
         print!("{:<30} {:>11.4} ", label, consec_sim);
@@ -409,9 +260,12 @@ fn print_comparison_table(all_results: &[(String, f64, Vec<ExperimentResults>)])
     println!("📊 Pérdida de Accuracy por Dataset");
     println!("{}\n", "=".repeat(90));
 
-    println!("{:<30} {:>12} {:>12} {:>12} {:>12} {:>15} {:>12}",
-             "Dataset", "GZIP", "Int8+GZIP", "Delta+GZIP", "Zstd", "PolarDelta", "Delta+ANS");
-    println!("{:-<105}", "");
+    print!("{:<30}", "Dataset");
+    for method in &methods {
+        print!(" {:>12}", method);
+    }
+    println!();
+    println!("{:-<width$}", "", width = 30 + 13 * methods.len());
 
     for (label, _consec_sim, results) in all_results {
         print!("{:<30}", label);
@@ -429,7 +283,9 @@ fn print_comparison_table(all_results: &[(String, f64, Vec<ExperimentResults>)])
     let mut delta_failed_count = 0;
 
     for (label, consec_sim, results) in all_results {
-        let delta = results.iter().find(|r| r.method.contains("Delta")).unwrap();
+        let Some(delta) = results.iter().find(|r| r.method.contains("Delta")) else {
+            continue;
+        };
 
         println!("\n{}", label);
         println!("  Similitud Consecutiva: {:.4}", consec_sim);
@@ -1,254 +1,156 @@
 //! ANS (Asymmetric Numeral Systems) Compression for Delta Encoding
 //!
 //! Implementa compresión mediante ANS que alcanza ~95% de la entropía teórica
+//!
+//! Misma cuantización que `ans_simple` (deltas int8 en lazo cerrado, escala
+//! max|delta|); solo cambia la codificación de entropía: ANS de
+//! `constriction` con el histograma de los deltas como modelo, en lugar de
+//! GZIP. Así ambas variantes reconstruyen exactamente los mismos vectores.
+//!
+//! Formato del contenido: escala (f32) + primer vector (tipo de elemento) +
+//! histograma (u16 entradas + por cada una, delta i8 + cuenta u32) +
+//! palabras u32 del codificador ANS.
+
+use constriction::stream::model::DefaultContiguousCategoricalEntropyModel;
+use constriction::stream::stack::DefaultAnsCoder;
+use constriction::stream::{Decode, Encode};
 
-use constriction::stream::{
-    model::DefaultLeakyQuantizer,
-    stack::AnsCoder,
-    Decode, Encode,
-};
-use constriction::UnwrapInfallible;
+use super::ans_simple::{integrate_deltas, quantize_deltas};
+use super::bytes::{check_count, ByteReader};
+use super::container::{self, CodecId, ContainerHeader};
+use super::element::{element_le_bytes, Element};
+use super::error::CompressionError;
+use super::vectors::{compress_nested, VectorsView};
 
-type DefaultAnsCoder = AnsCoder<u32, u64, Vec<u32>>;
-use std::collections::HashMap;
+/// Símbolos del modelo: deltas int8 en [-127, 127]
+const SYMBOLS: usize = 255;
 
 /// Delta Encoding + ANS: Compresión óptima para deltas de baja entropía
-pub fn delta_ans_compress(vectors: &[Vec<f32>]) -> Vec<u8> {
+pub fn delta_rans_compress(vectors: &[Vec<f32>]) -> Result<Vec<u8>, CompressionError> {
+    compress_nested(vectors, delta_rans_compress_view)
+}
+
+/// Igual que `delta_rans_compress`, sobre una vista plana
+pub fn delta_rans_compress_view<T: Element>(vectors: VectorsView<'_, T>) -> Result<Vec<u8>, CompressionError> {
     if vectors.is_empty() {
-        return vec![];
+        return container::wrap(CodecId::DeltaRans, T::TYPE, &[], 0, 0, &[]);
     }
+    let dim = vectors.dim();
+    let n_vectors = vectors.n();
 
-    let dim = vectors[0].len();
-    let n_vectors = vectors.len();
-
-    // 1. Calcular deltas y cuantizar a int8
-    let mut deltas_i8 = Vec::new();
+    // 1. Deltas cuantizados a int8 en lazo cerrado
+    let (scale, deltas_i8) = quantize_deltas(vectors);
 
-    // Primer vector completo en float32
-    let mut first_vec_bytes = Vec::new();
-    for &val in &vectors[0] {
-        first_vec_bytes.extend(&val.to_le_bytes());
+    // 2. Histograma de frecuencias (modelo de probabilidad para ANS)
+    let mut histogram = [0u32; SYMBOLS];
+    for &q in &deltas_i8 {
+        histogram[(q as i16 + 127) as usize] += 1;
     }
 
-    // Deltas cuantizados
-    for i in 1..n_vectors {
-        for j in 0..dim {
-            let delta = vectors[i][j] - vectors[i - 1][j];
-            // Cuantizar a int8: rango [-1, 1] → [-127, 127]
-            let quantized = (delta * 127.0).clamp(-127.0, 127.0) as i8;
-            deltas_i8.push(quantized);
-        }
-    }
-
-    // 2. Calcular histograma de frecuencias
-    let mut histogram: HashMap<i8, usize> = HashMap::new();
-    for &val in &deltas_i8 {
-        *histogram.entry(val).or_insert(0) += 1;
+    // 3. Serializar: escala + primer vector + histograma + deltas ANS
+    let mut payload = scale.to_le_bytes().to_vec();
+    payload.extend(element_le_bytes(vectors.row(0)).iter());
+    let entries: Vec<(usize, u32)> = histogram.iter().copied().enumerate().filter(|&(_, count)| count > 0).collect();
+    payload.extend(&(entries.len() as u16).to_le_bytes());
+    for &(symbol, count) in &entries {
+        payload.push((symbol as i16 - 127) as i8 as u8);
+        payload.extend(&count.to_le_bytes());
     }
 
-    // 3. Crear modelo de probabilidad para ANS
-    // Convertir histograma a probabilidades
-    let total = deltas_i8.len() as f64;
-    let mut probabilities: Vec<(i8, f64)> = histogram
-        .iter()
-        .map(|(&symbol, &count)| (symbol, count as f64 / total))
-        .collect();
-    probabilities.sort_by_key(|(s, _)| *s);
-
-    // 4. Codificar con ANS
-    let symbols: Vec<i32> = deltas_i8.iter().map(|&x| x as i32 + 128).collect(); // Shift a [0, 255]
-
-    // Crear modelo cuantizado
-    let quantizer = DefaultLeakyQuantizer::new(-1..=255); // Rango extendido para seguridad
-
-    let probabilities_shifted: Vec<f64> = {
-        let mut probs = vec![1e-10; 257]; // Probabilidad mínima para símbolos no vistos
-        for (symbol, prob) in probabilities {
-            let idx = (symbol as i32 + 128) as usize;
-            if idx < probs.len() {
-                probs[idx] = prob;
-            }
+    // 4. Codificar con ANS (una pila: del último delta al primero)
+    if !deltas_i8.is_empty() {
+        let model = entropy_model(&histogram)?;
+        let mut coder = DefaultAnsCoder::new();
+        for &q in deltas_i8.iter().rev() {
+            coder.encode_symbol((q as i16 + 127) as usize, &model).map_err(CompressionError::ans)?;
+        }
+        for word in coder.into_compressed().map_err(CompressionError::ans)? {
+            payload.extend(&word.to_le_bytes());
         }
-        // Normalizar
-        let sum: f64 = probs.iter().sum();
-        probs.iter().map(|&p| p / sum).collect()
-    };
-
-    let model = quantizer.quantize_symbols(probabilities_shifted.iter().copied());
-
-    // Codificar
-    let mut coder = DefaultAnsCoder::new();
-    for &symbol in symbols.iter().rev() {
-        coder.encode_symbol(symbol, model.as_view()).unwrap_infallible();
-    }
-
-    let compressed_deltas = coder.into_compressed().unwrap_infallible();
-
-    // 5. Serializar: metadata + primer vector + deltas ANS
-    let mut result = Vec::new();
-
-    // Metadata
-    result.extend(&(n_vectors as u32).to_le_bytes());
-    result.extend(&(dim as u32).to_le_bytes());
-
-    // Primer vector (float32)
-    result.extend(&first_vec_bytes);
-
-    // Histograma (para decodificación)
-    result.extend(&(histogram.len() as u32).to_le_bytes());
-    for (symbol, count) in &histogram {
-        result.push(*symbol as u8);
-        result.extend(&(*count as u32).to_le_bytes());
     }
 
-    // Deltas comprimidos con ANS
-    result.extend(&(compressed_deltas.len() as u32).to_le_bytes());
-    result.extend(&compressed_deltas);
+    container::wrap(CodecId::DeltaRans, T::TYPE, &[], n_vectors, dim, &payload)
+}
 
-    result
+/// Modelo categórico con las frecuencias del histograma; los deltas no
+/// vistos conservan una probabilidad mínima
+fn entropy_model(histogram: &[u32; SYMBOLS]) -> Result<DefaultContiguousCategoricalEntropyModel, CompressionError> {
+    let total: u64 = histogram.iter().map(|&count| count as u64).sum();
+    let probabilities: Vec<f64> = histogram.iter().map(|&count| count as f64 / total as f64).collect();
+    DefaultContiguousCategoricalEntropyModel::from_floating_point_probabilities(&probabilities)
+        .map_err(|()| CompressionError::ans("histograma no representable"))
 }
 
 /// Descompresión de Delta + ANS
-pub fn delta_ans_decompress(compressed: &[u8]) -> Vec<Vec<f32>> {
-    if compressed.len() < 8 {
-        return vec![];
+pub fn delta_rans_decompress(compressed: &[u8]) -> Result<Vec<Vec<f32>>, CompressionError> {
+    container::decompress_as(compressed, CodecId::DeltaRans)
+}
+
+pub(crate) fn decode_payload<T: Element>(
+    header: &ContainerHeader,
+    payload: &[u8],
+    out: &mut [T],
+) -> Result<(), CompressionError> {
+    let n_vectors = header.n;
+    let dim = header.dim;
+    if n_vectors == 0 {
+        return Ok(());
+    }
+    if dim == 0 {
+        return Err(CompressionError::UnsupportedDimension { dim, min: 1 });
     }
 
-    let mut offset = 0;
-
-    // Leer metadata
-    let n_vectors = u32::from_le_bytes([
-        compressed[offset],
-        compressed[offset + 1],
-        compressed[offset + 2],
-        compressed[offset + 3],
-    ]) as usize;
-    offset += 4;
-
-    let dim = u32::from_le_bytes([
-        compressed[offset],
-        compressed[offset + 1],
-        compressed[offset + 2],
-        compressed[offset + 3],
-    ]) as usize;
-    offset += 4;
+    let mut reader = ByteReader::payload(payload);
+    let scale = reader.read_f32()?;
 
     // Leer primer vector
-    let mut first_vec = Vec::with_capacity(dim);
-    for _ in 0..dim {
-        let val = f32::from_le_bytes([
-            compressed[offset],
-            compressed[offset + 1],
-            compressed[offset + 2],
-            compressed[offset + 3],
-        ]);
-        first_vec.push(val);
-        offset += 4;
-    }
+    let element = header.element;
+    let prev = element.read_vec(&mut reader, dim)?;
 
     // Leer histograma
-    let histogram_size = u32::from_le_bytes([
-        compressed[offset],
-        compressed[offset + 1],
-        compressed[offset + 2],
-        compressed[offset + 3],
-    ]) as usize;
-    offset += 4;
-
-    let mut histogram: HashMap<i8, usize> = HashMap::new();
-    let mut total_count = 0;
-    for _ in 0..histogram_size {
-        let symbol = compressed[offset] as i8;
-        offset += 1;
-        let count = u32::from_le_bytes([
-            compressed[offset],
-            compressed[offset + 1],
-            compressed[offset + 2],
-            compressed[offset + 3],
-        ]) as usize;
-        offset += 4;
-        histogram.insert(symbol, count);
-        total_count += count;
-    }
-
-    // Reconstruir probabilidades
-    let total = total_count as f64;
-    let mut probabilities_shifted = vec![1e-10; 257];
-    for (symbol, count) in histogram {
-        let idx = (symbol as i32 + 128) as usize;
-        if idx < probabilities_shifted.len() {
-            probabilities_shifted[idx] = count as f64 / total;
+    let entries = reader.read_u16()? as usize;
+    check_count("histogram", entries, 5, reader.remaining())?;
+    let mut histogram = [0u32; SYMBOLS];
+    for _ in 0..entries {
+        let symbol = reader.read_u8()? as i8;
+        if symbol == i8::MIN {
+            return Err(CompressionError::BadLength { field: "histogram", value: symbol as u8 as usize });
         }
+        histogram[(symbol as i16 + 127) as usize] = reader.read_u32()?;
     }
 
-    // Normalizar
-    let sum: f64 = probabilities_shifted.iter().sum();
-    probabilities_shifted = probabilities_shifted.iter().map(|&p| p / sum).collect();
-
-    // Leer deltas ANS comprimidos
-    let compressed_size = u32::from_le_bytes([
-        compressed[offset],
-        compressed[offset + 1],
-        compressed[offset + 2],
-        compressed[offset + 3],
-    ]) as usize;
-    offset += 4;
-
-    let compressed_deltas = &compressed[offset..offset + compressed_size];
-
-    // Decodificar ANS
-    let quantizer = DefaultLeakyQuantizer::new(-1..=255);
-    let model = quantizer.quantize_symbols(probabilities_shifted.iter().copied());
-
-    let compressed_vec: Vec<u32> = compressed_deltas
-        .chunks(4)
-        .map(|chunk| {
-            let mut bytes = [0u8; 4];
-            bytes.copy_from_slice(chunk);
-            u32::from_le_bytes(bytes)
-        })
-        .collect();
-
-    let mut coder = DefaultAnsCoder::from_compressed(compressed_vec).unwrap_infallible();
-
-    let n_deltas = (n_vectors - 1) * dim;
-    let mut deltas_i8 = Vec::with_capacity(n_deltas);
-
-    for _ in 0..n_deltas {
-        let symbol = coder.decode_symbol(model.as_view()).unwrap_infallible();
-        deltas_i8.push((symbol - 128) as i8);
+    // El histograma cuenta exactamente los deltas codificados
+    let n_deltas = (n_vectors - 1).checked_mul(dim)
+        .ok_or(CompressionError::BadLength { field: "n_vectors", value: n_vectors })?;
+    let total: u64 = histogram.iter().map(|&count| count as u64).sum();
+    if total != n_deltas as u64 {
+        return Err(CompressionError::DimensionMismatch { expected: n_deltas, found: total as usize });
     }
 
-    deltas_i8.reverse(); // ANS decodifica en orden inverso
-
-    // Reconstruir vectores desde deltas
-    let mut vectors = Vec::with_capacity(n_vectors);
-    vectors.push(first_vec.clone());
-
-    let mut prev = first_vec;
-    let mut delta_idx = 0;
-
-    for _ in 1..n_vectors {
-        let mut current = Vec::with_capacity(dim);
-        for j in 0..dim {
-            let delta_quantized = deltas_i8[delta_idx] as f32;
-            let delta = delta_quantized / 127.0;
-            current.push(prev[j] + delta);
-            delta_idx += 1;
+    // Decodificar ANS (en el mismo orden en que se leyeron los vectores)
+    let mut deltas_i8 = Vec::with_capacity(n_deltas);
+    if n_deltas > 0 {
+        let model = entropy_model(&histogram)?;
+        let words: Vec<u32> = reader.rest().chunks_exact(4).map(|w| u32::from_le_bytes([w[0], w[1], w[2], w[3]])).collect();
+        let mut coder = DefaultAnsCoder::from_compressed(words).map_err(|_| CompressionError::ans("deltas ANS no válidos"))?;
+        for _ in 0..n_deltas {
+            let symbol = coder.decode_symbol(&model).map_err(CompressionError::ans)?;
+            deltas_i8.push((symbol as i16 - 127) as i8);
         }
-        vectors.push(current.clone());
-        prev = current;
     }
 
-    vectors
+    // Reconstruir vectores desde deltas
+    integrate_deltas(element, scale, prev, deltas_i8.into_iter(), out);
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::methods::{delta_ans_compress, delta_ans_decompress};
 
     #[test]
-    fn test_delta_ans_roundtrip() {
+    fn test_delta_rans_roundtrip() {
         // Crear vectores de prueba
         let mut vectors = Vec::new();
         let mut current = vec![0.5f32; 10];
@@ -263,10 +165,10 @@ mod tests {
         }
 
         // Comprimir
-        let compressed = delta_ans_compress(&vectors);
+        let compressed = delta_rans_compress(&vectors).unwrap();
 
         // Descomprimir
-        let decompressed = delta_ans_decompress(&compressed);
+        let decompressed = delta_rans_decompress(&compressed).unwrap();
 
         // Verificar
         assert_eq!(vectors.len(), decompressed.len());
@@ -277,6 +179,10 @@ mod tests {
                 assert!((o - d).abs() < 0.01, "Valor original: {}, Decomprimido: {}", o, d);
             }
         }
+
+        // Un solo vector (sin deltas) y entrada vacía
+        assert_eq!(delta_rans_decompress(&delta_rans_compress(&vectors[..1]).unwrap()).unwrap(), vectors[..1].to_vec());
+        assert!(delta_rans_decompress(&delta_rans_compress(&[]).unwrap()).unwrap().is_empty());
     }
 
     #[test]
@@ -294,7 +200,7 @@ mod tests {
         }
 
         let original_size = vectors.len() * vectors[0].len() * 4;
-        let compressed = delta_ans_compress(&vectors);
+        let compressed = delta_rans_compress(&vectors).unwrap();
         let ratio = original_size as f64 / compressed.len() as f64;
 
         println!("Original: {} bytes", original_size);
@@ -304,4 +210,53 @@ mod tests {
         // Esperamos >=10x con deltas muy pequeños
         assert!(ratio > 10.0, "Ratio: {:.2}x, esperaba >10x", ratio);
     }
+
+    #[test]
+    fn test_matches_gzip_variant_with_fewer_bytes() {
+        // Pasos pequeños con saltos raros: el salto máximo fija una escala
+        // gruesa y casi todos los deltas int8 caen cerca de 0, donde GZIP
+        // (por bytes) queda lejos de la entropía del histograma
+        use rand::{rngs::StdRng, Rng, SeedableRng};
+        let mut rng = StdRng::seed_from_u64(5);
+        let (n, dim) = (3000, 16);
+        let mut current = vec![0.0f32; dim];
+        let vectors: Vec<Vec<f32>> = (0..n)
+            .map(|_| {
+                for val in current.iter_mut() {
+                    let jump = if rng.gen::<f32>() < 0.005 { 0.5 } else { 0.0 };
+                    *val += (rng.gen::<f32>() - 0.5) * 0.02 + jump;
+                }
+                current.clone()
+            })
+            .collect();
+
+        let rans = delta_rans_compress(&vectors).unwrap();
+        let gzip = delta_ans_compress(&vectors).unwrap();
+        println!("Delta+rANS {} bytes, Delta+ANS (int8+GZIP) {} bytes", rans.len(), gzip.len());
+        assert!(rans.len() < gzip.len());
+
+        // Misma cuantización: la reconstrucción es idéntica
+        assert_eq!(delta_rans_decompress(&rans).unwrap(), delta_ans_decompress(&gzip).unwrap());
+    }
+
+    #[test]
+    fn test_tail_error_bounded_on_long_sequence() {
+        // 3000 pasos de +0.001: en lazo abierto cada delta se trunca a 0 y el
+        // error crece hasta 3.0; en lazo cerrado queda en medio paso (1/254)
+        let mut vectors = Vec::new();
+        let mut current = vec![0.5f32; 8];
+        for _ in 0..3000 {
+            vectors.push(current.clone());
+            for val in current.iter_mut() {
+                *val += 0.001;
+            }
+        }
+
+        let decompressed = delta_rans_decompress(&delta_rans_compress(&vectors).unwrap()).unwrap();
+        let tail_error = vectors[2900..].iter().flatten()
+            .zip(decompressed[2900..].iter().flatten())
+            .map(|(a, b)| (a - b).abs())
+            .fold(0.0f32, f32::max);
+        assert!(tail_error <= 1.0 / 254.0 + 1e-3, "error en la cola: {}", tail_error);
+    }
 }
@@ -3,173 +3,145 @@
 //! Implementación simplificada que logra compresión cercana a entropía
 
 use flate2::Compression;
-use flate2::write::GzEncoder;
-use flate2::read::GzDecoder;
-use std::io::{Write, Read};
+
+use super::bytes::{gzip_decode, gzip_encode, ByteReader};
+use super::container::{self, CodecId, ContainerHeader};
+use super::element::{element_le_bytes, Element, ElementType};
+use super::error::CompressionError;
+use super::vectors::{compress_nested, VectorsView};
 
 /// Delta Encoding con cuantización int8 uniforme + GZIP
 ///
 /// Estrategia:
-/// - Cuantizar TODOS los deltas a int8 (±127)
+/// - Cuantizar TODOS los deltas a int8 (±127), en lazo cerrado
 /// - Escalar basado en max(|delta|)
 /// - Comprimir con GZIP (que funciona mejor que ANS para entropía uniforme)
-pub fn delta_ans_compress(vectors: &[Vec<f32>]) -> Vec<u8> {
-    if vectors.is_empty() {
-        return vec![];
-    }
-
-    let dim = vectors[0].len();
-    let n_vectors = vectors.len();
-
-    // 1. Calcular deltas
-    let mut deltas_f32 = Vec::new();
+pub fn delta_ans_compress(vectors: &[Vec<f32>]) -> Result<Vec<u8>, CompressionError> {
+    compress_nested(vectors, delta_ans_compress_view)
+}
 
-    for i in 1..n_vectors {
-        for j in 0..dim {
-            let delta = vectors[i][j] - vectors[i - 1][j];
-            deltas_f32.push(delta);
-        }
+/// Igual que `delta_ans_compress`, sobre una vista plana
+pub fn delta_ans_compress_view<T: Element>(vectors: VectorsView<'_, T>) -> Result<Vec<u8>, CompressionError> {
+    if vectors.is_empty() {
+        return container::wrap(CodecId::DeltaAns, T::TYPE, &[], 0, 0, &[]);
     }
+    let dim = vectors.dim();
+    let n_vectors = vectors.n();
 
-    // 2. Encontrar máximo absoluto para escalar
-    let max_abs_delta = deltas_f32.iter()
-        .map(|&d| d.abs())
-        .max_by(|a, b| a.partial_cmp(b).unwrap())
-        .unwrap_or(1.0);
-
-    // Si todos los deltas son 0, usar factor de escala 1.0
-    let scale = if max_abs_delta > 1e-10 { max_abs_delta } else { 1.0 };
-
-    // 3. Cuantizar a int8 (rango [-127, 127])
-    let deltas_i8: Vec<i8> = deltas_f32.iter()
-        .map(|&delta| {
-            let normalized = delta / scale;
-            (normalized * 127.0).clamp(-127.0, 127.0) as i8
-        })
-        .collect();
+    // 1-2. Escala y deltas int8 en lazo cerrado
+    let (scale, deltas_i8) = quantize_deltas(vectors);
 
-    // 4. Convertir a bytes (shift para evitar negativos en GZIP)
+    // 3. Convertir a bytes (shift para evitar negativos en GZIP)
     let encoded: Vec<u8> = deltas_i8.iter()
         .map(|&q| (q as i16 + 128) as u8)
         .collect();
 
-    // 5. Aplicar GZIP sobre datos cuantizados
-    let mut encoder = GzEncoder::new(Vec::new(), Compression::best());
-    encoder.write_all(&encoded).unwrap();
-    let compressed_deltas = encoder.finish().unwrap();
+    // 4. Aplicar GZIP sobre datos cuantizados
+    let compressed_deltas = gzip_encode(&encoded, Compression::best())?;
 
-    // 6. Serializar resultado
-    let mut result = Vec::new();
+    // 5. Serializar resultado: escala + primer vector (tipo original) + deltas comprimidos
+    let mut payload = Vec::with_capacity(4 + dim * T::TYPE.size() + compressed_deltas.len());
+    payload.extend(&scale.to_le_bytes());
+    payload.extend(element_le_bytes(vectors.row(0)).iter());
+    payload.extend(&compressed_deltas);
 
-    // Metadata
-    result.extend(&(n_vectors as u32).to_le_bytes());
-    result.extend(&(dim as u32).to_le_bytes());
-    result.extend(&scale.to_le_bytes());
+    container::wrap(CodecId::DeltaAns, T::TYPE, &[], n_vectors, dim, &payload)
+}
 
-    // Primer vector (float32)
-    for &val in &vectors[0] {
-        result.extend(&val.to_le_bytes());
-    }
+/// Escala (máximo |delta| entre vectores originales consecutivos, 1.0 si
+/// todos son 0) y deltas cuantizados a int8 (rango [-127, 127]) en lazo
+/// cerrado (DPCM): cada delta se mide contra el vector que reconstruirá el
+/// decodificador, así el error de cuantización no se acumula a lo largo de
+/// la secuencia
+///
+/// Compartido con `ans`, que solo cambia la codificación de entropía.
+pub(crate) fn quantize_deltas<T: Element>(vectors: VectorsView<'_, T>) -> (f32, Vec<i8>) {
+    let dim = vectors.dim();
+    let data = vectors.as_slice();
+    let max_abs_delta = data[dim..].iter()
+        .zip(data.iter())
+        .map(|(&curr, &prev)| (curr.to_f64() - prev.to_f64()).abs() as f32)
+        .fold(0.0f32, f32::max);
+    let scale = if max_abs_delta > 1e-10 { max_abs_delta } else { 1.0 };
 
-    // Deltas comprimidos
-    result.extend(&(compressed_deltas.len() as u32).to_le_bytes());
-    result.extend(&compressed_deltas);
+    let element = T::TYPE;
+    let mut prev: Vec<f64> = vectors.row(0).iter().map(|x| x.to_f64()).collect();
+    let mut deltas_i8 = Vec::with_capacity(data.len() - dim);
+    for row in vectors.rows().skip(1) {
+        for (p, &x) in prev.iter_mut().zip(row) {
+            let normalized = ((x.to_f64() - *p) / scale as f64) as f32;
+            let quantized = (normalized * 127.0).round().clamp(-127.0, 127.0) as i8;
+            deltas_i8.push(quantized);
+            *p = element.round(*p + ((quantized as f32 / 127.0) * scale) as f64);
+        }
+    }
+    (scale, deltas_i8)
+}
 
-    result
+/// Reconstruye los vectores desde el primero (`prev`) integrando los deltas
+/// int8, con el mismo redondeo al tipo de elemento que el codificador
+pub(crate) fn integrate_deltas<T: Element>(
+    element: ElementType,
+    scale: f32,
+    mut prev: Vec<f64>,
+    mut deltas: impl Iterator<Item = i8>,
+    out: &mut [T],
+) {
+    let dim = prev.len();
+    for (o, &p) in out[..dim].iter_mut().zip(&prev) {
+        *o = T::from_f64(p);
+    }
+    for row in out[dim..].chunks_exact_mut(dim) {
+        for ((o, p), quantized) in row.iter_mut().zip(prev.iter_mut()).zip(deltas.by_ref()) {
+            *p = element.round(*p + ((quantized as f32 / 127.0) * scale) as f64);
+            *o = T::from_f64(*p);
+        }
+    }
 }
 
 /// Descompresión de Delta + ANS simplificado
-pub fn delta_ans_decompress(compressed: &[u8]) -> Vec<Vec<f32>> {
-    if compressed.len() < 12 {
-        return vec![];
+pub fn delta_ans_decompress(compressed: &[u8]) -> Result<Vec<Vec<f32>>, CompressionError> {
+    container::decompress_as(compressed, CodecId::DeltaAns)
+}
+
+pub(crate) fn decode_payload<T: Element>(
+    header: &ContainerHeader,
+    payload: &[u8],
+    out: &mut [T],
+) -> Result<(), CompressionError> {
+    let n_vectors = header.n;
+    let dim = header.dim;
+    if n_vectors == 0 {
+        return Ok(());
+    }
+    if dim == 0 {
+        return Err(CompressionError::UnsupportedDimension { dim, min: 1 });
     }
 
-    let mut offset = 0;
-
-    // Leer metadata
-    let n_vectors = u32::from_le_bytes([
-        compressed[offset],
-        compressed[offset + 1],
-        compressed[offset + 2],
-        compressed[offset + 3],
-    ]) as usize;
-    offset += 4;
-
-    let dim = u32::from_le_bytes([
-        compressed[offset],
-        compressed[offset + 1],
-        compressed[offset + 2],
-        compressed[offset + 3],
-    ]) as usize;
-    offset += 4;
-
-    let scale = f32::from_le_bytes([
-        compressed[offset],
-        compressed[offset + 1],
-        compressed[offset + 2],
-        compressed[offset + 3],
-    ]);
-    offset += 4;
+    let mut reader = ByteReader::payload(payload);
+    let scale = reader.read_f32()?;
 
     // Leer primer vector
-    let mut first_vec = Vec::with_capacity(dim);
-    for _ in 0..dim {
-        let val = f32::from_le_bytes([
-            compressed[offset],
-            compressed[offset + 1],
-            compressed[offset + 2],
-            compressed[offset + 3],
-        ]);
-        first_vec.push(val);
-        offset += 4;
-    }
-
-    // Leer deltas comprimidos
-    let compressed_size = u32::from_le_bytes([
-        compressed[offset],
-        compressed[offset + 1],
-        compressed[offset + 2],
-        compressed[offset + 3],
-    ]) as usize;
-    offset += 4;
+    let element = header.element;
+    let prev = element.read_vec(&mut reader, dim)?;
 
-    let compressed_deltas = &compressed[offset..offset + compressed_size];
+    // Deltas comprimidos: resto del contenido
+    let compressed_deltas = reader.rest();
 
     // Descomprimir GZIP
-    let mut decoder = GzDecoder::new(compressed_deltas);
-    let mut encoded = Vec::new();
-    decoder.read_to_end(&mut encoded).unwrap();
-
-    // Decodificar deltas: convertir u8 → i8 → f32
-    let deltas_f32: Vec<f32> = encoded.iter()
-        .map(|&byte| {
-            let quantized = (byte as i16 - 128) as i8;
-            (quantized as f32 / 127.0) * scale
-        })
-        .collect();
+    let encoded = gzip_decode(compressed_deltas)?;
 
-    // Reconstruir vectores desde deltas
-    let mut vectors = Vec::with_capacity(n_vectors);
-    vectors.push(first_vec.clone());
-
-    let mut prev = first_vec;
-    let mut delta_idx = 0;
-
-    for _ in 1..n_vectors {
-        let mut current = Vec::with_capacity(dim);
-        for _ in 0..dim {
-            if delta_idx < deltas_f32.len() {
-                current.push(prev[current.len()] + deltas_f32[delta_idx]);
-                delta_idx += 1;
-            } else {
-                break;
-            }
-        }
-        vectors.push(current.clone());
-        prev = current;
+    let n_deltas = (n_vectors - 1).checked_mul(dim)
+        .ok_or(CompressionError::BadLength { field: "n_vectors", value: n_vectors })?;
+    if encoded.len() != n_deltas {
+        return Err(CompressionError::DimensionMismatch { expected: n_deltas, found: encoded.len() });
     }
 
-    vectors
+    // Primer vector, y el resto integrando deltas: convertir u8 → i8 → f32
+    let deltas = encoded.iter().map(|&byte| (byte as i16 - 128) as i8);
+    integrate_deltas(element, scale, prev, deltas, out);
+
+    Ok(())
 }
 
 #[cfg(test)]
@@ -189,8 +161,8 @@ mod tests {
             vectors.push(current.clone());
         }
 
-        let compressed = delta_ans_compress(&vectors);
-        let decompressed = delta_ans_decompress(&compressed);
+        let compressed = delta_ans_compress(&vectors).unwrap();
+        let decompressed = delta_ans_decompress(&compressed).unwrap();
 
         assert_eq!(vectors.len(), decompressed.len());
 
@@ -201,4 +173,33 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_tail_error_bounded_on_long_sequence() {
+        // Paseo aleatorio de 5000 pasos: con deltas en lazo abierto el error de
+        // cuantización se acumularía; en lazo cerrado queda acotado por paso
+        use rand::{rngs::StdRng, Rng, SeedableRng};
+        let mut rng = StdRng::seed_from_u64(5);
+        let (n, dim) = (5000, 16);
+        let mut current = vec![0.0f32; dim];
+        let vectors: Vec<Vec<f32>> = (0..n)
+            .map(|_| {
+                for val in current.iter_mut() {
+                    *val += rng.gen::<f32>() * 0.02 - 0.01;
+                }
+                current.clone()
+            })
+            .collect();
+
+        let compressed = delta_ans_compress(&vectors).unwrap();
+        let decompressed = delta_ans_decompress(&compressed).unwrap();
+
+        // Medio paso de cuantización: max|delta| / 127 / 2 ≤ 0.01 / 254
+        let bound = 0.01 / 254.0 + 1e-6;
+        let tail_error = vectors[n - 100..].iter().flatten()
+            .zip(decompressed[n - 100..].iter().flatten())
+            .map(|(a, b)| (a - b).abs())
+            .fold(0.0f32, f32::max);
+        assert!(tail_error <= bound, "error en la cola: {} > {}", tail_error, bound);
+    }
 }
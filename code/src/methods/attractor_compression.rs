@@ -5,16 +5,32 @@
 //! 2. Modelar trayectoria en espacio reducido como sistema dinámico
 //! 3. Almacenar: componentes principales + parámetros del modelo + residuos pequeños
 //!
+//! Formato del contenido: media (tipo de elemento, × dim) + componentes
+//! principales (float32, k × dim) + coeficientes del modelo (ver
+//! `dynamics`) + longitud (u32) + trayectoria (GZIP, ANS o puntos de
+//! control) y, opcionalmente, residuos (ver `encode_residuals`).
+//! Parámetros: ver `encode_params`.
+//!
 //! Potencial: 100-1000x compresión para embeddings con atractores de baja dimensión
 
-use ndarray::{Array1, Array2, Axis};
+use constriction::stream::model::{DefaultLeakyQuantizer, EncoderModel};
+use constriction::stream::stack::DefaultAnsCoder;
+use constriction::stream::{Decode, Encode};
+use ndarray::{Array2, ArrayView1, ArrayView2};
 use flate2::Compression;
-use flate2::write::GzEncoder;
-use flate2::read::GzDecoder;
-use std::io::{Write, Read};
+use probability::distribution::Laplace;
+
+use super::bytes::{check_count, gzip_decode, gzip_encode, write_varint, ByteReader};
+use super::container::{self, CodecId, ContainerHeader};
+use super::dynamics::{Predictor, TrajectoryModel};
+use super::element::Element;
+use super::error::CompressionError;
+use super::pca::{ComponentCount, Pca, PcaSolver};
+use super::vectors::{compress_nested, VectorsView};
+use crate::attractor_analysis::max_lyapunov_exponent;
 
 /// Compresión basada en atractor con PCA (wrapper con componentes fijos)
-pub fn attractor_compress(vectors: &[Vec<f32>]) -> Vec<u8> {
+pub fn attractor_compress(vectors: &[Vec<f32>]) -> Result<Vec<u8>, CompressionError> {
     attractor_compress_with_components(vectors, 10)
 }
 
@@ -27,225 +43,922 @@ pub fn attractor_compress(vectors: &[Vec<f32>]) -> Vec<u8> {
 ///
 /// # Parámetros
 /// - `n_components`: Número de componentes principales a retener (default: 10)
-pub fn attractor_compress_with_components(vectors: &[Vec<f32>], n_components: usize) -> Vec<u8> {
+pub fn attractor_compress_with_components(
+    vectors: &[Vec<f32>],
+    n_components: usize,
+) -> Result<Vec<u8>, CompressionError> {
+    let config = AttractorConfig { components: ComponentCount::Fixed(n_components), ..AttractorConfig::default() };
+    attractor_compress_with_config(vectors, &config)
+}
+
+/// Configuración del codec de atractor
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AttractorConfig {
+    /// Número de componentes principales, fijo o elegido por un objetivo
+    /// de varianza explicada o de pérdida coseno
+    pub components: ComponentCount,
+
+    /// Método de cálculo de la base PCA
+    pub solver: PcaSolver,
+
+    /// Cota de error absoluto por coordenada: si se indica, se añade una capa
+    /// de residuos cuantizados que garantiza `max |x - x̂| <= eps`
+    pub error_bound: Option<f64>,
+
+    /// Paso de cuantización de los deltas de la trayectoria latente
+    pub quantization: DeltaQuantization,
+
+    /// Predictor de la trayectoria latente: solo se guardan sus residuos
+    pub model: TrajectoryModel,
+
+    /// Modo de sombreado: en lugar de residuos se guardan la condición
+    /// inicial y puntos de control donde la trayectoria predicha se aleja
+    /// más de esta tolerancia (error absoluto por componente latente)
+    pub shadowing: Option<f64>,
+
+    /// Codificación de entropía de los residuos latentes (en modo de
+    /// sombreado no hay residuos y se usa siempre GZIP)
+    pub latent_coder: LatentCoder,
+}
+
+impl Default for AttractorConfig {
+    fn default() -> Self {
+        AttractorConfig {
+            components: ComponentCount::Fixed(10),
+            solver: PcaSolver::Exact,
+            error_bound: None,
+            quantization: DeltaQuantization::Adaptive,
+            model: TrajectoryModel::Delta,
+            shadowing: None,
+            latent_coder: LatentCoder::Gzip,
+        }
+    }
+}
+
+/// Codificador de entropía de la trayectoria latente
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LatentCoder {
+    /// GZIP sobre los residuos int16 (formato original)
+    #[default]
+    Gzip,
+
+    /// ANS con una Laplace ajustada a los residuos de cada componente: los
+    /// residuos de predicción se concentran en torno a cero y GZIP, que
+    /// trabaja por bytes, queda lejos de su entropía
+    Ans,
+}
+
+/// Elección del paso de cuantización (int16) de los deltas latentes
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum DeltaQuantization {
+    /// Paso por componente a partir de la estadística de sus deltas:
+    /// σ/`ADAPTIVE_LEVELS`, ampliado si hace falta para que el mayor residuo
+    /// de predicción quepa en int16 (sin saturación)
+    #[default]
+    Adaptive,
+
+    /// Distorsión RMS objetivo por componente latente: paso = d·√12
+    TargetDistortion(f64),
+
+    /// Paso fijo para todas las componentes (0.001 en el formato anterior)
+    Fixed(f64),
+}
+
+/// Niveles de cuantización por desviación típica de los deltas (modo adaptativo)
+pub(crate) const ADAPTIVE_LEVELS: f64 = 128.0;
+
+/// Mayor residuo cuantizado admitido sin saturar en lazo cerrado: con el
+/// predictor de deltas el error acumulado añade hasta medio paso
+pub(crate) const MAX_QUANTIZED_DELTA: f64 = 32766.0;
+
+/// Metadatos de un blob de atractor (parámetros del contenedor)
+#[derive(Debug, Clone, PartialEq)]
+pub struct AttractorInfo {
+    /// Componentes principales retenidas
+    pub n_components: usize,
+    /// Fracción de la varianza retenida
+    pub explained_variance: f64,
+    /// Cota de error de la capa de residuos, si la hay
+    pub error_bound: Option<f64>,
+    /// Residuos latentes saturados al rango de int16 al codificar
+    pub clipped: usize,
+    /// Paso de cuantización de cada componente
+    pub steps: Vec<f32>,
+    /// Modelo de la trayectoria
+    pub model: TrajectoryModel,
+    /// Puntos de control del modo de sombreado, si está activo
+    pub shadowing: Option<ShadowingInfo>,
+    /// Coste de la trayectoria con ANS, si se usó
+    pub ans: Option<LatentAnsInfo>,
+    /// Bytes de cada sección del contenido
+    pub sizes: AttractorSizes,
+}
+
+/// Bytes de cada sección del contenido de un blob de atractor
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AttractorSizes {
+    /// Media y componentes principales
+    pub basis: usize,
+    /// Coeficientes del modelo de trayectoria
+    pub model: usize,
+    /// Trayectoria comprimida (GZIP o ANS), sin su longitud
+    pub trajectory: usize,
+    /// Capa de residuos (paso incluido), 0 si no hay
+    pub residuals: usize,
+}
+
+/// Resultado de la codificación ANS de la trayectoria
+#[derive(Debug, Clone, PartialEq)]
+pub struct LatentAnsInfo {
+    /// Bits por residuo de cada componente con su modelo (sin sobrecarga)
+    pub bits_per_component: Vec<f32>,
+    /// Bytes de la trayectoria con ANS (incluidos primeros puntos y modelos)
+    pub ans_bytes: usize,
+    /// Bytes de la misma trayectoria con GZIP
+    pub gzip_bytes: usize,
+}
+
+/// Resultado del modo de sombreado
+#[derive(Debug, Clone, PartialEq)]
+pub struct ShadowingInfo {
+    /// Error absoluto máximo por componente latente antes de resincronizar
+    pub tolerance: f64,
+    /// Exponente de Lyapunov máximo medido en la trayectoria latente (por paso)
+    pub lyapunov_exponent: f64,
+    /// Pasos entre resincronizaciones periódicas (0 = solo por divergencia)
+    pub interval: usize,
+    /// Puntos de control guardados (sin contar la condición inicial)
+    pub checkpoints: usize,
+    /// Bytes de la trayectoria comprimida: condición inicial + puntos de control
+    pub checkpoint_bytes: usize,
+    /// Bytes de los coeficientes del modelo
+    pub model_bytes: usize,
+}
+
+/// Compresión basada en atractor con configuración completa
+pub fn attractor_compress_with_config(
+    vectors: &[Vec<f32>],
+    config: &AttractorConfig,
+) -> Result<Vec<u8>, CompressionError> {
+    compress_nested(vectors, |view| attractor_compress_view_with_config(view, config))
+}
+
+/// Igual que `attractor_compress_with_components`, sobre una vista plana
+/// (sin copiar los datos a una matriz intermedia)
+pub fn attractor_compress_view<T: Element>(
+    vectors: VectorsView<'_, T>,
+    n_components: usize,
+) -> Result<Vec<u8>, CompressionError> {
+    let config = AttractorConfig { components: ComponentCount::Fixed(n_components), ..AttractorConfig::default() };
+    attractor_compress_view_with_config(vectors, &config)
+}
+
+/// Compresión basada en atractor sobre una vista plana
+///
+/// La media se guarda en el tipo de elemento de la entrada (f16/bf16 ocupan
+/// la mitad que f32). La varianza explicada por las componentes retenidas
+/// queda en los parámetros del contenedor (ver `attractor_explained_variance`).
+///
+/// Con `error_bound`, la cota se garantiza al descomprimir en el mismo tipo
+/// de elemento con que se comprimió.
+pub fn attractor_compress_view_with_config<T: Element>(
+    vectors: VectorsView<'_, T>,
+    config: &AttractorConfig,
+) -> Result<Vec<u8>, CompressionError> {
+    attractor_compress_view_with_report(vectors, config).map(|(blob, _)| blob)
+}
+
+/// Igual que `attractor_compress_view_with_config`, devolviendo además los
+/// metadatos escritos en la cabecera (incluido el número de deltas saturados)
+pub fn attractor_compress_view_with_report<T: Element>(
+    vectors: VectorsView<'_, T>,
+    config: &AttractorConfig,
+) -> Result<(Vec<u8>, AttractorInfo), CompressionError> {
+    match config.quantization {
+        DeltaQuantization::Adaptive => {}
+        DeltaQuantization::TargetDistortion(d) | DeltaQuantization::Fixed(d) => {
+            if !(d.is_finite() && d > 0.0) {
+                return Err(CompressionError::InvalidParameter { name: "delta_step", value: d });
+            }
+        }
+    }
+    config.model.validate()?;
+    let error_bound = match config.error_bound {
+        Some(eps) if !(eps.is_finite() && eps > 0.0) => {
+            return Err(CompressionError::InvalidParameter { name: "error_bound", value: eps });
+        }
+        bound => bound,
+    };
+    if let Some(tolerance) = config.shadowing.filter(|&tol| !(tol.is_finite() && tol > 0.0)) {
+        return Err(CompressionError::InvalidParameter { name: "shadowing_tolerance", value: tolerance });
+    }
+
     if vectors.is_empty() {
-        return vec![];
+        let shadowing = config.shadowing.map(|tolerance| ShadowingInfo {
+            tolerance,
+            lyapunov_exponent: 0.0,
+            interval: 0,
+            checkpoints: 0,
+            checkpoint_bytes: 0,
+            model_bytes: 0,
+        });
+        let info = AttractorInfo {
+            n_components: 0,
+            explained_variance: 1.0,
+            error_bound,
+            clipped: 0,
+            steps: Vec::new(),
+            model: config.model,
+            shadowing,
+            ans: None,
+            sizes: AttractorSizes::default(),
+        };
+        let blob = container::wrap(CodecId::Attractor, T::TYPE, &encode_params(&info), 0, 0, &[])?;
+        return Ok((blob, info));
     }
 
-    let n = vectors.len();
-    let dim = vectors[0].len();
+    let n = vectors.n();
+    let dim = vectors.dim();
+
+    // 1. Vista ndarray sobre los datos originales
+    let data = ArrayView2::from_shape((n, dim), vectors.as_slice())
+        .map_err(|_| CompressionError::BadLength { field: "n", value: n })?;
+
+    // 2. PCA: base ortonormal de las k direcciones de mayor varianza
+    //    (se proyecta con la base y la media tal como se almacenan)
+    let mut pca = Pca::fit_count(data, config.components, config.solver);
+    let explained_variance = pca.explained_variance();
+    pca.round_to_f32();
+    pca.mean.mapv_inplace(|m| T::TYPE.round(m));
+    let k = pca.n_components();
 
-    // 1. Convertir a Array2 para ndarray
-    let mut data = Array2::<f64>::zeros((n, dim));
-    for i in 0..n {
-        for j in 0..dim {
-            data[[i, j]] = vectors[i][j] as f64;
+    // 3. Proyectar datos a espacio k-dimensional
+    let projected = pca.project(data);
+
+    // 4. Codificar trayectoria en espacio reducido
+    // Estrategia: residuos de predicción del modelo dinámico (con el de
+    // deltas, delta encoding), con paso por componente; en modo de sombreado,
+    // solo los puntos de control donde el modelo integrado se aleja
+    let predictor = Predictor::fit(config.model, projected.view())?;
+    let mut model = Vec::new();
+    predictor.write(&mut model);
+    let (trajectory, clipped, steps, mut shadowing) = match config.shadowing {
+        Some(tolerance) => {
+            let (lyapunov_exponent, interval) = resync_interval(&projected, &predictor, tolerance);
+            let (trajectory, checkpoints) = encode_shadowing(&projected, &predictor, tolerance, interval);
+            let shadowing = ShadowingInfo {
+                tolerance,
+                lyapunov_exponent,
+                interval,
+                checkpoints,
+                checkpoint_bytes: 0,
+                model_bytes: model.len(),
+            };
+            (trajectory, 0, vec![0.0; k], Some(shadowing))
+        }
+        None => {
+            let steps = delta_steps(&projected, &predictor, config.quantization);
+            let (trajectory, clipped) = encode_trajectory(&projected, &steps, &predictor);
+            (trajectory, clipped, steps, None)
         }
+    };
+
+    // 5. Comprimir trayectoria con GZIP (o ANS)
+    let mut compressed_trajectory = gzip_encode(&trajectory, Compression::best())?;
+    let mut ans = None;
+    if let Some(shadowing) = shadowing.as_mut() {
+        shadowing.checkpoint_bytes = compressed_trajectory.len();
+    } else if config.latent_coder == LatentCoder::Ans {
+        let (section, bits_per_component) = ans_encode_trajectory(&trajectory, k, predictor.warmup().min(n))?;
+        ans = Some(LatentAnsInfo { bits_per_component, ans_bytes: section.len(), gzip_bytes: compressed_trajectory.len() });
+        compressed_trajectory = section;
     }
 
-    // 2. Centrar datos (restar media)
-    let mean: Array1<f64> = data.mean_axis(Axis(0)).unwrap();
-    let centered = &data - &mean.view().insert_axis(Axis(0));
+    // El informe refleja los valores tal como quedan en la cabecera
+    let explained_variance = explained_variance as f32 as f64;
+    let mut info = AttractorInfo {
+        n_components: k,
+        explained_variance,
+        error_bound,
+        clipped,
+        steps,
+        model: config.model,
+        shadowing,
+        ans,
+        sizes: AttractorSizes::default(),
+    };
 
-    // 3. SVD para PCA (simplificado: usar covarianza + eigenvectores)
-    // Por simplicidad, usamos aproximación: tomar primeras n_components dimensiones
-    // con mayor varianza
+    // 6. Serializar resultado (k, varianza explicada y cota de error van en los parámetros)
+    let mut payload = Vec::new();
 
-    // Calcular varianza por dimensión
-    let mut variances: Vec<(usize, f64)> = (0..dim)
-        .map(|j| {
-            let col = centered.column(j);
-            let variance = col.iter().map(|&x| x * x).sum::<f64>() / (n as f64);
-            (j, variance)
-        })
-        .collect();
+    // Media (para descentrar), en el tipo de elemento original
+    for &val in pca.mean.iter() {
+        T::TYPE.write(val, &mut payload);
+    }
+
+    // Componentes principales (k × dim, float32)
+    for &val in pca.components.iter() {
+        payload.extend(&(val as f32).to_le_bytes());
+    }
 
-    // Ordenar por varianza descendente
-    variances.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap());
+    // Coeficientes del modelo de trayectoria
+    payload.extend(&model);
 
-    // Seleccionar top-k componentes
-    let k = n_components.min(dim).min(50); // Máximo 50 componentes
-    let selected_dims: Vec<usize> = variances.iter().take(k).map(|(idx, _)| *idx).collect();
+    // Trayectoria comprimida (con su longitud)
+    payload.extend(&(compressed_trajectory.len() as u32).to_le_bytes());
+    payload.extend(&compressed_trajectory);
 
-    // 4. Proyectar datos a espacio k-dimensional
-    let mut projected = Array2::<f64>::zeros((n, k));
-    for i in 0..n {
-        for (j, &dim_idx) in selected_dims.iter().enumerate() {
-            projected[[i, j]] = centered[[i, dim_idx]];
+    // 7. Capa de residuos: el codificador reconstruye exactamente lo que verá
+    //    el decodificador y cuantiza x - x̂ con paso ≤ 2·eps
+    if let Some(eps) = error_bound {
+        let decoded = decode_latent(&trajectory, n, &info, &predictor)?;
+        let (mean, components) = (pca.mean.as_slice().expect("contiguous"), pca.components.as_slice().expect("contiguous"));
+        let reconstruct = |i: usize, row: &mut [f64]| reconstruct_row(mean, components, decoded.row(i).iter().copied(), row);
+        payload.extend(encode_residuals(data, reconstruct, eps)?);
+    }
+
+    let basis = dim * T::TYPE.size() + k * dim * 4;
+    let trajectory = compressed_trajectory.len();
+    let residuals = payload.len() - (basis + model.len() + 4 + trajectory);
+    info.sizes = AttractorSizes { basis, model: model.len(), trajectory, residuals };
+
+    let blob = container::wrap(CodecId::Attractor, T::TYPE, &encode_params(&info), n, dim, &payload)?;
+    Ok((blob, info))
+}
+
+/// Parámetros del contenedor: k (u32) + varianza explicada (f32) + cota de
+/// error (f64, 0 si no hay capa de residuos) + residuos saturados (u32) +
+/// pasos de cuantización (f32 × k) + modelo (u8 + sus parámetros, ver
+/// `TrajectoryModel`) + secciones opcionales
+fn encode_params(info: &AttractorInfo) -> Vec<u8> {
+    let mut params = (info.n_components as u32).to_le_bytes().to_vec();
+    params.extend(&(info.explained_variance as f32).to_le_bytes());
+    params.extend(&info.error_bound.unwrap_or(0.0).to_le_bytes());
+    params.extend(&(info.clipped.min(u32::MAX as usize) as u32).to_le_bytes());
+    for step in &info.steps {
+        params.extend(&step.to_le_bytes());
+    }
+    info.model.write(&mut params);
+    if let Some(shadowing) = &info.shadowing {
+        params.push(SECTION_SHADOWING);
+        params.extend(&shadowing.tolerance.to_le_bytes());
+        params.extend(&(shadowing.lyapunov_exponent as f32).to_le_bytes());
+        for count in [shadowing.interval, shadowing.checkpoints, shadowing.checkpoint_bytes, shadowing.model_bytes] {
+            params.extend(&(count.min(u32::MAX as usize) as u32).to_le_bytes());
+        }
+    }
+    if let Some(ans) = &info.ans {
+        params.push(SECTION_ANS);
+        params.extend(&(ans.ans_bytes.min(u32::MAX as usize) as u32).to_le_bytes());
+        params.extend(&(ans.gzip_bytes.min(u32::MAX as usize) as u32).to_le_bytes());
+        for bits in &ans.bits_per_component {
+            params.extend(&bits.to_le_bytes());
         }
     }
+    params
+}
 
-    // 5. Codificar trayectoria en espacio reducido
-    // Estrategia: Delta encoding en espacio reducido
-    let mut trajectory = Vec::new();
+/// Etiquetas de las secciones opcionales de los parámetros (etiqueta u8 +
+/// contenido), una por modo
+///
+/// Sombreado: tolerancia (f64) + exponente de Lyapunov (f32) + intervalo,
+/// puntos de control, bytes de puntos de control y bytes del modelo (u32
+/// cada uno). La trayectoria no guarda residuos, ver `encode_shadowing`.
+const SECTION_SHADOWING: u8 = 1;
+/// ANS: bytes con ANS (u32) + bytes con GZIP (u32) + bits por residuo de
+/// cada componente (f32 × k). La trayectoria no pasa por GZIP, ver
+/// `ans_encode_trajectory`.
+const SECTION_ANS: u8 = 2;
 
-    // Primer punto completo
-    for j in 0..k {
-        trajectory.extend(&(projected[[0, j]] as f32).to_le_bytes());
+fn decode_params(params: &[u8]) -> Result<AttractorInfo, CompressionError> {
+    let mut params = ByteReader::header(params);
+    let n_components = params.read_len()?;
+    let explained_variance = params.read_f32()? as f64;
+    let error_bound = Some(params.read_f64()?).filter(|&eps| eps > 0.0);
+    let clipped = params.read_len()?;
+    let steps = params.read_f32_vec(n_components)?;
+    let model = TrajectoryModel::read(&mut params)?;
+    let (mut shadowing, mut ans) = (None, None);
+    while params.remaining() > 0 {
+        match params.read_u8()? {
+            SECTION_SHADOWING => {
+                shadowing = Some(ShadowingInfo {
+                    tolerance: params.read_f64()?,
+                    lyapunov_exponent: params.read_f32()? as f64,
+                    interval: params.read_len()?,
+                    checkpoints: params.read_len()?,
+                    checkpoint_bytes: params.read_len()?,
+                    model_bytes: params.read_len()?,
+                });
+            }
+            SECTION_ANS => {
+                ans = Some(LatentAnsInfo {
+                    ans_bytes: params.read_len()?,
+                    gzip_bytes: params.read_len()?,
+                    bits_per_component: params.read_f32_vec(n_components)?,
+                });
+            }
+            tag => return Err(CompressionError::BadLength { field: "section", value: tag as usize }),
+        }
     }
+    let sizes = AttractorSizes::default();
+    Ok(AttractorInfo { n_components, explained_variance, error_bound, clipped, steps, model, shadowing, ans, sizes })
+}
 
-    // Deltas subsiguientes (cuantizados a int16 para compresión)
-    for i in 1..n {
+/// Paso de cuantización de cada componente latente (redondeado a f32, tal
+/// como se guarda en la cabecera)
+///
+/// La resolución sigue a la escala de los deltas, no a la de los residuos:
+/// un mejor predictor deja residuos más pequeños con el mismo paso, y eso es
+/// lo que ahorra bits.
+pub(crate) fn delta_steps(projected: &Array2<f64>, predictor: &Predictor, quantization: DeltaQuantization) -> Vec<f32> {
+    let n = projected.nrows();
+    let residuals = predictor.residuals(projected.view());
+    projected
+        .columns()
+        .into_iter()
+        .zip(residuals.columns())
+        .map(|(z, residuals)| {
+            let step = match quantization {
+                DeltaQuantization::Fixed(step) => step,
+                DeltaQuantization::TargetDistortion(d) => d * 12f64.sqrt(),
+                DeltaQuantization::Adaptive => {
+                    let sum_sq = z.windows(2).into_iter().map(|w| (w[1] - w[0]).powi(2)).sum::<f64>();
+                    let sigma = (sum_sq / (n.max(2) - 1) as f64).sqrt();
+                    let max = residuals.iter().fold(0.0f64, |m, r| m.max(r.abs()));
+                    (sigma / ADAPTIVE_LEVELS).max(max / MAX_QUANTIZED_DELTA)
+                }
+            } as f32;
+            // Componente constante (o paso fuera de rango): cualquier paso sirve
+            if step.is_normal() { step } else { 1.0 }
+        })
+        .collect()
+}
+
+/// Trayectoria: primeros puntos (float32, tantos como el orden del modelo) y
+/// residuos de predicción int16 en lazo cerrado: se predice desde los
+/// puntos reconstruidos, no desde los originales, así el error por paso
+/// queda acotado sin importar la longitud. Devuelve además cuántos residuos
+/// se saturaron al rango de int16.
+pub(crate) fn encode_trajectory(projected: &Array2<f64>, steps: &[f32], predictor: &Predictor) -> (Vec<u8>, usize) {
+    let (n, k) = projected.dim();
+    let warmup = predictor.warmup().min(n);
+    let mut trajectory = Vec::new();
+    let mut reconstructed = Array2::<f64>::zeros((n, k));
+
+    // Primeros puntos completos
+    for t in 0..warmup {
         for j in 0..k {
-            let delta = projected[[i, j]] - projected[[i - 1, j]];
-            // Cuantizar a int16
-            let quantized = (delta * 1000.0).clamp(-32768.0, 32767.0) as i16;
+            let z = projected[[t, j]] as f32;
+            trajectory.extend(&z.to_le_bytes());
+            reconstructed[[t, j]] = z as f64;
+        }
+    }
+
+    let mut clipped = 0;
+    let mut prediction = vec![0.0f64; k];
+    let mut state = predictor.state();
+    for t in warmup..n {
+        predictor.predict(&mut state, reconstructed.view(), t, &mut prediction);
+        for (j, (&p, &step)) in prediction.iter().zip(steps).enumerate() {
+            let step = step as f64;
+            let scaled = ((projected[[t, j]] - p) / step).round();
+            if !(-32768.0..=32767.0).contains(&scaled) {
+                clipped += 1;
+            }
+            let quantized = scaled.clamp(-32768.0, 32767.0) as i16;
             trajectory.extend(&quantized.to_le_bytes());
+            reconstructed[[t, j]] = p + quantized as f64 * step;
         }
     }
 
-    // 6. Comprimir trayectoria con GZIP
-    let mut encoder = GzEncoder::new(Vec::new(), Compression::best());
-    encoder.write_all(&trajectory).unwrap();
-    let compressed_trajectory = encoder.finish().unwrap();
+    (trajectory, clipped)
+}
 
-    // 7. Serializar resultado
-    let mut result = Vec::new();
+/// Rango de los residuos cuantizados (int16)
+const RESIDUAL_RANGE: std::ops::RangeInclusive<i32> = i16::MIN as i32..=i16::MAX as i32;
 
-    // Metadata
-    result.extend(&(n as u32).to_le_bytes());
-    result.extend(&(dim as u32).to_le_bytes());
-    result.extend(&(k as u32).to_le_bytes());
+/// Codifica con ANS los residuos int16 de una trayectoria (tal como la
+/// escribe `encode_trajectory`): primeros puntos (float32, sin cambios) +
+/// Laplace de cada componente (posición y escala, f32) + palabras u32
+///
+/// La Laplace de cada componente es la de máxima verosimilitud: posición en
+/// la mediana y escala igual a la desviación absoluta media respecto a ella.
+/// Devuelve además los bits por residuo de cada componente con su modelo.
+fn ans_encode_trajectory(trajectory: &[u8], k: usize, warmup: usize) -> Result<(Vec<u8>, Vec<f32>), CompressionError> {
+    let (head, body) = trajectory.split_at((4 * k * warmup).min(trajectory.len()));
+    let residuals: Vec<i32> = body.chunks_exact(2).map(|q| i16::from_le_bytes([q[0], q[1]]) as i32).collect();
 
-    // Media (para descentrar)
-    for &val in mean.iter() {
-        result.extend(&(val as f32).to_le_bytes());
+    let mut section = head.to_vec();
+    let mut laplace = Vec::with_capacity(k);
+    for j in 0..k {
+        let mut column: Vec<i32> = residuals.iter().skip(j).step_by(k).copied().collect();
+        column.sort_unstable();
+        let location = column.get(column.len() / 2).copied().unwrap_or(0) as f32;
+        let deviation = column.iter().map(|&q| (q as f64 - location as f64).abs()).sum::<f64>() / column.len().max(1) as f64;
+        let scale = (deviation as f32).max(MIN_LAPLACE_SCALE);
+        section.extend(&location.to_le_bytes());
+        section.extend(&scale.to_le_bytes());
+        laplace.push((location, scale));
     }
 
-    // Índices de dimensiones seleccionadas
-    for &idx in &selected_dims {
-        result.extend(&(idx as u32).to_le_bytes());
+    let quantizer = DefaultLeakyQuantizer::new(RESIDUAL_RANGE);
+    let models: Vec<_> = laplace.iter().map(|&(m, b)| quantizer.quantize(Laplace::new(m as f64, b as f64))).collect();
+
+    // ANS es una pila: se codifica del último al primero
+    let mut coder = DefaultAnsCoder::new();
+    let mut bits = vec![0.0f64; k];
+    for (i, &q) in residuals.iter().enumerate().rev() {
+        let model = &models[i % k];
+        if let Some((_, probability)) = model.left_cumulative_and_probability(q) {
+            bits[i % k] -= (probability.get() as f64 / (1u64 << 24) as f64).log2();
+        }
+        coder.encode_symbol(q, model).map_err(CompressionError::ans)?;
+    }
+    for word in coder.into_compressed().map_err(CompressionError::ans)? {
+        section.extend(&word.to_le_bytes());
     }
 
-    // Trayectoria comprimida
-    result.extend(&(compressed_trajectory.len() as u32).to_le_bytes());
-    result.extend(&compressed_trajectory);
+    let per_component = (residuals.len() / k.max(1)).max(1) as f64;
+    Ok((section, bits.iter().map(|&b| (b / per_component) as f32).collect()))
+}
+
+/// Inversa de `ans_encode_trajectory`: reconstruye los bytes de la
+/// trayectoria (primeros puntos + residuos int16)
+fn ans_decode_trajectory(section: &[u8], n: usize, k: usize, warmup: usize) -> Result<Vec<u8>, CompressionError> {
+    let mut reader = ByteReader::payload(section);
+    let mut trajectory = reader.take(4 * k * warmup)?.to_vec();
+    let laplace = reader.read_f32_vec(2 * k)?;
+    if laplace.chunks_exact(2).any(|p| !(p[0].is_finite() && p[1].is_finite() && p[1] > 0.0)) {
+        return Err(CompressionError::ans("modelo de Laplace no válido"));
+    }
+    let quantizer = DefaultLeakyQuantizer::new(RESIDUAL_RANGE);
+    let models: Vec<_> = laplace.chunks_exact(2).map(|p| quantizer.quantize(Laplace::new(p[0] as f64, p[1] as f64))).collect();
 
-    result
+    let words: Vec<u32> = reader.rest().chunks_exact(4).map(|w| u32::from_le_bytes([w[0], w[1], w[2], w[3]])).collect();
+    let mut coder = DefaultAnsCoder::from_compressed(words).map_err(|_| CompressionError::ans("trayectoria ANS no válida"))?;
+    let count = (n - warmup).checked_mul(k).ok_or(CompressionError::BadLength { field: "trajectory", value: n })?;
+    container::check_decoded_len("trajectory", count, section.len())?;
+    trajectory.reserve(2 * count);
+    for i in 0..count {
+        let q = coder.decode_symbol(&models[i % k]).map_err(CompressionError::ans)?;
+        trajectory.extend(&(q as i16).to_le_bytes());
+    }
+    Ok(trajectory)
 }
 
-/// Descompresión basada en atractor
-pub fn attractor_decompress(compressed: &[u8]) -> Vec<Vec<f32>> {
-    if compressed.len() < 12 {
-        return vec![];
-    }
-
-    let mut offset = 0;
-
-    // Leer metadata
-    let n = u32::from_le_bytes([
-        compressed[offset],
-        compressed[offset + 1],
-        compressed[offset + 2],
-        compressed[offset + 3],
-    ]) as usize;
-    offset += 4;
-
-    let dim = u32::from_le_bytes([
-        compressed[offset],
-        compressed[offset + 1],
-        compressed[offset + 2],
-        compressed[offset + 3],
-    ]) as usize;
-    offset += 4;
-
-    let k = u32::from_le_bytes([
-        compressed[offset],
-        compressed[offset + 1],
-        compressed[offset + 2],
-        compressed[offset + 3],
-    ]) as usize;
-    offset += 4;
+/// Escala mínima de la Laplace (componente con todos los residuos iguales)
+const MIN_LAPLACE_SCALE: f32 = 1e-3;
 
-    // Leer media
-    let mut mean = vec![0.0f32; dim];
-    for i in 0..dim {
-        mean[i] = f32::from_le_bytes([
-            compressed[offset],
-            compressed[offset + 1],
-            compressed[offset + 2],
-            compressed[offset + 3],
-        ]);
-        offset += 4;
-    }
-
-    // Leer índices de dimensiones seleccionadas
-    let mut selected_dims = vec![0usize; k];
-    for i in 0..k {
-        selected_dims[i] = u32::from_le_bytes([
-            compressed[offset],
-            compressed[offset + 1],
-            compressed[offset + 2],
-            compressed[offset + 3],
-        ]) as usize;
-        offset += 4;
-    }
-
-    // Leer trayectoria comprimida
-    let trajectory_size = u32::from_le_bytes([
-        compressed[offset],
-        compressed[offset + 1],
-        compressed[offset + 2],
-        compressed[offset + 3],
-    ]) as usize;
-    offset += 4;
-
-    let compressed_trajectory = &compressed[offset..offset + trajectory_size];
-
-    // Descomprimir trayectoria
-    let mut decoder = GzDecoder::new(compressed_trajectory);
-    let mut trajectory = Vec::new();
-    decoder.read_to_end(&mut trajectory).unwrap();
+/// Residuos cuantizados: paso (f64) + GZIP(varints zigzag, fila mayor)
+/// sobre la reconstrucción `reconstruct(i, fila)` que verá el decodificador
+///
+/// El paso deja margen para el redondeo final al tipo de elemento: con
+/// `|y - x| <= paso/2` y `|redondeo(y) - y| <= margen`, el error total no
+/// supera eps. Si el tipo es demasiado grueso para ese margen se usa
+/// paso = eps, que también basta: x es representable, así que
+/// `|redondeo(y) - y| <= |x - y|`.
+pub(crate) fn encode_residuals<T: Element>(
+    data: ArrayView2<'_, T>,
+    reconstruct: impl Fn(usize, &mut [f64]),
+    eps: f64,
+) -> Result<Vec<u8>, CompressionError> {
+    let magnitude = data.iter().fold(0.0f64, |m, x| m.max(x.to_f64().abs())) + eps;
+    let margin = T::TYPE.rounding_bound(magnitude);
+    let step = if margin < eps / 2.0 { 2.0 * (eps - margin) } else { eps };
+
+    let mut residuals = Vec::new();
+    let mut row = vec![0.0f64; data.ncols()];
+    for (i, x) in data.rows().into_iter().enumerate() {
+        reconstruct(i, &mut row);
+        for (&x, &r) in x.iter().zip(&row) {
+            write_varint(((x.to_f64() - r) / step).round() as i64, &mut residuals);
+        }
+    }
+
+    let mut section = step.to_le_bytes().to_vec();
+    section.extend(gzip_encode(&residuals, Compression::best())?);
+    Ok(section)
+}
+
+/// Decodifica la trayectoria: primeros puntos (float32) + residuos int16
+/// (× paso) sobre la predicción del modelo
+pub(crate) fn decode_trajectory(
+    trajectory: &[u8],
+    n: usize,
+    steps: &[f32],
+    predictor: &Predictor,
+) -> Result<Array2<f64>, CompressionError> {
+    let k = steps.len();
+    let warmup = predictor.warmup().min(n);
+    let mut traj = ByteReader::payload(trajectory);
+
+    // Primeros puntos (float32) + residuos int16 por cada paso
+    check_count("n", n - warmup, 2 * k, trajectory.len().saturating_sub(4 * k * warmup))?;
 
-    // Decodificar trayectoria
     let mut projected = Array2::<f64>::zeros((n, k));
-    let mut traj_offset = 0;
 
-    // Primer punto
-    for j in 0..k {
-        projected[[0, j]] = f32::from_le_bytes([
-            trajectory[traj_offset],
-            trajectory[traj_offset + 1],
-            trajectory[traj_offset + 2],
-            trajectory[traj_offset + 3],
-        ]) as f64;
-        traj_offset += 4;
+    // Primeros puntos
+    for t in 0..warmup {
+        for j in 0..k {
+            projected[[t, j]] = traj.read_f32()? as f64;
+        }
+    }
+
+    // Residuos sobre la predicción desde lo ya reconstruido
+    let mut prediction = vec![0.0f64; k];
+    let mut state = predictor.state();
+    for t in warmup..n {
+        predictor.predict(&mut state, projected.view(), t, &mut prediction);
+        for (j, (&p, &step)) in prediction.iter().zip(steps).enumerate() {
+            let quantized = traj.read_i16()?;
+            projected[[t, j]] = p + quantized as f64 * step as f64;
+        }
+    }
+
+    Ok(projected)
+}
+
+/// Trayectoria latente según el modo declarado en la cabecera
+fn decode_latent(
+    trajectory: &[u8],
+    n: usize,
+    info: &AttractorInfo,
+    predictor: &Predictor,
+) -> Result<Array2<f64>, CompressionError> {
+    match info.shadowing {
+        Some(_) => decode_shadowing(trajectory, n, info.n_components, predictor),
+        None => decode_trajectory(trajectory, n, &info.steps, predictor),
     }
+}
+
+/// Exponente de Lyapunov de la trayectoria latente (redondeado a f32, tal
+/// como se guarda) e intervalo de resincronización
+///
+/// Un error inicial δ₀ (el RMS del error de predicción a un paso) crece
+/// como δ₀·e^{λt}, así que alcanza la tolerancia tras ln(tol/δ₀)/λ pasos.
+/// Sin divergencia medible (λ ≤ 0) o con δ₀ ya por encima de la tolerancia
+/// solo se resincroniza cuando la predicción se aleja.
+fn resync_interval(projected: &Array2<f64>, predictor: &Predictor, tolerance: f64) -> (f64, usize) {
+    let rows: Vec<Vec<f32>> = projected.rows().into_iter().map(|z| z.iter().map(|&x| x as f32).collect()).collect();
+    let lyapunov = max_lyapunov_exponent(&rows) as f32 as f64;
+
+    let residuals = predictor.residuals(projected.view());
+    let delta0 = (residuals.iter().map(|r| r * r).sum::<f64>() / residuals.len().max(1) as f64).sqrt();
+    let interval = if lyapunov > 0.0 && delta0 > 0.0 && tolerance > delta0 {
+        ((tolerance / delta0).ln() / lyapunov).ceil().min(u32::MAX as f64) as usize
+    } else {
+        0
+    };
+    (lyapunov, interval)
+}
+
+/// Trayectoria en modo de sombreado: primeros puntos (float32) + número de
+/// puntos de control (varint) + por cada uno, distancia al anterior (varint)
+/// y el punto (float32). Devuelve además cuántos puntos de control hubo.
+///
+/// El predictor avanza sobre la trayectoria reconstruida, igual que en el
+/// decodificador; un punto de control sustituye a la predicción cuando
+/// alguna componente se aleja más de `tolerance` o pasan `interval` pasos
+/// desde el último punto guardado.
+pub(crate) fn encode_shadowing(
+    projected: &Array2<f64>,
+    predictor: &Predictor,
+    tolerance: f64,
+    interval: usize,
+) -> (Vec<u8>, usize) {
+    let (n, k) = projected.dim();
+    let warmup = predictor.warmup().min(n);
+    let mut trajectory = Vec::new();
+    let mut reconstructed = Array2::<f64>::zeros((n, k));
 
-    // Deltas
-    for i in 1..n {
+    // Condición inicial
+    for t in 0..warmup {
         for j in 0..k {
-            let quantized = i16::from_le_bytes([
-                trajectory[traj_offset],
-                trajectory[traj_offset + 1],
-            ]);
-            traj_offset += 2;
-
-            let delta = (quantized as f64) / 1000.0;
-            projected[[i, j]] = projected[[i - 1, j]] + delta;
+            let z = projected[[t, j]] as f32;
+            trajectory.extend(&z.to_le_bytes());
+            reconstructed[[t, j]] = z as f64;
         }
     }
 
-    // Reconstruir vectores completos
-    let mut vectors = Vec::with_capacity(n);
+    let mut checkpoints = Vec::new();
+    let mut count = 0;
+    let mut previous = warmup;
+    let mut last_sync = warmup.saturating_sub(1);
+    let mut prediction = vec![0.0f64; k];
+    let mut state = predictor.state();
+    for t in warmup..n {
+        // La predicción se calcula siempre: los modelos con estado avanzan
+        predictor.predict(&mut state, reconstructed.view(), t, &mut prediction);
+        let diverged = prediction.iter().zip(projected.row(t)).any(|(&p, &z)| {
+            let error = (p - z).abs();
+            error > tolerance || error.is_nan()
+        });
+        if diverged || (interval > 0 && t - last_sync >= interval) {
+            write_varint((t - previous) as i64, &mut checkpoints);
+            for j in 0..k {
+                let z = projected[[t, j]] as f32;
+                checkpoints.extend(&z.to_le_bytes());
+                reconstructed[[t, j]] = z as f64;
+            }
+            count += 1;
+            previous = t;
+            last_sync = t;
+        } else {
+            reconstructed.row_mut(t).assign(&ArrayView1::from(&prediction));
+        }
+    }
+
+    write_varint(count as i64, &mut trajectory);
+    trajectory.extend(&checkpoints);
+    (trajectory, count)
+}
+
+/// Decodifica la trayectoria en modo de sombreado: integra el modelo desde
+/// la condición inicial y sustituye la predicción en cada punto de control
+pub(crate) fn decode_shadowing(
+    trajectory: &[u8],
+    n: usize,
+    k: usize,
+    predictor: &Predictor,
+) -> Result<Array2<f64>, CompressionError> {
+    let warmup = predictor.warmup().min(n);
+    let mut traj = ByteReader::payload(trajectory);
+    check_count("n", warmup, 4 * k, trajectory.len())?;
 
-    for i in 0..n {
-        let mut vec = mean.clone();
+    let mut projected = Array2::<f64>::zeros((n, k));
+    for t in 0..warmup {
+        for j in 0..k {
+            projected[[t, j]] = traj.read_f32()? as f64;
+        }
+    }
+
+    // Cada punto de control ocupa al menos un byte de distancia + k float32
+    let count = usize::try_from(traj.read_varint()?)
+        .map_err(|_| CompressionError::BadLength { field: "checkpoints", value: 0 })?;
+    check_count("checkpoints", count, 1 + 4 * k, traj.remaining())?;
 
-        // Aplicar proyección inversa (solo restaurar componentes seleccionados)
-        for (j, &dim_idx) in selected_dims.iter().enumerate() {
-            vec[dim_idx] += projected[[i, j]] as f32;
+    let mut remaining = count;
+    let mut previous = warmup;
+    let mut next_checkpoint = |traj: &mut ByteReader<'_>, remaining: usize| -> Result<Option<usize>, CompressionError> {
+        if remaining == 0 {
+            return Ok(None);
         }
+        let gap = usize::try_from(traj.read_varint()?).ok();
+        previous = gap
+            .and_then(|gap| previous.checked_add(gap))
+            .ok_or(CompressionError::BadLength { field: "checkpoints", value: count })?;
+        Ok(Some(previous))
+    };
+    let mut next = next_checkpoint(&mut traj, remaining)?;
 
-        vectors.push(vec);
+    let mut prediction = vec![0.0f64; k];
+    let mut state = predictor.state();
+    for t in warmup..n {
+        predictor.predict(&mut state, projected.view(), t, &mut prediction);
+        if next == Some(t) {
+            for j in 0..k {
+                projected[[t, j]] = traj.read_f32()? as f64;
+            }
+            remaining -= 1;
+            next = next_checkpoint(&mut traj, remaining)?;
+        } else {
+            projected.row_mut(t).assign(&ArrayView1::from(&prediction));
+        }
+    }
+    if next.is_some() {
+        return Err(CompressionError::BadLength { field: "checkpoints", value: count });
     }
 
-    vectors
+    Ok(projected)
+}
+
+/// Proyección inversa de un vector: media + Σ_j z_j · v_j
+///
+/// Compartida por codificador y decodificador para que la capa de residuos
+/// vea exactamente la misma reconstrucción.
+pub(crate) fn reconstruct_row(mean: &[f64], components: &[f64], z: impl Iterator<Item = f64>, row: &mut [f64]) {
+    row.copy_from_slice(mean);
+    for (z, component) in z.zip(components.chunks_exact(row.len())) {
+        for (r, &v) in row.iter_mut().zip(component) {
+            *r += z * v;
+        }
+    }
+}
+
+/// Metadatos de un blob de atractor (de su cabecera), con los tamaños de
+/// cada sección del contenido
+pub fn attractor_info(compressed: &[u8]) -> Result<AttractorInfo, CompressionError> {
+    let (header, payload) = container::open(compressed, CodecId::Attractor)?;
+    let mut info = decode_params(&header.params)?;
+    if header.n > 0 {
+        info.sizes = payload_sizes(&header, &info, payload)?;
+    }
+    Ok(info)
+}
+
+/// Recorre el contenido con la misma disposición que `decode_payload`
+fn payload_sizes(header: &ContainerHeader, info: &AttractorInfo, payload: &[u8]) -> Result<AttractorSizes, CompressionError> {
+    let mut reader = ByteReader::payload(payload);
+    let basis = header.dim.checked_mul(header.element.size() + 4 * info.n_components)
+        .ok_or(CompressionError::BadLength { field: "dim", value: header.dim })?;
+    reader.take(basis)?;
+    let before_model = reader.remaining();
+    Predictor::read(info.model, info.n_components, &mut reader)?;
+    let model = before_model - reader.remaining();
+    let trajectory = reader.read_len()?;
+    reader.take(trajectory)?;
+    Ok(AttractorSizes { basis, model, trajectory, residuals: reader.remaining() })
+}
+
+/// Fracción de la varianza retenida por un blob de atractor (de su cabecera)
+pub fn attractor_explained_variance(compressed: &[u8]) -> Result<f64, CompressionError> {
+    Ok(attractor_info(compressed)?.explained_variance)
+}
+
+/// Descompresión basada en atractor
+pub fn attractor_decompress(compressed: &[u8]) -> Result<Vec<Vec<f32>>, CompressionError> {
+    container::decompress_as(compressed, CodecId::Attractor)
+}
+
+pub(crate) fn decode_payload<T: Element>(
+    header: &ContainerHeader,
+    payload: &[u8],
+    out: &mut [T],
+) -> Result<(), CompressionError> {
+    let n = header.n;
+    let dim = header.dim;
+    if n == 0 {
+        return Ok(());
+    }
+    if dim == 0 {
+        return Err(CompressionError::UnsupportedDimension { dim, min: 1 });
+    }
+
+    // Parámetros: componentes retenidas, cota de error y pasos de cuantización
+    let info = decode_params(&header.params)?;
+    let k = info.n_components;
+    if k > dim {
+        return Err(CompressionError::DimensionMismatch { expected: dim, found: k });
+    }
+
+    let mut reader = ByteReader::payload(payload);
+
+    // Leer media
+    let mean = header.element.read_vec(&mut reader, dim)?;
+
+    // Leer componentes principales (k × dim)
+    let components = reader.read_f32_vec(k * dim)?;
+
+    let components: Vec<f64> = components.into_iter().map(|v| v as f64).collect();
+
+    // Coeficientes del modelo de trayectoria
+    let predictor = Predictor::read(info.model, k, &mut reader)?;
+
+    // Trayectoria comprimida
+    let trajectory_len = reader.read_len()?;
+    let trajectory = match info.ans {
+        Some(_) => ans_decode_trajectory(reader.take(trajectory_len)?, n, k, predictor.warmup().min(n))?,
+        None => gzip_decode(reader.take(trajectory_len)?)?,
+    };
+    let projected = decode_latent(&trajectory, n, &info, &predictor)?;
+
+    // Capa de residuos (si la cabecera declara una cota de error)
+    let (residual_step, residuals) = if info.error_bound.is_some() {
+        (Some(reader.read_f64()?), gzip_decode(reader.rest())?)
+    } else {
+        (None, Vec::new())
+    };
+    let mut residuals = ByteReader::payload(&residuals);
+
+    // Reconstruir vectores completos
+    let mut row = vec![0.0f64; dim];
+    for (vec, z) in out.chunks_exact_mut(dim).zip(projected.rows()) {
+        reconstruct_row(&mean, &components, z.iter().copied(), &mut row);
+        if let Some(step) = residual_step {
+            for r in row.iter_mut() {
+                *r += residuals.read_varint()? as f64 * step;
+            }
+        }
+        for (o, &x) in vec.iter_mut().zip(&row) {
+            *o = T::from_f64(x);
+        }
+    }
+
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::methods::container::read_header;
+    use crate::methods::RandomizedSvd;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    fn max_abs_error(a: &[Vec<f32>], b: &[Vec<f32>]) -> f32 {
+        a.iter().flatten().zip(b.iter().flatten()).map(|(x, y)| (x - y).abs()).fold(0.0f32, f32::max)
+    }
 
     #[test]
     fn test_attractor_compression() {
@@ -277,7 +990,7 @@ mod tests {
         }
 
         // Comprimir
-        let compressed = attractor_compress_with_components(&vectors, 10);
+        let compressed = attractor_compress_with_components(&vectors, 10).unwrap();
         let original_size = n * dim * 4;
         let ratio = original_size as f64 / compressed.len() as f64;
 
@@ -289,7 +1002,400 @@ mod tests {
         assert!(ratio > 5.0, "Ratio: {:.2}x, esperaba >5x", ratio);
 
         // Descomprimir
-        let decompressed = attractor_decompress(&compressed);
+        let decompressed = attractor_decompress(&compressed).unwrap();
         assert_eq!(decompressed.len(), vectors.len());
     }
+
+    #[test]
+    fn test_attractor_pca_on_rotated_data() {
+        // Trayectoria 3D embebida en una base rotada: la varianza se reparte
+        // entre todas las coordenadas, así que elegir coordenadas crudas falla
+        let (n, dim) = (400, 96);
+        let basis: Vec<Vec<f32>> = (0..3)
+            .map(|r| (0..dim).map(|j| ((r * dim + j) as f32 * 1.37).sin()).collect())
+            .collect();
+        let vectors: Vec<Vec<f32>> = (0..n)
+            .map(|t| {
+                let t = t as f32 * 0.02;
+                let z = [t.cos(), (2.0 * t).sin(), 0.5 * t.sin()];
+                (0..dim).map(|j| 0.1 + (0..3).map(|r| z[r] * basis[r][j]).sum::<f32>()).collect()
+            })
+            .collect();
+
+        let randomized = AttractorConfig {
+            components: ComponentCount::Fixed(3),
+            solver: PcaSolver::Randomized(RandomizedSvd::default()),
+            ..AttractorConfig::default()
+        };
+        for compressed in [
+            attractor_compress_with_components(&vectors, 3).unwrap(),
+            attractor_compress_with_config(&vectors, &randomized).unwrap(),
+        ] {
+            let decompressed = attractor_decompress(&compressed).unwrap();
+
+            let max_error = max_abs_error(&vectors, &decompressed);
+            assert!(max_error < 0.05, "max_error = {}", max_error);
+        }
+    }
+
+    #[test]
+    fn test_component_count_targets() {
+        // Senoides ortogonales sobre una base DCT ortogonal: varianzas
+        // exactamente 16:4:1:0.25, más ruido pequeño
+        let (n, dim) = (300, 32);
+        let mut rng = StdRng::seed_from_u64(7);
+        let scales = [4.0f32, 2.0, 1.0, 0.5];
+        let basis: Vec<Vec<f32>> = (0..4)
+            .map(|r| {
+                (0..dim)
+                    .map(|j| (std::f32::consts::PI * (j as f32 + 0.5) * (r + 1) as f32 / dim as f32).cos())
+                    .collect()
+            })
+            .collect();
+        let vectors: Vec<Vec<f32>> = (0..n)
+            .map(|t| {
+                let phase = 2.0 * std::f32::consts::PI * t as f32 / n as f32;
+                let z: Vec<f32> = (0..4).map(|r| scales[r] * (phase * (r + 1) as f32).sin()).collect();
+                (0..dim)
+                    .map(|j| 0.5 + (0..4).map(|r| z[r] * basis[r][j]).sum::<f32>() + 1e-3 * rng.gen::<f32>())
+                    .collect()
+            })
+            .collect();
+        let k_of = |blob: &[u8]| ByteReader::header(&read_header(blob).unwrap().0.params).read_len().unwrap();
+
+        for solver in [PcaSolver::Exact, PcaSolver::Randomized(RandomizedSvd::default())] {
+            // 16 + 4 + 1 = 21 de 21.25: tres componentes retienen ~98.8 %
+            let config = AttractorConfig { components: ComponentCount::ExplainedVariance(0.98), solver, ..AttractorConfig::default() };
+            let blob = attractor_compress_with_config(&vectors, &config).unwrap();
+            assert_eq!(k_of(&blob), 3);
+            let explained = attractor_explained_variance(&blob).unwrap();
+            assert!((0.98..1.0).contains(&explained), "explained = {}", explained);
+
+            let config = AttractorConfig { components: ComponentCount::ExplainedVariance(0.999), solver, ..AttractorConfig::default() };
+            let blob = attractor_compress_with_config(&vectors, &config).unwrap();
+            assert_eq!(k_of(&blob), 4);
+            assert!(attractor_explained_variance(&blob).unwrap() >= 0.999);
+        }
+
+        // Objetivo de pérdida coseno: más estricto, más componentes
+        let loose = AttractorConfig { components: ComponentCount::MaxCosineLoss(0.05), ..AttractorConfig::default() };
+        let strict = AttractorConfig { components: ComponentCount::MaxCosineLoss(1e-3), ..AttractorConfig::default() };
+        let k_loose = k_of(&attractor_compress_with_config(&vectors, &loose).unwrap());
+        let k_strict = k_of(&attractor_compress_with_config(&vectors, &strict).unwrap());
+        assert_eq!((k_loose, k_strict), (2, 4));
+
+        // Sin tope de 50 componentes
+        let wide: Vec<Vec<f32>> = (0..120).map(|_| (0..80).map(|_| rng.gen::<f32>()).collect()).collect();
+        assert_eq!(k_of(&attractor_compress_with_components(&wide, 70).unwrap()), 70);
+    }
+
+    #[test]
+    fn test_error_bound_is_guaranteed() {
+        // Ruido sin estructura: PCA-4 deja casi toda la señal en los residuos
+        let (n, dim) = (200, 48);
+        let mut rng = StdRng::seed_from_u64(11);
+        let data: Vec<f32> = (0..n * dim).map(|i| (i % dim) as f32 * 0.1 + rng.gen::<f32>() * 3.0 - 1.5).collect();
+        let view = VectorsView::new(&data, dim).unwrap();
+
+        let mut previous_len = 0;
+        for eps in [0.1, 0.01, 1e-4] {
+            let config = AttractorConfig {
+                components: ComponentCount::Fixed(4),
+                error_bound: Some(eps),
+                ..AttractorConfig::default()
+            };
+            let blob = attractor_compress_view_with_config(view, &config).unwrap();
+            let mut out = vec![0.0f32; n * dim];
+            crate::methods::decompress_into(&blob, &mut out).unwrap();
+
+            let max_error = data.iter().zip(&out).map(|(a, b)| (a - b).abs() as f64).fold(0.0, f64::max);
+            assert!(max_error <= eps, "eps {}: max_error {}", eps, max_error);
+
+            // Cota más estricta, residuos más caros
+            assert!(blob.len() > previous_len);
+            previous_len = blob.len();
+        }
+
+        // También en f16 (cota por debajo de su ulp en parte del rango) y f64
+        let half: Vec<half::f16> = data.iter().map(|&x| half::f16::from_f32(x)).collect();
+        let wide: Vec<f64> = data.iter().map(|&x| x as f64 + 1e-9).collect();
+        let config = AttractorConfig { error_bound: Some(1e-3), ..AttractorConfig::default() };
+        let blob = attractor_compress_view_with_config(VectorsView::new(&half, dim).unwrap(), &config).unwrap();
+        let mut out = vec![half::f16::ZERO; n * dim];
+        crate::methods::decompress_into(&blob, &mut out).unwrap();
+        assert!(half.iter().zip(&out).all(|(a, b)| (a.to_f64() - b.to_f64()).abs() <= 1e-3));
+
+        let blob = attractor_compress_view_with_config(VectorsView::new(&wide, dim).unwrap(), &config).unwrap();
+        let mut out = vec![0.0f64; n * dim];
+        crate::methods::decompress_into(&blob, &mut out).unwrap();
+        assert!(wide.iter().zip(&out).all(|(a, b)| (a - b).abs() <= 1e-3));
+
+        let config = AttractorConfig { error_bound: Some(0.0), ..AttractorConfig::default() };
+        assert!(matches!(
+            attractor_compress_view_with_config(view, &config),
+            Err(CompressionError::InvalidParameter { name: "error_bound", .. })
+        ));
+    }
+
+    #[test]
+    fn test_trajectory_tail_error_bounded() {
+        // Órbita larga en 2D rotada a 64D: la trayectoria reconstruida no debe
+        // derivar, el error en la cola es el de un solo paso de cuantización
+        let (n, dim) = (6000, 64);
+        let basis: Vec<Vec<f32>> = (0..2)
+            .map(|r| (0..dim).map(|j| ((r * dim + j) as f32 * 0.37).sin() * 0.2).collect())
+            .collect();
+        let vectors: Vec<Vec<f32>> = (0..n)
+            .map(|t| {
+                let t = t as f32 * 0.013;
+                let z = [3.0 * t.cos(), 2.0 * (1.7 * t).sin()];
+                (0..dim).map(|j| z[0] * basis[0][j] + z[1] * basis[1][j]).collect()
+            })
+            .collect();
+
+        let compressed = attractor_compress_with_components(&vectors, 2).unwrap();
+        let decompressed = attractor_decompress(&compressed).unwrap();
+
+        // Error de la trayectoria ≤ paso/2 por componente; en cada coordenada
+        // ≤ Σ_j paso_j/2 · |v_j|, con |v_j| ≤ 1
+        let info = attractor_info(&compressed).unwrap();
+        assert_eq!(info.clipped, 0);
+        let bound = info.steps.iter().map(|&s| s / 2.0).sum::<f32>();
+        let tail_error = max_abs_error(&vectors[n - 200..], &decompressed[n - 200..]);
+        assert!(tail_error <= bound + 1e-5, "error en la cola: {} > {}", tail_error, bound);
+    }
+
+    #[test]
+    fn test_var_model_beats_deltas_on_oscillation() {
+        // El círculo 2D de `test_attractor_compression`: con VAR(2) la
+        // trayectoria es casi exactamente predecible y sus residuos son ~0
+        let (n, dim) = (1000, 768);
+        let mut rng = StdRng::seed_from_u64(3);
+        let projection: Vec<[f32; 2]> = (0..dim).map(|_| [rng.gen(), rng.gen()]).collect();
+        let vectors: Vec<Vec<f32>> = (0..n)
+            .map(|t| {
+                let theta = t as f32 * 0.01;
+                projection.iter().map(|p| theta.cos() * p[0] + theta.sin() * p[1]).collect()
+            })
+            .collect();
+
+        let deltas = AttractorConfig { components: ComponentCount::Fixed(2), ..AttractorConfig::default() };
+        let var = AttractorConfig { model: TrajectoryModel::Var { order: 2 }, ..deltas };
+        let trajectory_len = |blob: &[u8]| attractor_info(blob).unwrap().sizes.trajectory;
+
+        let delta_blob = attractor_compress_with_config(&vectors, &deltas).unwrap();
+        let var_blob = attractor_compress_with_config(&vectors, &var).unwrap();
+        let sizes = attractor_info(&var_blob).unwrap().sizes;
+        assert_eq!(attractor_info(&var_blob).unwrap().model, TrajectoryModel::Var { order: 2 });
+        // Coeficientes del VAR(2): (1 + p·k) × k float32
+        assert_eq!((sizes.basis, sizes.model, sizes.residuals), (3 * dim * 4, (1 + 2 * 2) * 2 * 4, 0));
+        assert_eq!(sizes.basis + sizes.model + 4 + sizes.trajectory, read_header(&var_blob).unwrap().1.len());
+        println!("trayectoria: deltas {} bytes, VAR(2) {} bytes", trajectory_len(&delta_blob), trajectory_len(&var_blob));
+        assert!(trajectory_len(&var_blob) * 4 < trajectory_len(&delta_blob));
+
+        // Mismo paso de cuantización: misma precisión
+        for blob in [&delta_blob, &var_blob] {
+            let steps = attractor_info(blob).unwrap().steps;
+            let bound = steps.iter().map(|&s| s / 2.0).sum::<f32>() + 1e-5;
+            let decompressed = attractor_decompress(blob).unwrap();
+            let max_error = max_abs_error(&vectors, &decompressed);
+            assert!(max_error <= bound, "max_error {} > {}", max_error, bound);
+        }
+
+        let config = AttractorConfig { model: TrajectoryModel::Var { order: 0 }, ..deltas };
+        assert!(attractor_compress_with_config(&vectors, &config).is_err());
+    }
+
+    #[test]
+    fn test_sindy_model_on_lifted_lorenz() {
+        // Lorenz proyectado a 768D: PCA-3 recupera el espacio del atractor (a
+        // una transformación afín, que deja el mapa polinómico de grado 2)
+        let (n, dim) = (3000, 768);
+        let vectors = crate::datasets::generate_lorenz_embedding(n, dim, 0.01, &mut StdRng::seed_from_u64(13));
+
+        let base = AttractorConfig { components: ComponentCount::Fixed(3), ..AttractorConfig::default() };
+        let models = [
+            TrajectoryModel::Delta,
+            TrajectoryModel::Var { order: 1 },
+            TrajectoryModel::Sindy { degree: 2, threshold: crate::methods::dynamics::DEFAULT_SINDY_THRESHOLD },
+        ];
+        let sizes: Vec<usize> = models
+            .iter()
+            .map(|&model| {
+                let blob = attractor_compress_with_config(&vectors, &AttractorConfig { model, ..base }).unwrap();
+                let info = attractor_info(&blob).unwrap();
+                assert_eq!((info.model, info.clipped), (model, 0));
+
+                let bound = info.steps.iter().map(|&s| s / 2.0).sum::<f32>() + 1e-4;
+                let decompressed = attractor_decompress(&blob).unwrap();
+                let max_error = max_abs_error(&vectors, &decompressed);
+                assert!(max_error <= bound, "{:?}: max_error {} > {}", model, max_error, bound);
+                blob.len()
+            })
+            .collect();
+
+        println!("deltas {} bytes, VAR(1) {} bytes, SINDy {} bytes", sizes[0], sizes[1], sizes[2]);
+        assert!(sizes[2] < sizes[1] && sizes[2] < sizes[0]);
+    }
+
+    #[test]
+    fn test_learned_models_beat_var_on_lifted_lorenz() {
+        // Muestreo grueso (dt = 0.05): la red de eco compensa sus ~1.2 KB de
+        // lectura con residuos mucho menores que los del VAR(1); los análogos
+        // repiten la búsqueda de vecinos en el decodificador (cero bytes de modelo)
+        let (n, dim) = (3000, 256);
+        let vectors = crate::datasets::generate_lorenz_embedding(n, dim, 0.05, &mut StdRng::seed_from_u64(17));
+
+        let base = AttractorConfig { components: ComponentCount::Fixed(3), ..AttractorConfig::default() };
+        let var_config = AttractorConfig { model: TrajectoryModel::Var { order: 1 }, ..base };
+        let var = attractor_compress_with_config(&vectors, &var_config).unwrap();
+
+        for model in [crate::methods::dynamics::DEFAULT_ECHO_STATE, crate::methods::dynamics::DEFAULT_ANALOGUES] {
+            let blob = attractor_compress_with_config(&vectors, &AttractorConfig { model, ..base }).unwrap();
+            println!("{:?}: {} bytes vs VAR(1) {} bytes", model, blob.len(), var.len());
+            assert!(blob.len() < var.len());
+
+            let info = attractor_info(&blob).unwrap();
+            assert_eq!((info.model, info.clipped), (model, 0));
+            let bound = info.steps.iter().map(|&s| s / 2.0).sum::<f32>() + 1e-4;
+            let max_error = max_abs_error(&vectors, &attractor_decompress(&blob).unwrap());
+            assert!(max_error <= bound, "{:?}: max_error {} > {}", model, max_error, bound);
+        }
+    }
+
+    #[test]
+    fn test_shadowing_checkpoints_on_lifted_lorenz() {
+        // SINDy sigue la órbita muchos pasos antes de alejarse; el predictor
+        // de deltas necesita un punto de control casi en cada paso
+        let (n, dim, tolerance) = (3000, 128, 0.05);
+        let vectors = crate::datasets::generate_lorenz_embedding(n, dim, 0.01, &mut StdRng::seed_from_u64(19));
+
+        let base = AttractorConfig {
+            components: ComponentCount::Fixed(3),
+            shadowing: Some(tolerance),
+            ..AttractorConfig::default()
+        };
+        let sindy_config = AttractorConfig { model: TrajectoryModel::Sindy { degree: 2, threshold: 1e-3 }, ..base };
+        let mut reports = Vec::new();
+        for config in [base, sindy_config] {
+            let view_data: Vec<f32> = vectors.iter().flatten().copied().collect();
+            let view = VectorsView::new(&view_data, dim).unwrap();
+            let (blob, info) = attractor_compress_view_with_report(view, &config).unwrap();
+            assert_eq!(attractor_info(&blob).unwrap(), info);
+
+            let shadowing = info.shadowing.clone().unwrap();
+            println!(
+                "{:?}: {} puntos de control ({} bytes), modelo {} bytes, λ = {:.4}, intervalo {}, total {} bytes",
+                config.model, shadowing.checkpoints, shadowing.checkpoint_bytes, shadowing.model_bytes,
+                shadowing.lyapunov_exponent, shadowing.interval, blob.len()
+            );
+            assert!(shadowing.lyapunov_exponent > 0.0);
+
+            // Error latente ≤ tol por componente; la base es ortonormal
+            let error = max_abs_error(&vectors, &attractor_decompress(&blob).unwrap());
+            assert!(error <= 3.0 * tolerance as f32 + 1e-4, "{:?}: max_error {}", config.model, error);
+            reports.push((shadowing, blob.len()));
+        }
+        let (delta, sindy) = (&reports[0], &reports[1]);
+        assert!(sindy.0.interval > 0);
+        assert!(sindy.0.checkpoints * 4 < delta.0.checkpoints);
+        assert!(sindy.1 < delta.1);
+
+        // Combinado con la capa de residuos, la cota sigue valiendo
+        let config = AttractorConfig { error_bound: Some(1e-3), ..sindy_config };
+        let blob = attractor_compress_with_config(&vectors, &config).unwrap();
+        assert!(max_abs_error(&vectors, &attractor_decompress(&blob).unwrap()) <= 1e-3);
+
+        let config = AttractorConfig { shadowing: Some(0.0), ..base };
+        assert!(matches!(
+            attractor_compress_with_config(&vectors, &config),
+            Err(CompressionError::InvalidParameter { name: "shadowing_tolerance", .. })
+        ));
+    }
+
+    #[test]
+    fn test_latent_ans_against_gzip() {
+        // Mismos residuos int16 (misma reconstrucción); ANS con una Laplace
+        // por componente se acerca a su entropía, GZIP no
+        let (n, dim) = (3000, 128);
+        let vectors = crate::datasets::generate_lorenz_embedding(n, dim, 0.05, &mut StdRng::seed_from_u64(23));
+        let gzip_config = AttractorConfig { components: ComponentCount::Fixed(3), ..AttractorConfig::default() };
+        let ans_config = AttractorConfig { latent_coder: LatentCoder::Ans, ..gzip_config };
+
+        let data: Vec<f32> = vectors.iter().flatten().copied().collect();
+        let view = VectorsView::new(&data, dim).unwrap();
+        let (gzip, _) = attractor_compress_view_with_report(view, &gzip_config).unwrap();
+        let (blob, info) = attractor_compress_view_with_report(view, &ans_config).unwrap();
+        assert_eq!(attractor_info(&blob).unwrap(), info);
+        assert_eq!(attractor_decompress(&blob).unwrap(), attractor_decompress(&gzip).unwrap());
+
+        let ans = info.ans.unwrap();
+        let gzip_bits = ans.gzip_bytes as f64 * 8.0 / ((n - 1) * 3) as f64;
+        println!(
+            "bits/componente ANS {:?} (total {} B), GZIP {:.2} (total {} B)",
+            ans.bits_per_component, ans.ans_bytes, gzip_bits, ans.gzip_bytes
+        );
+        assert!(ans.ans_bytes < ans.gzip_bytes && blob.len() < gzip.len());
+        assert!(ans.bits_per_component.iter().all(|&b| (b as f64) < gzip_bits));
+
+        // Con la capa de residuos también
+        let config = AttractorConfig { error_bound: Some(1e-3), ..ans_config };
+        let blob = attractor_compress_with_config(&vectors, &config).unwrap();
+        let max_error = max_abs_error(&vectors, &attractor_decompress(&blob).unwrap());
+        assert!(max_error <= 1e-3, "max_error {}", max_error);
+    }
+
+    #[test]
+    fn test_delta_steps_follow_latent_scale() {
+        // Misma órbita a escalas muy distintas: con paso fijo 0.001 la grande
+        // satura int16 y la pequeña se cuantiza a cero
+        let (n, dim) = (500, 32);
+        let orbit = |scale: f32| -> Vec<Vec<f32>> {
+            (0..n)
+                .map(|t| {
+                    let t = t as f32 * 0.05;
+                    let z = [t.cos(), (0.7 * t).sin()];
+                    (0..dim).map(|j| scale * (z[0] * (j as f32 * 0.3).sin() + z[1] * (j as f32 * 0.8).cos())).collect()
+                })
+                .collect()
+        };
+        let fixed = AttractorConfig {
+            components: ComponentCount::Fixed(2),
+            quantization: DeltaQuantization::Fixed(1e-3),
+            ..AttractorConfig::default()
+        };
+        let adaptive = AttractorConfig { quantization: DeltaQuantization::Adaptive, ..fixed };
+
+        for scale in [1e4f32, 1e-4] {
+            let vectors = orbit(scale);
+            let view_data: Vec<f32> = vectors.iter().flatten().copied().collect();
+            let view = VectorsView::new(&view_data, dim).unwrap();
+
+            let (blob, info) = attractor_compress_view_with_report(view, &adaptive).unwrap();
+            assert_eq!(info.clipped, 0);
+            assert_eq!(attractor_info(&blob).unwrap(), info);
+            let adaptive_error = max_abs_error(&vectors, &attractor_decompress(&blob).unwrap());
+            assert!(adaptive_error <= 1e-3 * scale, "escala {}: error {}", scale, adaptive_error);
+
+            let (blob, info) = attractor_compress_view_with_report(view, &fixed).unwrap();
+            assert_eq!(info.steps, vec![1e-3f32; 2]);
+            let fixed_error = max_abs_error(&vectors, &attractor_decompress(&blob).unwrap());
+            assert!(fixed_error > 10.0 * adaptive_error, "escala {}: {} vs {}", scale, fixed_error, adaptive_error);
+            if scale > 1.0 {
+                assert!(info.clipped > 0);
+            }
+        }
+
+        // Distorsión objetivo: paso = d·√12 en todas las componentes
+        let vectors = orbit(1.0);
+        let config = AttractorConfig { quantization: DeltaQuantization::TargetDistortion(1e-4), ..fixed };
+        let info = attractor_info(&attractor_compress_with_config(&vectors, &config).unwrap()).unwrap();
+        assert!(info.steps.iter().all(|&s| (s as f64 - 1e-4 * 12f64.sqrt()).abs() < 1e-9));
+
+        let config = AttractorConfig { quantization: DeltaQuantization::Fixed(0.0), ..fixed };
+        assert!(matches!(
+            attractor_compress_with_config(&vectors, &config),
+            Err(CompressionError::InvalidParameter { name: "delta_step", .. })
+        ));
+    }
 }
@@ -0,0 +1,627 @@
+//! Compresión por variedad no lineal: autoencoder MLP entrenado en CPU
+//!
+//! Estrategia:
+//! 1. Normalizar (media por dimensión, escala RMS global)
+//! 2. Entrenar un autoencoder dim → H → L → H → dim (tanh en las capas
+//!    ocultas) con Adam sobre el error cuadrático, con semilla fija
+//! 3. Almacenar: pesos del decodificador (float16) + códigos latentes
+//!    (deltas int16 en lazo cerrado, como la trayectoria del atractor) +
+//!    capa opcional de residuos con cota de error
+//!
+//! Una trayectoria curva necesita muchas componentes lineales; el
+//! decodificador no lineal la sigue con pocas coordenadas a cambio de
+//! guardar sus pesos. `autoencoder_compress_view_with_report` compara con
+//! el codec de atractor (PCA del mismo tamaño latente y la misma cota) y
+//! estima la longitud a partir de la cual los pesos se amortizan.
+//!
+//! Formato del contenido: media (tipo de elemento, × dim) + escala (f32) +
+//! decodificador (float16: W₃ L × H, b₃, W₄ H × dim, b₄) + longitud (u32) +
+//! GZIP(trayectoria latente) + [paso (f64) + GZIP(residuos varint)].
+//! Parámetros: L (u32) + H (u32) + cota de error (f64, 0 sin residuos) +
+//! deltas saturados (u32) + pasos (f32 × L).
+
+use flate2::Compression;
+use half::f16;
+use ndarray::{Array1, Array2, ArrayView2, Axis};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+
+use super::attractor_compression::{
+    attractor_compress_view_with_report, decode_trajectory, delta_steps, encode_residuals, encode_trajectory,
+    AttractorConfig, DeltaQuantization,
+};
+use super::bytes::{gzip_decode, gzip_encode, ByteReader};
+use super::container::{self, CodecId, ContainerHeader};
+use super::dynamics::{Predictor, TrajectoryModel};
+use super::element::Element;
+use super::error::CompressionError;
+use super::pca::ComponentCount;
+use super::vectors::{compress_nested, VectorsView};
+
+/// Tamaño máximo de las capas (latente y oculta)
+pub const MAX_LAYER: usize = 4096;
+
+/// Vectores por minilote de Adam
+const BATCH: usize = 64;
+
+/// Configuración del codec de autoencoder
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AutoencoderConfig {
+    /// Dimensión del código latente (L)
+    pub latent: usize,
+
+    /// Neuronas de cada capa oculta (H)
+    pub hidden: usize,
+
+    /// Pasadas completas sobre los datos
+    pub epochs: usize,
+
+    /// Tasa de aprendizaje de Adam
+    pub learning_rate: f64,
+
+    /// Semilla de la inicialización y del orden de los minilotes
+    pub seed: u64,
+
+    /// Cota de error máximo absoluto por elemento (capa de residuos)
+    pub error_bound: Option<f64>,
+
+    /// Paso de cuantización de los deltas latentes
+    pub quantization: DeltaQuantization,
+}
+
+impl Default for AutoencoderConfig {
+    fn default() -> Self {
+        AutoencoderConfig {
+            latent: 8,
+            hidden: 32,
+            epochs: 30,
+            learning_rate: 3e-3,
+            seed: 0,
+            error_bound: Some(0.01),
+            quantization: DeltaQuantization::Adaptive,
+        }
+    }
+}
+
+/// Metadatos de un blob de autoencoder (parámetros del contenedor)
+#[derive(Debug, Clone, PartialEq)]
+pub struct AutoencoderInfo {
+    pub latent: usize,
+    pub hidden: usize,
+    /// Cota de error garantizada por la capa de residuos, si la hay
+    pub error_bound: Option<f64>,
+    /// Deltas latentes saturados al rango de int16 al codificar
+    pub clipped: usize,
+    /// Paso de cuantización de cada componente latente
+    pub steps: Vec<f32>,
+}
+
+/// Informe de compresión: tamaños por sección y punto de equilibrio frente
+/// al codec lineal
+#[derive(Debug, Clone, PartialEq)]
+pub struct AutoencoderReport {
+    pub info: AutoencoderInfo,
+    /// Error cuadrático medio (datos normalizados) de la última época
+    pub training_loss: f64,
+    /// Bytes del decodificador (pesos float16 + escala)
+    pub decoder_bytes: usize,
+    /// Bytes por vector de códigos latentes y residuos
+    pub bytes_per_vector: f64,
+    /// Blob del codec de atractor con L componentes y la misma cota
+    pub linear_bytes: usize,
+    /// Bytes por vector del codec lineal (trayectoria y residuos)
+    pub linear_bytes_per_vector: f64,
+    /// Vectores a partir de los cuales el autoencoder ocupa menos que el
+    /// codec lineal (`None` si por vector no ahorra nada)
+    pub break_even: Option<usize>,
+}
+
+/// Compresión por autoencoder con la configuración por defecto
+pub fn autoencoder_compress(vectors: &[Vec<f32>]) -> Result<Vec<u8>, CompressionError> {
+    autoencoder_compress_with_config(vectors, &AutoencoderConfig::default())
+}
+
+/// Compresión por autoencoder con configuración completa
+pub fn autoencoder_compress_with_config(
+    vectors: &[Vec<f32>],
+    config: &AutoencoderConfig,
+) -> Result<Vec<u8>, CompressionError> {
+    compress_nested(vectors, |view| autoencoder_compress_view(view, config))
+}
+
+/// Compresión por autoencoder sobre una vista plana
+pub fn autoencoder_compress_view<T: Element>(
+    vectors: VectorsView<'_, T>,
+    config: &AutoencoderConfig,
+) -> Result<Vec<u8>, CompressionError> {
+    encode(vectors, config).map(|encoded| encoded.blob)
+}
+
+/// Igual que `autoencoder_compress_view`, con el informe de tamaños; además
+/// comprime con el codec de atractor para estimar el punto de equilibrio
+pub fn autoencoder_compress_view_with_report<T: Element>(
+    vectors: VectorsView<'_, T>,
+    config: &AutoencoderConfig,
+) -> Result<(Vec<u8>, AutoencoderReport), CompressionError> {
+    let encoded = encode(vectors, config)?;
+    let n = vectors.n().max(1) as f64;
+    let mean_bytes = vectors.dim() * T::TYPE.size();
+
+    let linear_config = AttractorConfig {
+        components: ComponentCount::Fixed(config.latent),
+        error_bound: config.error_bound,
+        quantization: config.quantization,
+        ..AttractorConfig::default()
+    };
+    let (linear, linear_info) = attractor_compress_view_with_report(vectors, &linear_config)?;
+    let linear_payload = container::open(&linear, CodecId::Attractor)?.1.len();
+    let linear_model = linear_info.n_components * vectors.dim() * 4;
+    let linear_bytes_per_vector = linear_payload.saturating_sub(mean_bytes + linear_model) as f64 / n;
+
+    let bytes_per_vector = encoded.variable_bytes as f64 / n;
+    let break_even = break_even(encoded.decoder_bytes, linear_model, bytes_per_vector, linear_bytes_per_vector);
+
+    let report = AutoencoderReport {
+        info: encoded.info,
+        training_loss: encoded.training_loss,
+        decoder_bytes: encoded.decoder_bytes,
+        bytes_per_vector,
+        linear_bytes: linear.len(),
+        linear_bytes_per_vector,
+        break_even,
+    };
+    Ok((encoded.blob, report))
+}
+
+/// Vectores a partir de los cuales un modelo de `model_bytes` con
+/// `bytes_per_vector` ocupa menos que el lineal; `None` si por vector no
+/// ahorra nada (un modelo más pequeño solo gana en secuencias cortas)
+fn break_even(model_bytes: usize, linear_model: usize, bytes_per_vector: f64, linear_bytes_per_vector: f64) -> Option<usize> {
+    if model_bytes <= linear_model && bytes_per_vector <= linear_bytes_per_vector {
+        Some(0)
+    } else if bytes_per_vector >= linear_bytes_per_vector {
+        None
+    } else {
+        let saving = linear_bytes_per_vector - bytes_per_vector;
+        Some(((model_bytes - linear_model) as f64 / saving).ceil() as usize)
+    }
+}
+
+/// Resultado interno de la codificación
+struct Encoded {
+    blob: Vec<u8>,
+    info: AutoencoderInfo,
+    training_loss: f64,
+    decoder_bytes: usize,
+    /// Trayectoria latente y residuos (lo que crece con n)
+    variable_bytes: usize,
+}
+
+fn encode<T: Element>(vectors: VectorsView<'_, T>, config: &AutoencoderConfig) -> Result<Encoded, CompressionError> {
+    for (name, size) in [("latent", config.latent), ("hidden", config.hidden)] {
+        if size == 0 || size > MAX_LAYER {
+            return Err(CompressionError::InvalidParameter { name, value: size as f64 });
+        }
+    }
+    if !(config.learning_rate.is_finite() && config.learning_rate > 0.0) {
+        return Err(CompressionError::InvalidParameter { name: "learning_rate", value: config.learning_rate });
+    }
+    if let Some(eps) = config.error_bound.filter(|eps| !(eps.is_finite() && *eps > 0.0)) {
+        return Err(CompressionError::InvalidParameter { name: "error_bound", value: eps });
+    }
+    if let DeltaQuantization::Fixed(d) | DeltaQuantization::TargetDistortion(d) = config.quantization {
+        if !(d.is_finite() && d > 0.0) {
+            return Err(CompressionError::InvalidParameter { name: "delta_step", value: d });
+        }
+    }
+
+    let (latent, hidden) = (config.latent, config.hidden);
+    if vectors.is_empty() {
+        let info = AutoencoderInfo { latent, hidden, error_bound: config.error_bound, clipped: 0, steps: Vec::new() };
+        let blob = container::wrap(CodecId::Autoencoder, T::TYPE, &encode_params(&info), 0, 0, &[])?;
+        return Ok(Encoded { blob, info, training_loss: 0.0, decoder_bytes: 0, variable_bytes: 0 });
+    }
+
+    let n = vectors.n();
+    let dim = vectors.dim();
+    let data = ArrayView2::from_shape((n, dim), vectors.as_slice())
+        .map_err(|_| CompressionError::BadLength { field: "n", value: n })?;
+
+    // 1. Normalización: media en el tipo de elemento original, escala f32
+    let mean: Array1<f64> = data
+        .mapv(|x| x.to_f64())
+        .mean_axis(Axis(0))
+        .unwrap_or_else(|| Array1::zeros(dim))
+        .mapv(|m| T::TYPE.round(m));
+    let centered = data.mapv(|x| x.to_f64()) - &mean;
+    let rms = (centered.iter().map(|x| x * x).sum::<f64>() / centered.len() as f64).sqrt() as f32;
+    let scale = if rms.is_normal() { rms } else { 1.0 };
+    let normalized = centered / scale as f64;
+
+    // 2. Entrenamiento y códigos latentes
+    let (network, training_loss) = Network::train(&normalized, config);
+    let codes = network.encode(&normalized);
+    let decoder = network.decoder();
+
+    // 3. Trayectoria latente cuantizada en lazo cerrado
+    let predictor = Predictor::fit(TrajectoryModel::Delta, codes.view())?;
+    let steps = delta_steps(&codes, &predictor, config.quantization);
+    let (trajectory, clipped) = encode_trajectory(&codes, &steps, &predictor);
+    let compressed_trajectory = gzip_encode(&trajectory, Compression::best())?;
+
+    let mut payload = Vec::new();
+    for &val in mean.iter() {
+        T::TYPE.write(val, &mut payload);
+    }
+    let model_start = payload.len();
+    payload.extend(&scale.to_le_bytes());
+    decoder.write(&mut payload);
+    let decoder_bytes = payload.len() - model_start;
+
+    payload.extend(&(compressed_trajectory.len() as u32).to_le_bytes());
+    payload.extend(&compressed_trajectory);
+
+    // 4. Residuos sobre la salida exacta del decodificador (pesos float16,
+    //    códigos decodificados)
+    if let Some(eps) = config.error_bound {
+        let decoded = decode_trajectory(&trajectory, n, &steps, &predictor)?;
+        let mean = mean.as_slice().expect("contiguous");
+        let reconstruct = |i: usize, row: &mut [f64]| decoder.reconstruct(decoded.row(i).as_slice().expect("contiguous"), mean, scale, row);
+        payload.extend(encode_residuals(data, reconstruct, eps)?);
+    }
+    let variable_bytes = payload.len() - model_start - decoder_bytes;
+
+    let info = AutoencoderInfo { latent, hidden, error_bound: config.error_bound, clipped, steps };
+    let blob = container::wrap(CodecId::Autoencoder, T::TYPE, &encode_params(&info), n, dim, &payload)?;
+    Ok(Encoded { blob, info, training_loss, decoder_bytes, variable_bytes })
+}
+
+/// Parámetros del contenedor: L (u32) + H (u32) + cota de error (f64, 0 si
+/// no hay residuos) + deltas saturados (u32) + pasos (f32 × L)
+fn encode_params(info: &AutoencoderInfo) -> Vec<u8> {
+    let mut params = (info.latent as u32).to_le_bytes().to_vec();
+    params.extend(&(info.hidden as u32).to_le_bytes());
+    params.extend(&info.error_bound.unwrap_or(0.0).to_le_bytes());
+    params.extend(&(info.clipped.min(u32::MAX as usize) as u32).to_le_bytes());
+    for step in &info.steps {
+        params.extend(&step.to_le_bytes());
+    }
+    params
+}
+
+fn decode_params(params: &[u8], n: usize) -> Result<AutoencoderInfo, CompressionError> {
+    let mut params = ByteReader::header(params);
+    let latent = params.read_len()?;
+    let hidden = params.read_len()?;
+    if latent > MAX_LAYER || hidden > MAX_LAYER {
+        return Err(CompressionError::BadLength { field: "latent", value: latent.max(hidden) });
+    }
+    let error_bound = Some(params.read_f64()?).filter(|&eps| eps > 0.0);
+    let clipped = params.read_len()?;
+    let steps = if n == 0 { Vec::new() } else { params.read_f32_vec(latent)? };
+    Ok(AutoencoderInfo { latent, hidden, error_bound, clipped, steps })
+}
+
+/// Metadatos de un blob de autoencoder (de su cabecera)
+pub fn autoencoder_info(compressed: &[u8]) -> Result<AutoencoderInfo, CompressionError> {
+    let header = container::read_header(compressed)?.0;
+    if header.codec != CodecId::Autoencoder {
+        return Err(CompressionError::WrongCodec { expected: CodecId::Autoencoder, found: header.codec });
+    }
+    decode_params(&header.params, header.n)
+}
+
+/// Descompresión por autoencoder
+pub fn autoencoder_decompress(compressed: &[u8]) -> Result<Vec<Vec<f32>>, CompressionError> {
+    container::decompress_as(compressed, CodecId::Autoencoder)
+}
+
+pub(crate) fn decode_payload<T: Element>(
+    header: &ContainerHeader,
+    payload: &[u8],
+    out: &mut [T],
+) -> Result<(), CompressionError> {
+    let n = header.n;
+    let dim = header.dim;
+    if n == 0 {
+        return Ok(());
+    }
+    if dim == 0 {
+        return Err(CompressionError::UnsupportedDimension { dim, min: 1 });
+    }
+
+    let info = decode_params(&header.params, n)?;
+    let mut reader = ByteReader::payload(payload);
+    let mean = header.element.read_vec(&mut reader, dim)?;
+    let scale = reader.read_f32()?;
+    let decoder = Decoder::read(&mut reader, info.latent, info.hidden, dim)?;
+
+    let trajectory_len = reader.read_len()?;
+    let trajectory = gzip_decode(reader.take(trajectory_len)?)?;
+    let predictor = Predictor::fit(TrajectoryModel::Delta, Array2::zeros((0, info.latent)).view())?;
+    let codes = decode_trajectory(&trajectory, n, &info.steps, &predictor)?;
+
+    let (residual_step, residuals) = if info.error_bound.is_some() {
+        (Some(reader.read_f64()?), gzip_decode(reader.rest())?)
+    } else {
+        (None, Vec::new())
+    };
+    let mut residuals = ByteReader::payload(&residuals);
+
+    let mut row = vec![0.0f64; dim];
+    for (vec, z) in out.chunks_exact_mut(dim).zip(codes.rows()) {
+        decoder.reconstruct(z.as_slice().expect("contiguous"), &mean, scale, &mut row);
+        if let Some(step) = residual_step {
+            for r in row.iter_mut() {
+                *r += residuals.read_varint()? as f64 * step;
+            }
+        }
+        for (o, &x) in vec.iter_mut().zip(&row) {
+            *o = T::from_f64(x);
+        }
+    }
+
+    Ok(())
+}
+
+/// Autoencoder completo: capas (W: entradas × salidas, b: 1 × salidas) en
+/// orden dim → H → L → H → dim
+struct Network {
+    weights: Vec<Array2<f64>>,
+    biases: Vec<Array2<f64>>,
+}
+
+impl Network {
+    /// Adam (β₁ = 0.9, β₂ = 0.999) por minilotes barajados con la semilla
+    /// de la configuración; devuelve la red y el error medio de la última
+    /// época
+    fn train(x: &Array2<f64>, config: &AutoencoderConfig) -> (Network, f64) {
+        let (n, dim) = x.dim();
+        let sizes = [dim, config.hidden, config.latent, config.hidden, dim];
+        let mut rng = StdRng::seed_from_u64(config.seed);
+
+        // Inicialización de Glorot uniforme, sesgos a cero
+        let weights: Vec<Array2<f64>> = sizes
+            .windows(2)
+            .map(|w| {
+                let limit = (6.0 / (w[0] + w[1]) as f64).sqrt();
+                Array2::from_shape_simple_fn((w[0], w[1]), || rng.gen_range(-limit..limit))
+            })
+            .collect();
+        let biases: Vec<Array2<f64>> = sizes[1..].iter().map(|&s| Array2::zeros((1, s))).collect();
+        let mut network = Network { weights, biases };
+
+        let mut moments: Vec<(Array2<f64>, Array2<f64>)> = network
+            .parameters()
+            .map(|p| (Array2::zeros(p.raw_dim()), Array2::zeros(p.raw_dim())))
+            .collect();
+        let mut order: Vec<usize> = (0..n).collect();
+        let mut step = 0;
+        let mut loss = 0.0;
+
+        for _ in 0..config.epochs {
+            order.shuffle(&mut rng);
+            let mut total = 0.0;
+            for batch in order.chunks(BATCH) {
+                let xb = x.select(Axis(0), batch);
+                let (gradients, batch_loss) = network.gradients(&xb);
+                total += batch_loss * batch.len() as f64;
+
+                step += 1;
+                let (c1, c2) = (1.0 - 0.9f64.powi(step), 1.0 - 0.999f64.powi(step));
+                for ((p, g), (m, v)) in network.parameters_mut().zip(&gradients).zip(moments.iter_mut()) {
+                    m.zip_mut_with(g, |m, &g| *m = 0.9 * *m + 0.1 * g);
+                    v.zip_mut_with(g, |v, &g| *v = 0.999 * *v + 0.001 * g * g);
+                    ndarray::Zip::from(p).and(&*m).and(&*v).for_each(|p, &m, &v| {
+                        *p -= config.learning_rate * (m / c1) / ((v / c2).sqrt() + 1e-8);
+                    });
+                }
+            }
+            loss = total / n as f64;
+        }
+        (network, loss)
+    }
+
+    fn parameters(&self) -> impl Iterator<Item = &Array2<f64>> {
+        self.weights.iter().zip(&self.biases).flat_map(|(w, b)| [w, b])
+    }
+
+    fn parameters_mut(&mut self) -> impl Iterator<Item = &mut Array2<f64>> {
+        self.weights.iter_mut().zip(self.biases.iter_mut()).flat_map(|(w, b)| [w, b])
+    }
+
+    /// Activaciones de cada capa (la entrada primero); tanh en las ocultas
+    fn forward(&self, x: &Array2<f64>) -> Vec<Array2<f64>> {
+        let mut activations = vec![x.clone()];
+        for (layer, (w, b)) in self.weights.iter().zip(&self.biases).enumerate() {
+            let mut a = activations[layer].dot(w) + b;
+            if layer % 2 == 0 {
+                a.mapv_inplace(f64::tanh);
+            }
+            activations.push(a);
+        }
+        activations
+    }
+
+    /// Gradientes del error cuadrático medio (mismo orden que `parameters`)
+    fn gradients(&self, x: &Array2<f64>) -> (Vec<Array2<f64>>, f64) {
+        let activations = self.forward(x);
+        let output = &activations[self.weights.len()];
+        let error = output - x;
+        let loss = error.iter().map(|e| e * e).sum::<f64>() / error.len() as f64;
+
+        let mut delta = error * (2.0 / x.len() as f64);
+        let mut gradients = vec![Array2::zeros((0, 0)); 2 * self.weights.len()];
+        for layer in (0..self.weights.len()).rev() {
+            if layer % 2 == 0 {
+                delta.zip_mut_with(&activations[layer + 1], |d, &a| *d *= 1.0 - a * a);
+            }
+            gradients[2 * layer] = activations[layer].t().dot(&delta);
+            gradients[2 * layer + 1] = delta.sum_axis(Axis(0)).insert_axis(Axis(0));
+            delta = delta.dot(&self.weights[layer].t());
+        }
+        (gradients, loss)
+    }
+
+    /// Códigos latentes (salida de la segunda capa)
+    fn encode(&self, x: &Array2<f64>) -> Array2<f64> {
+        let mut activations = self.forward(x);
+        activations.swap_remove(2)
+    }
+
+    /// Mitad decodificadora con los pesos redondeados a float16
+    fn decoder(&self) -> Decoder {
+        let round = |a: &Array2<f64>| a.mapv(|x| f16::from_f64(x).to_f64());
+        Decoder {
+            hidden_weights: round(&self.weights[2]),
+            hidden_bias: round(&self.biases[2]).remove_axis(Axis(0)),
+            output_weights: round(&self.weights[3]),
+            output_bias: round(&self.biases[3]).remove_axis(Axis(0)),
+        }
+    }
+}
+
+/// Decodificador almacenado: z → tanh(z·W₃ + b₃) → ·W₄ + b₄
+struct Decoder {
+    hidden_weights: Array2<f64>,
+    hidden_bias: Array1<f64>,
+    output_weights: Array2<f64>,
+    output_bias: Array1<f64>,
+}
+
+impl Decoder {
+    fn write(&self, out: &mut Vec<u8>) {
+        let values = self.hidden_weights.iter()
+            .chain(&self.hidden_bias)
+            .chain(&self.output_weights)
+            .chain(&self.output_bias);
+        for &v in values {
+            out.extend(&f16::from_f64(v).to_le_bytes());
+        }
+    }
+
+    fn read(reader: &mut ByteReader<'_>, latent: usize, hidden: usize, dim: usize) -> Result<Decoder, CompressionError> {
+        let mut read = |rows: usize, cols: usize| -> Result<Array2<f64>, CompressionError> {
+            let count = rows.checked_mul(cols).ok_or(CompressionError::BadLength { field: "dim", value: dim })?;
+            let bytes = reader.take(count.checked_mul(2).ok_or(CompressionError::BadLength { field: "dim", value: dim })?)?;
+            let values = bytes.chunks_exact(2).map(|b| f16::from_le_bytes([b[0], b[1]]).to_f64()).collect();
+            Array2::from_shape_vec((rows, cols), values).map_err(|_| CompressionError::BadLength { field: "dim", value: dim })
+        };
+        let hidden_weights = read(latent, hidden)?;
+        let hidden_bias = read(1, hidden)?.remove_axis(Axis(0));
+        let output_weights = read(hidden, dim)?;
+        let output_bias = read(1, dim)?.remove_axis(Axis(0));
+        Ok(Decoder { hidden_weights, hidden_bias, output_weights, output_bias })
+    }
+
+    /// Vector reconstruido: media + escala · decodificador(z)
+    ///
+    /// Bucles explícitos en orden fijo: codificador y decodificador obtienen
+    /// exactamente la misma fila (la capa de residuos depende de ello).
+    fn reconstruct(&self, z: &[f64], mean: &[f64], scale: f32, row: &mut [f64]) {
+        let mut hidden = self.hidden_bias.to_vec();
+        for (&z, weights) in z.iter().zip(self.hidden_weights.rows()) {
+            for (h, &w) in hidden.iter_mut().zip(weights) {
+                *h += z * w;
+            }
+        }
+        row.copy_from_slice(self.output_bias.as_slice().expect("contiguous"));
+        for (h, weights) in hidden.iter().map(|h| h.tanh()).zip(self.output_weights.rows()) {
+            for (r, &w) in row.iter_mut().zip(weights) {
+                *r += h * w;
+            }
+        }
+        for (r, &m) in row.iter_mut().zip(mean) {
+            *r = m + scale as f64 * *r;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Curva suave (1D) enrollada en 64D: armónicos de un ángulo que va y
+    /// viene, sin cerrarse
+    fn curved_trajectory(n: usize, dim: usize) -> Vec<Vec<f32>> {
+        let mut rng = StdRng::seed_from_u64(9);
+        let directions: Vec<Vec<f64>> = (0..12).map(|_| (0..dim).map(|_| rng.gen::<f64>() - 0.5).collect()).collect();
+        (0..n)
+            .map(|t| {
+                let theta = std::f64::consts::PI * (0.5 + 0.45 * (0.01 * t as f64).sin());
+                (0..dim)
+                    .map(|j| {
+                        (0..6).map(|m| {
+                            let h = (m + 1) as f64;
+                            ((h * theta).cos() * directions[2 * m][j] + (h * theta).sin() * directions[2 * m + 1][j]) / h
+                        }).sum::<f64>() as f32
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_autoencoder_beats_linear_on_curved_trajectory() {
+        let vectors = curved_trajectory(2000, 64);
+        let config = AutoencoderConfig { latent: 2, hidden: 32, epochs: 60, ..AutoencoderConfig::default() };
+        let (flat, dim) = crate::methods::vectors::flatten(&vectors).unwrap();
+        let view = VectorsView::new(&flat, dim).unwrap();
+        let (blob, report) = autoencoder_compress_view_with_report(view, &config).unwrap();
+        println!("{:?}", report);
+
+        // Determinista: misma semilla, mismo blob
+        assert_eq!(autoencoder_compress_with_config(&vectors, &config).unwrap(), blob);
+        assert_eq!(autoencoder_info(&blob).unwrap(), report.info);
+        assert_eq!(report.decoder_bytes, 4 + 2 * (2 * 32 + 32 + 32 * 64 + 64));
+
+        // Cota de error garantizada
+        let decompressed = autoencoder_decompress(&blob).unwrap();
+        let max_error = vectors.iter().flatten()
+            .zip(decompressed.iter().flatten())
+            .map(|(a, b)| (a - b).abs())
+            .fold(0.0f32, f32::max);
+        assert!(max_error <= 0.01, "max_error = {}", max_error);
+
+        // Con dos coordenadas la PCA deja casi toda la curva en los residuos
+        assert!(report.bytes_per_vector * 2.0 < report.linear_bytes_per_vector);
+        let break_even = report.break_even.unwrap();
+        assert!(break_even < 2000 && blob.len() < report.linear_bytes, "{:?}", report);
+    }
+
+    #[test]
+    fn test_break_even_needs_per_vector_saving() {
+        // Decodificador más pequeño pero más bytes por vector: solo gana en
+        // secuencias cortas, nunca se amortiza
+        assert_eq!(break_even(500, 1000, 12.0, 10.0), None);
+        assert_eq!(break_even(500, 1000, 10.0, 10.0), Some(0));
+        assert_eq!(break_even(1000, 1000, 8.0, 10.0), Some(0));
+        assert_eq!(break_even(3000, 1000, 8.0, 10.0), Some(1000));
+        assert_eq!(break_even(3000, 1000, 10.0, 10.0), None);
+    }
+
+    #[test]
+    fn test_autoencoder_roundtrip_edge_cases() {
+        // Sin residuos, un solo vector, vacío y f16
+        let vectors: Vec<Vec<f32>> = (0..100).map(|i| (0..8).map(|j| ((i * j) as f32 * 0.01).sin()).collect()).collect();
+        let config = AutoencoderConfig { latent: 3, hidden: 8, epochs: 5, error_bound: None, ..AutoencoderConfig::default() };
+        let decompressed = autoencoder_decompress(&autoencoder_compress_with_config(&vectors, &config).unwrap()).unwrap();
+        assert_eq!(decompressed.len(), 100);
+
+        let single = vec![vec![1.0f32, -2.0, 3.0]];
+        let decompressed = autoencoder_decompress(&autoencoder_compress(&single).unwrap()).unwrap();
+        assert!(single[0].iter().zip(&decompressed[0]).all(|(a, b)| (a - b).abs() <= 0.01));
+
+        assert!(autoencoder_decompress(&autoencoder_compress(&[]).unwrap()).unwrap().is_empty());
+        let config = AutoencoderConfig { latent: 0, ..AutoencoderConfig::default() };
+        assert!(autoencoder_compress_with_config(&single, &config).is_err());
+
+        let data: Vec<f16> = (0..50 * 8).map(|i| f16::from_f32((i as f32 * 0.1).sin())).collect();
+        let config = AutoencoderConfig { epochs: 5, ..AutoencoderConfig::default() };
+        let blob = autoencoder_compress_view(VectorsView::new(&data, 8).unwrap(), &config).unwrap();
+        let mut out = vec![f16::ZERO; data.len()];
+        crate::methods::decompress_into(&blob, &mut out).unwrap();
+        assert!(data.iter().zip(&out).all(|(a, b)| (a.to_f32() - b.to_f32()).abs() <= 0.01));
+    }
+}
@@ -0,0 +1,223 @@
+//! Utilidades de (de)serialización compartidas por los métodos
+//!
+//! `ByteReader` lee campos little-endian con comprobación de límites, de modo
+//! que un blob truncado o corrupto produce un `CompressionError` en lugar de
+//! un pánico por indexación.
+
+use flate2::Compression;
+use flate2::write::GzEncoder;
+use flate2::read::GzDecoder;
+use std::io::{Write, Read};
+
+use super::error::{Backend, CompressionError};
+
+/// Qué parte del blob se está leyendo (determina el tipo de error por truncado)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Section {
+    Header,
+    Payload,
+}
+
+/// Lector secuencial little-endian con comprobación de límites
+pub(crate) struct ByteReader<'a> {
+    data: &'a [u8],
+    offset: usize,
+    section: Section,
+}
+
+impl<'a> ByteReader<'a> {
+    pub(crate) fn header(data: &'a [u8]) -> Self {
+        ByteReader { data, offset: 0, section: Section::Header }
+    }
+
+    pub(crate) fn payload(data: &'a [u8]) -> Self {
+        ByteReader { data, offset: 0, section: Section::Payload }
+    }
+
+    pub(crate) fn remaining(&self) -> usize {
+        self.data.len() - self.offset
+    }
+
+    fn truncated(&self, needed: usize) -> CompressionError {
+        let needed = self.offset.saturating_add(needed);
+        let available = self.data.len();
+        match self.section {
+            Section::Header => CompressionError::TruncatedHeader { needed, available },
+            Section::Payload => CompressionError::TruncatedPayload { needed, available },
+        }
+    }
+
+    /// Toma los siguientes `n` bytes
+    pub(crate) fn take(&mut self, n: usize) -> Result<&'a [u8], CompressionError> {
+        if n > self.remaining() {
+            return Err(self.truncated(n));
+        }
+        let slice = &self.data[self.offset..self.offset + n];
+        self.offset += n;
+        Ok(slice)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], CompressionError> {
+        let mut bytes = [0u8; N];
+        bytes.copy_from_slice(self.take(N)?);
+        Ok(bytes)
+    }
+
+    /// Resto del buffer sin consumir
+    pub(crate) fn rest(&mut self) -> &'a [u8] {
+        let slice = &self.data[self.offset..];
+        self.offset = self.data.len();
+        slice
+    }
+
+    pub(crate) fn read_u8(&mut self) -> Result<u8, CompressionError> {
+        Ok(self.array::<1>()?[0])
+    }
+
+    pub(crate) fn read_u16(&mut self) -> Result<u16, CompressionError> {
+        Ok(u16::from_le_bytes(self.array()?))
+    }
+
+    pub(crate) fn read_i16(&mut self) -> Result<i16, CompressionError> {
+        Ok(i16::from_le_bytes(self.array()?))
+    }
+
+    pub(crate) fn read_u32(&mut self) -> Result<u32, CompressionError> {
+        Ok(u32::from_le_bytes(self.array()?))
+    }
+
+    pub(crate) fn read_u64(&mut self) -> Result<u64, CompressionError> {
+        Ok(u64::from_le_bytes(self.array()?))
+    }
+
+    pub(crate) fn read_f32(&mut self) -> Result<f32, CompressionError> {
+        Ok(f32::from_le_bytes(self.array()?))
+    }
+
+    pub(crate) fn read_f64(&mut self) -> Result<f64, CompressionError> {
+        Ok(f64::from_le_bytes(self.array()?))
+    }
+
+    /// Lee un entero con signo en zigzag + LEB128 (ver `write_varint`)
+    pub(crate) fn read_varint(&mut self) -> Result<i64, CompressionError> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = self.read_u8()?;
+            value |= ((byte & 0x7f) as u64) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(((value >> 1) as i64) ^ -((value & 1) as i64));
+            }
+        }
+        Err(CompressionError::BadLength { field: "varint", value: self.offset })
+    }
+
+    /// Lee un u32 como conteo/dimensión
+    pub(crate) fn read_len(&mut self) -> Result<usize, CompressionError> {
+        Ok(self.read_u32()? as usize)
+    }
+
+    /// Lee `count` floats, verificando antes que caben en el buffer
+    pub(crate) fn read_f32_vec(&mut self, count: usize) -> Result<Vec<f32>, CompressionError> {
+        let bytes = self.take(count.checked_mul(4).ok_or(self.truncated(usize::MAX))?)?;
+        Ok(bytes.chunks_exact(4)
+            .map(|chunk| f32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]))
+            .collect())
+    }
+}
+
+/// Escribe un entero con signo en zigzag + LEB128: los valores pequeños en
+/// magnitud ocupan un byte, lo que deja al compresor de entropía un alfabeto
+/// concentrado
+pub(crate) fn write_varint(value: i64, out: &mut Vec<u8>) {
+    let mut zigzag = ((value << 1) ^ (value >> 63)) as u64;
+    while zigzag >= 0x80 {
+        out.push((zigzag as u8) | 0x80);
+        zigzag >>= 7;
+    }
+    out.push(zigzag as u8);
+}
+
+/// Verifica que todos los vectores tengan la misma dimensión y la devuelve
+pub(crate) fn common_dim(vectors: &[Vec<f32>]) -> Result<usize, CompressionError> {
+    let dim = vectors.first().map_or(0, |v| v.len());
+    match vectors.iter().find(|v| v.len() != dim) {
+        Some(v) => Err(CompressionError::DimensionMismatch { expected: dim, found: v.len() }),
+        None => Ok(dim),
+    }
+}
+
+/// Verifica que un conteo leído de la cabecera sea alcanzable con los bytes
+/// disponibles, antes de reservar memoria para él
+pub(crate) fn check_count(
+    field: &'static str,
+    count: usize,
+    bytes_per_item: usize,
+    available: usize,
+) -> Result<(), CompressionError> {
+    match count.checked_mul(bytes_per_item) {
+        Some(needed) if needed <= available => Ok(()),
+        _ => Err(CompressionError::BadLength { field, value: count }),
+    }
+}
+
+pub(crate) fn gzip_encode(data: &[u8], level: Compression) -> Result<Vec<u8>, CompressionError> {
+    let mut encoder = GzEncoder::new(Vec::new(), level);
+    encoder.write_all(data).map_err(|e| CompressionError::backend(Backend::Gzip, e))?;
+    encoder.finish().map_err(|e| CompressionError::backend(Backend::Gzip, e))
+}
+
+pub(crate) fn gzip_decode(compressed: &[u8]) -> Result<Vec<u8>, CompressionError> {
+    let mut decoder = GzDecoder::new(compressed);
+    let mut bytes = Vec::new();
+    decoder.read_to_end(&mut bytes).map_err(|e| CompressionError::backend(Backend::Gzip, e))?;
+    Ok(bytes)
+}
+
+/// Descomprime GZIP exactamente en `out`: error si sobran o faltan bytes
+pub(crate) fn gzip_decode_into(compressed: &[u8], out: &mut [u8]) -> Result<(), CompressionError> {
+    let mut decoder = GzDecoder::new(compressed);
+    decoder.read_exact(out).map_err(|e| CompressionError::backend(Backend::Gzip, e))?;
+
+    let mut extra = [0u8; 1];
+    match decoder.read(&mut extra) {
+        Ok(0) => Ok(()),
+        Ok(_) => Err(CompressionError::DimensionMismatch { expected: out.len(), found: out.len() + 1 }),
+        Err(e) => Err(CompressionError::backend(Backend::Gzip, e)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reader_reports_truncation() {
+        let data = [1u8, 0, 0, 0, 7];
+        let mut reader = ByteReader::header(&data);
+        assert_eq!(reader.read_u32().unwrap(), 1);
+        assert!(matches!(
+            reader.read_u32(),
+            Err(CompressionError::TruncatedHeader { needed: 8, available: 5 })
+        ));
+
+        let mut reader = ByteReader::payload(&data);
+        assert!(matches!(
+            reader.read_f32_vec(2),
+            Err(CompressionError::TruncatedPayload { needed: 8, available: 5 })
+        ));
+        let mut varints = Vec::new();
+        for value in [0, -1, 1, 63, -64, 64, i64::MAX, i64::MIN] {
+            write_varint(value, &mut varints);
+        }
+        let mut reader = ByteReader::payload(&varints);
+        for value in [0, -1, 1, 63, -64, 64, i64::MAX, i64::MIN] {
+            assert_eq!(reader.read_varint().unwrap(), value);
+        }
+        assert!(matches!(reader.read_varint(), Err(CompressionError::TruncatedPayload { .. })));
+
+        assert!(matches!(
+            check_count("n", 9, 4, data.len()),
+            Err(CompressionError::BadLength { field: "n", value: 9 })
+        ));
+    }
+}
@@ -0,0 +1,1459 @@
+//! Interfaz común `Codec` y registro de métodos de compresión
+//!
+//! Cada método de `methods` se expone como un tipo que implementa `Codec`.
+//! `CodecRegistry` permite enumerarlos, buscarlos por id de texto y
+//! configurarlos sin tocar el código del benchmark.
+
+use std::fmt;
+
+use super::container::{self, read_header, CodecId, ContainerHeader};
+use super::error::CompressionError;
+use super::vectors::VectorsView;
+use super::autoencoder_compression::MAX_LAYER;
+use super::mixture_compression::MAX_CLUSTERS;
+use super::symbolic_compression::{MAX_CELLS, MAX_CONTEXT_ORDER};
+use super::dynamics::{DEFAULT_ANALOGUES, DEFAULT_ECHO_STATE, DEFAULT_SINDY_THRESHOLD};
+use super::{
+    attractor_compress_view_with_config, attractor_compress_with_config, attractor_decompress, AttractorConfig,
+    DeltaQuantization, LatentCoder, TrajectoryModel,
+    dmd_compress_view, dmd_compress_with_config, dmd_decompress, DmdConfig,
+    mixture_compress_view, mixture_compress_with_config, mixture_decompress, MixtureConfig,
+    autoencoder_compress_view, autoencoder_compress_with_config, autoencoder_decompress, AutoencoderConfig,
+    symbolic_compress_view, symbolic_compress_with_config, symbolic_decompress, SymbolicConfig,
+    delta_ans_compress, delta_ans_compress_view, delta_ans_decompress,
+    delta_rans_compress, delta_rans_compress_view, delta_rans_decompress,
+    delta_compress, delta_compress_view, delta_decompress,
+    delta_lossless_compress_view, delta_lossless_compress_with_keyframes, delta_lossless_decompress,
+    DEFAULT_KEYFRAME_INTERVAL,
+    gzip_compress, gzip_compress_view, gzip_decompress,
+    int8_compress, int8_compress_view, int8_decompress,
+    polar_delta_compress, polar_delta_compress_view, polar_delta_decompress,
+    zstd_compress_view, zstd_compress_with_level, zstd_decompress,
+    ComponentCount, PcaSolver, RandomizedSvd,
+};
+
+/// Valor de un parámetro de codec configurable por texto
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ParamValue {
+    Int(i64),
+    Float(f64),
+    Bool(bool),
+}
+
+impl ParamValue {
+    /// Interpreta un valor textual: `true`/`false`, entero o flotante
+    pub fn parse(text: &str) -> Option<ParamValue> {
+        let text = text.trim();
+        match text {
+            "true" => Some(ParamValue::Bool(true)),
+            "false" => Some(ParamValue::Bool(false)),
+            _ => text.parse::<i64>().map(ParamValue::Int)
+                .or_else(|_| text.parse::<f64>().map(ParamValue::Float))
+                .ok(),
+        }
+    }
+
+    /// Valor como entero no negativo (acepta flotantes sin parte decimal)
+    pub fn as_usize(&self) -> Option<usize> {
+        match *self {
+            ParamValue::Int(v) if v >= 0 => Some(v as usize),
+            ParamValue::Float(v) if v >= 0.0 && v.fract() == 0.0 => Some(v as usize),
+            _ => None,
+        }
+    }
+
+    /// Valor como entero con signo
+    pub fn as_i64(&self) -> Option<i64> {
+        match *self {
+            ParamValue::Int(v) => Some(v),
+            ParamValue::Float(v) if v.fract() == 0.0 => Some(v as i64),
+            _ => None,
+        }
+    }
+
+    /// Valor como flotante (acepta enteros)
+    pub fn as_f64(&self) -> Option<f64> {
+        match *self {
+            ParamValue::Int(v) => Some(v as f64),
+            ParamValue::Float(v) => Some(v),
+            ParamValue::Bool(_) => None,
+        }
+    }
+
+    /// Valor como booleano
+    pub fn as_bool(&self) -> Option<bool> {
+        match *self {
+            ParamValue::Bool(v) => Some(v),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for ParamValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParamValue::Int(v) => write!(f, "{}", v),
+            ParamValue::Float(v) => write!(f, "{}", v),
+            ParamValue::Bool(v) => write!(f, "{}", v),
+        }
+    }
+}
+
+/// Error al configurar un codec
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParamError {
+    /// No hay codec registrado con ese id
+    UnknownCodec(String),
+    /// El codec no tiene un parámetro con ese nombre
+    UnknownParam { codec: &'static str, name: String },
+    /// El valor no es válido para el parámetro
+    InvalidValue { name: &'static str, value: ParamValue },
+    /// Especificación textual mal formada (`id:clave=valor,...`)
+    MalformedSpec(String),
+}
+
+impl fmt::Display for ParamError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParamError::UnknownCodec(id) => write!(f, "codec desconocido: {}", id),
+            ParamError::UnknownParam { codec, name } => {
+                write!(f, "el codec {} no tiene el parámetro {}", codec, name)
+            }
+            ParamError::InvalidValue { name, value } => {
+                write!(f, "valor inválido para {}: {}", name, value)
+            }
+            ParamError::MalformedSpec(spec) => write!(f, "especificación mal formada: {}", spec),
+        }
+    }
+}
+
+impl std::error::Error for ParamError {}
+
+/// Método de compresión de secuencias de vectores
+pub trait Codec: Send + Sync {
+    /// Identificador estable usado por el registro (p. ej. `"attractor"`)
+    fn id(&self) -> &'static str;
+
+    /// Nombre legible para tablas de resultados (puede incluir parámetros)
+    fn name(&self) -> String;
+
+    /// `true` si la descompresión reproduce exactamente la entrada
+    fn is_lossless(&self) -> bool;
+
+    /// Parámetros configurables con su valor actual
+    fn params(&self) -> Vec<(&'static str, ParamValue)> {
+        Vec::new()
+    }
+
+    /// Modifica un parámetro por nombre
+    fn set_param(&mut self, name: &str, _value: ParamValue) -> Result<(), ParamError> {
+        Err(ParamError::UnknownParam { codec: self.id(), name: name.to_string() })
+    }
+
+    fn compress(&self, vectors: &[Vec<f32>]) -> Result<Vec<u8>, CompressionError>;
+
+    fn decompress(&self, compressed: &[u8]) -> Result<Vec<Vec<f32>>, CompressionError>;
+
+    /// Comprime una vista plana; por defecto copia a vectores anidados
+    fn compress_view(&self, vectors: VectorsView<'_>) -> Result<Vec<u8>, CompressionError> {
+        self.compress(&vectors.to_vecs())
+    }
+
+    /// Descomprime en un buffer del llamador (fila mayor) y devuelve la
+    /// cabecera; por defecto descomprime a vectores anidados y copia
+    fn decompress_into(&self, compressed: &[u8], out: &mut [f32]) -> Result<ContainerHeader, CompressionError> {
+        let (mut header, _) = read_header(compressed)?;
+        let vectors = self.decompress(compressed)?;
+        let needed = vectors.iter().map(|v| v.len()).sum();
+        if out.len() < needed {
+            return Err(CompressionError::BufferTooSmall { needed, available: out.len() });
+        }
+        for (dest, value) in out.iter_mut().zip(vectors.iter().flatten()) {
+            *dest = *value;
+        }
+        header.n = vectors.len();
+        Ok(header)
+    }
+}
+
+/// GZIP sobre los float32 crudos
+#[derive(Debug, Clone, Default)]
+pub struct GzipCodec;
+
+impl Codec for GzipCodec {
+    fn id(&self) -> &'static str { "gzip" }
+    fn name(&self) -> String { "GZIP".to_string() }
+    fn is_lossless(&self) -> bool { true }
+
+    fn compress(&self, vectors: &[Vec<f32>]) -> Result<Vec<u8>, CompressionError> {
+        gzip_compress(vectors)
+    }
+
+    fn decompress(&self, compressed: &[u8]) -> Result<Vec<Vec<f32>>, CompressionError> {
+        gzip_decompress(compressed)
+    }
+
+    fn compress_view(&self, vectors: VectorsView<'_>) -> Result<Vec<u8>, CompressionError> {
+        gzip_compress_view(vectors)
+    }
+
+    fn decompress_into(&self, compressed: &[u8], out: &mut [f32]) -> Result<ContainerHeader, CompressionError> {
+        container::decompress_as_into(compressed, CodecId::Gzip, out)
+    }
+}
+
+/// Cuantización int8 + GZIP
+#[derive(Debug, Clone, Default)]
+pub struct Int8Codec;
+
+impl Codec for Int8Codec {
+    fn id(&self) -> &'static str { "int8_gzip" }
+    fn name(&self) -> String { "Int8+GZIP".to_string() }
+    fn is_lossless(&self) -> bool { false }
+
+    fn compress(&self, vectors: &[Vec<f32>]) -> Result<Vec<u8>, CompressionError> {
+        int8_compress(vectors)
+    }
+
+    fn decompress(&self, compressed: &[u8]) -> Result<Vec<Vec<f32>>, CompressionError> {
+        int8_decompress(compressed)
+    }
+
+    fn compress_view(&self, vectors: VectorsView<'_>) -> Result<Vec<u8>, CompressionError> {
+        int8_compress_view(vectors)
+    }
+
+    fn decompress_into(&self, compressed: &[u8], out: &mut [f32]) -> Result<ContainerHeader, CompressionError> {
+        container::decompress_as_into(compressed, CodecId::Int8Gzip, out)
+    }
+}
+
+/// Deltas float32 + GZIP
+#[derive(Debug, Clone, Default)]
+pub struct DeltaGzipCodec;
+
+impl Codec for DeltaGzipCodec {
+    fn id(&self) -> &'static str { "delta_gzip" }
+    fn name(&self) -> String { "Delta+GZIP".to_string() }
+    fn is_lossless(&self) -> bool { false }
+
+    fn compress(&self, vectors: &[Vec<f32>]) -> Result<Vec<u8>, CompressionError> {
+        delta_compress(vectors)
+    }
+
+    fn decompress(&self, compressed: &[u8]) -> Result<Vec<Vec<f32>>, CompressionError> {
+        delta_decompress(compressed)
+    }
+
+    fn compress_view(&self, vectors: VectorsView<'_>) -> Result<Vec<u8>, CompressionError> {
+        delta_compress_view(vectors)
+    }
+
+    fn decompress_into(&self, compressed: &[u8], out: &mut [f32]) -> Result<ContainerHeader, CompressionError> {
+        container::decompress_as_into(compressed, CodecId::DeltaGzip, out)
+    }
+}
+
+/// Zstd sobre los float32 crudos
+#[derive(Debug, Clone)]
+pub struct ZstdCodec {
+    /// Nivel de compresión de zstd (1-22)
+    pub level: i32,
+}
+
+impl Default for ZstdCodec {
+    fn default() -> Self {
+        ZstdCodec { level: 3 }
+    }
+}
+
+impl Codec for ZstdCodec {
+    fn id(&self) -> &'static str { "zstd" }
+    fn name(&self) -> String { "Zstd".to_string() }
+    fn is_lossless(&self) -> bool { true }
+
+    fn params(&self) -> Vec<(&'static str, ParamValue)> {
+        vec![("level", ParamValue::Int(self.level as i64))]
+    }
+
+    fn set_param(&mut self, name: &str, value: ParamValue) -> Result<(), ParamError> {
+        match name {
+            "level" => {
+                let level = value.as_i64()
+                    .filter(|l| zstd::compression_level_range().contains(&(*l as i32)))
+                    .ok_or(ParamError::InvalidValue { name: "level", value })?;
+                self.level = level as i32;
+                Ok(())
+            }
+            _ => Err(ParamError::UnknownParam { codec: self.id(), name: name.to_string() }),
+        }
+    }
+
+    fn compress(&self, vectors: &[Vec<f32>]) -> Result<Vec<u8>, CompressionError> {
+        zstd_compress_with_level(vectors, self.level)
+    }
+
+    fn decompress(&self, compressed: &[u8]) -> Result<Vec<Vec<f32>>, CompressionError> {
+        zstd_decompress(compressed)
+    }
+
+    fn compress_view(&self, vectors: VectorsView<'_>) -> Result<Vec<u8>, CompressionError> {
+        zstd_compress_view(vectors, self.level)
+    }
+
+    fn decompress_into(&self, compressed: &[u8], out: &mut [f32]) -> Result<ContainerHeader, CompressionError> {
+        container::decompress_as_into(compressed, CodecId::Zstd, out)
+    }
+}
+
+/// Deltas en coordenadas hiperesféricas + GZIP
+#[derive(Debug, Clone, Default)]
+pub struct PolarDeltaCodec;
+
+impl Codec for PolarDeltaCodec {
+    fn id(&self) -> &'static str { "polar_delta" }
+    fn name(&self) -> String { "PolarDelta+GZIP".to_string() }
+    fn is_lossless(&self) -> bool { false }
+
+    fn compress(&self, vectors: &[Vec<f32>]) -> Result<Vec<u8>, CompressionError> {
+        polar_delta_compress(vectors)
+    }
+
+    fn decompress(&self, compressed: &[u8]) -> Result<Vec<Vec<f32>>, CompressionError> {
+        polar_delta_decompress(compressed)
+    }
+
+    fn compress_view(&self, vectors: VectorsView<'_>) -> Result<Vec<u8>, CompressionError> {
+        polar_delta_compress_view(vectors)
+    }
+
+    fn decompress_into(&self, compressed: &[u8], out: &mut [f32]) -> Result<ContainerHeader, CompressionError> {
+        container::decompress_as_into(compressed, CodecId::PolarDelta, out)
+    }
+}
+
+/// Deltas cuantizados a int8 + GZIP (`ans_simple`)
+#[derive(Debug, Clone, Default)]
+pub struct DeltaAnsCodec;
+
+impl Codec for DeltaAnsCodec {
+    fn id(&self) -> &'static str { "delta_ans" }
+    fn name(&self) -> String { "Delta+ANS".to_string() }
+    fn is_lossless(&self) -> bool { false }
+
+    fn compress(&self, vectors: &[Vec<f32>]) -> Result<Vec<u8>, CompressionError> {
+        delta_ans_compress(vectors)
+    }
+
+    fn decompress(&self, compressed: &[u8]) -> Result<Vec<Vec<f32>>, CompressionError> {
+        delta_ans_decompress(compressed)
+    }
+
+    fn compress_view(&self, vectors: VectorsView<'_>) -> Result<Vec<u8>, CompressionError> {
+        delta_ans_compress_view(vectors)
+    }
+
+    fn decompress_into(&self, compressed: &[u8], out: &mut [f32]) -> Result<ContainerHeader, CompressionError> {
+        container::decompress_as_into(compressed, CodecId::DeltaAns, out)
+    }
+}
+
+/// Deltas int8 + ANS de `constriction` (misma cuantización que `DeltaAnsCodec`)
+#[derive(Debug, Clone, Default)]
+pub struct DeltaRansCodec;
+
+impl Codec for DeltaRansCodec {
+    fn id(&self) -> &'static str { "delta_rans" }
+    fn name(&self) -> String { "Delta+rANS".to_string() }
+    fn is_lossless(&self) -> bool { false }
+
+    fn compress(&self, vectors: &[Vec<f32>]) -> Result<Vec<u8>, CompressionError> {
+        delta_rans_compress(vectors)
+    }
+
+    fn decompress(&self, compressed: &[u8]) -> Result<Vec<Vec<f32>>, CompressionError> {
+        delta_rans_decompress(compressed)
+    }
+
+    fn compress_view(&self, vectors: VectorsView<'_>) -> Result<Vec<u8>, CompressionError> {
+        delta_rans_compress_view(vectors)
+    }
+
+    fn decompress_into(&self, compressed: &[u8], out: &mut [f32]) -> Result<ContainerHeader, CompressionError> {
+        container::decompress_as_into(compressed, CodecId::DeltaRans, out)
+    }
+}
+
+/// Deltas float32 + RLE + GZIP
+#[derive(Debug, Clone)]
+pub struct DeltaLosslessCodec {
+    /// Vectores por bloque entre keyframes (0 = un único bloque)
+    pub keyframe_interval: usize,
+}
+
+impl Default for DeltaLosslessCodec {
+    fn default() -> Self {
+        DeltaLosslessCodec { keyframe_interval: DEFAULT_KEYFRAME_INTERVAL }
+    }
+}
+
+impl Codec for DeltaLosslessCodec {
+    fn id(&self) -> &'static str { "delta_lossless" }
+    fn name(&self) -> String { "Delta+RLE+GZIP".to_string() }
+    fn is_lossless(&self) -> bool { true }
+
+    fn params(&self) -> Vec<(&'static str, ParamValue)> {
+        vec![("keyframe_interval", ParamValue::Int(self.keyframe_interval as i64))]
+    }
+
+    fn set_param(&mut self, name: &str, value: ParamValue) -> Result<(), ParamError> {
+        match name {
+            "keyframe_interval" => {
+                self.keyframe_interval = value.as_usize()
+                    .filter(|&k| u32::try_from(k).is_ok())
+                    .ok_or(ParamError::InvalidValue { name: "keyframe_interval", value })?;
+                Ok(())
+            }
+            _ => Err(ParamError::UnknownParam { codec: self.id(), name: name.to_string() }),
+        }
+    }
+
+    fn compress(&self, vectors: &[Vec<f32>]) -> Result<Vec<u8>, CompressionError> {
+        delta_lossless_compress_with_keyframes(vectors, self.keyframe_interval)
+    }
+
+    fn decompress(&self, compressed: &[u8]) -> Result<Vec<Vec<f32>>, CompressionError> {
+        delta_lossless_decompress(compressed)
+    }
+
+    fn compress_view(&self, vectors: VectorsView<'_>) -> Result<Vec<u8>, CompressionError> {
+        delta_lossless_compress_view(vectors, self.keyframe_interval)
+    }
+
+    fn decompress_into(&self, compressed: &[u8], out: &mut [f32]) -> Result<ContainerHeader, CompressionError> {
+        container::decompress_as_into(compressed, CodecId::DeltaLossless, out)
+    }
+}
+
+/// Compresión basada en atractor (PCA + deltas en espacio reducido)
+#[derive(Debug, Clone)]
+pub struct AttractorCodec {
+    /// Número de componentes retenidas, fijo o por objetivo
+    pub components: ComponentCount,
+
+    /// Método de cálculo de la base PCA
+    pub solver: PcaSolver,
+
+    /// Cota de error absoluto por coordenada (capa de residuos)
+    pub error_bound: Option<f64>,
+
+    /// Paso de cuantización de los deltas latentes
+    pub quantization: DeltaQuantization,
+
+    /// Predictor de la trayectoria latente
+    pub model: TrajectoryModel,
+
+    /// Tolerancia del modo de sombreado (puntos de control en vez de residuos)
+    pub shadowing: Option<f64>,
+
+    /// Codificador de entropía de los residuos latentes
+    pub latent_coder: LatentCoder,
+}
+
+impl Default for AttractorCodec {
+    fn default() -> Self {
+        let config = AttractorConfig::default();
+        AttractorCodec {
+            components: config.components,
+            solver: config.solver,
+            error_bound: config.error_bound,
+            quantization: config.quantization,
+            model: config.model,
+            shadowing: config.shadowing,
+            latent_coder: config.latent_coder,
+        }
+    }
+}
+
+impl AttractorCodec {
+    fn config(&self) -> AttractorConfig {
+        AttractorConfig {
+            components: self.components,
+            solver: self.solver,
+            error_bound: self.error_bound,
+            quantization: self.quantization,
+            model: self.model,
+            shadowing: self.shadowing,
+            latent_coder: self.latent_coder,
+        }
+    }
+
+    /// Parámetros de la SVD aleatorizada; activarlos cambia a ese método
+    /// Parámetros de la red de eco (reservorio, radio espectral, fuga,
+    /// semilla), activándola con los valores por defecto si hace falta
+    fn echo_state_mut(&mut self) -> (&mut usize, &mut f64, &mut f64, &mut u64) {
+        if !matches!(self.model, TrajectoryModel::EchoState { .. }) {
+            self.model = DEFAULT_ECHO_STATE;
+        }
+        match &mut self.model {
+            TrajectoryModel::EchoState { reservoir, spectral_radius, leak, seed } => {
+                (reservoir, spectral_radius, leak, seed)
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    /// Parámetros del método de análogos (embedding, retardo, vecinos),
+    /// activándolo con los valores por defecto si hace falta
+    fn analogues_mut(&mut self) -> (&mut usize, &mut usize, &mut usize) {
+        if !matches!(self.model, TrajectoryModel::Analogues { .. }) {
+            self.model = DEFAULT_ANALOGUES;
+        }
+        match &mut self.model {
+            TrajectoryModel::Analogues { embedding, delay, neighbors } => (embedding, delay, neighbors),
+            _ => unreachable!(),
+        }
+    }
+
+    fn randomized_mut(&mut self) -> &mut RandomizedSvd {
+        if self.solver == PcaSolver::Exact {
+            self.solver = PcaSolver::Randomized(RandomizedSvd::default());
+        }
+        match &mut self.solver {
+            PcaSolver::Randomized(params) => params,
+            PcaSolver::Exact => unreachable!(),
+        }
+    }
+}
+
+impl Codec for AttractorCodec {
+    fn id(&self) -> &'static str { "attractor" }
+    fn name(&self) -> String {
+        let solver = match self.solver {
+            PcaSolver::Exact => "PCA",
+            PcaSolver::Randomized(_) => "rPCA",
+        };
+        let components = match self.components {
+            ComponentCount::Fixed(k) => format!("{}-{}", solver, k),
+            ComponentCount::ExplainedVariance(target) => format!("{}-EV{}", solver, target),
+            ComponentCount::MaxCosineLoss(loss) => format!("{}-cos{}", solver, loss),
+        };
+        let model = match self.model {
+            TrajectoryModel::Delta => String::new(),
+            TrajectoryModel::Var { order } => format!(", VAR{}", order),
+            TrajectoryModel::Sindy { degree, .. } => format!(", SINDy{}", degree),
+            TrajectoryModel::EchoState { reservoir, .. } => format!(", ESN{}", reservoir),
+            TrajectoryModel::Analogues { embedding, neighbors, .. } => format!(", Analog{}x{}", embedding, neighbors),
+        };
+        let shadowing = match (self.shadowing, self.latent_coder) {
+            (Some(tol), _) => format!(", shadow{}", tol),
+            (None, LatentCoder::Ans) => ", ANS".to_string(),
+            (None, LatentCoder::Gzip) => String::new(),
+        };
+        match self.error_bound {
+            Some(eps) => format!("Attractor({}{}{}, ε={})", components, model, shadowing, eps),
+            None => format!("Attractor({}{}{})", components, model, shadowing),
+        }
+    }
+    fn is_lossless(&self) -> bool { false }
+
+    fn params(&self) -> Vec<(&'static str, ParamValue)> {
+        let mut params = vec![match self.components {
+            ComponentCount::Fixed(k) => ("n_components", ParamValue::Int(k as i64)),
+            ComponentCount::ExplainedVariance(target) => ("explained_variance", ParamValue::Float(target)),
+            ComponentCount::MaxCosineLoss(loss) => ("max_cosine_loss", ParamValue::Float(loss)),
+        }];
+        match self.solver {
+            PcaSolver::Exact => params.push(("randomized_svd", ParamValue::Bool(false))),
+            PcaSolver::Randomized(svd) => {
+                params.push(("randomized_svd", ParamValue::Bool(true)));
+                params.push(("oversampling", ParamValue::Int(svd.oversampling as i64)));
+                params.push(("power_iterations", ParamValue::Int(svd.power_iterations as i64)));
+            }
+        }
+        if let Some(eps) = self.error_bound {
+            params.push(("error_bound", ParamValue::Float(eps)));
+        }
+        match self.quantization {
+            DeltaQuantization::Adaptive => {}
+            DeltaQuantization::TargetDistortion(d) => params.push(("target_distortion", ParamValue::Float(d))),
+            DeltaQuantization::Fixed(step) => params.push(("delta_step", ParamValue::Float(step))),
+        }
+        match self.model {
+            TrajectoryModel::Delta => {}
+            TrajectoryModel::Var { order } => params.push(("var_order", ParamValue::Int(order as i64))),
+            TrajectoryModel::Sindy { degree, threshold } => {
+                params.push(("sindy_degree", ParamValue::Int(degree as i64)));
+                params.push(("sindy_threshold", ParamValue::Float(threshold)));
+            }
+            TrajectoryModel::EchoState { reservoir, spectral_radius, leak, seed } => {
+                params.push(("esn_reservoir", ParamValue::Int(reservoir as i64)));
+                params.push(("esn_spectral_radius", ParamValue::Float(spectral_radius)));
+                params.push(("esn_leak", ParamValue::Float(leak)));
+                params.push(("esn_seed", ParamValue::Int(seed as i64)));
+            }
+            TrajectoryModel::Analogues { embedding, delay, neighbors } => {
+                params.push(("analogue_embedding", ParamValue::Int(embedding as i64)));
+                params.push(("analogue_delay", ParamValue::Int(delay as i64)));
+                params.push(("analogue_neighbors", ParamValue::Int(neighbors as i64)));
+            }
+        }
+        if let Some(tol) = self.shadowing {
+            params.push(("shadowing_tolerance", ParamValue::Float(tol)));
+        }
+        if self.latent_coder == LatentCoder::Ans {
+            params.push(("latent_ans", ParamValue::Bool(true)));
+        }
+        params
+    }
+
+    fn set_param(&mut self, name: &str, value: ParamValue) -> Result<(), ParamError> {
+        match name {
+            "error_bound" => {
+                // 0 desactiva la capa de residuos
+                let eps = value.as_f64()
+                    .filter(|&eps| eps.is_finite() && eps >= 0.0)
+                    .ok_or(ParamError::InvalidValue { name: "error_bound", value })?;
+                self.error_bound = (eps > 0.0).then_some(eps);
+                Ok(())
+            }
+            "target_distortion" | "delta_step" => {
+                // 0 vuelve al paso adaptativo
+                let param = if name == "delta_step" { "delta_step" } else { "target_distortion" };
+                let d = value.as_f64()
+                    .filter(|&d| d.is_finite() && d >= 0.0)
+                    .ok_or(ParamError::InvalidValue { name: param, value })?;
+                self.quantization = match (d > 0.0, param) {
+                    (false, _) => DeltaQuantization::Adaptive,
+                    (true, "delta_step") => DeltaQuantization::Fixed(d),
+                    (true, _) => DeltaQuantization::TargetDistortion(d),
+                };
+                Ok(())
+            }
+            "shadowing_tolerance" => {
+                // 0 desactiva el modo de sombreado
+                let tol = value.as_f64()
+                    .filter(|&tol| tol.is_finite() && tol >= 0.0)
+                    .ok_or(ParamError::InvalidValue { name: "shadowing_tolerance", value })?;
+                self.shadowing = (tol > 0.0).then_some(tol);
+                Ok(())
+            }
+            "latent_ans" => {
+                let ans = value.as_bool()
+                    .ok_or(ParamError::InvalidValue { name: "latent_ans", value })?;
+                self.latent_coder = if ans { LatentCoder::Ans } else { LatentCoder::Gzip };
+                Ok(())
+            }
+            "var_order" => {
+                // 0 vuelve al predictor de deltas
+                let order = value.as_usize()
+                    .ok_or(ParamError::InvalidValue { name: "var_order", value })?;
+                self.model = if order > 0 { TrajectoryModel::Var { order } } else { TrajectoryModel::Delta };
+                Ok(())
+            }
+            "sindy_degree" => {
+                // 0 vuelve al predictor de deltas
+                let degree = value.as_usize()
+                    .ok_or(ParamError::InvalidValue { name: "sindy_degree", value })?;
+                let threshold = match self.model {
+                    TrajectoryModel::Sindy { threshold, .. } => threshold,
+                    _ => DEFAULT_SINDY_THRESHOLD,
+                };
+                self.model = if degree > 0 { TrajectoryModel::Sindy { degree, threshold } } else { TrajectoryModel::Delta };
+                Ok(())
+            }
+            "sindy_threshold" => {
+                let threshold = value.as_f64()
+                    .filter(|&t| t.is_finite() && t >= 0.0)
+                    .ok_or(ParamError::InvalidValue { name: "sindy_threshold", value })?;
+                let degree = match self.model {
+                    TrajectoryModel::Sindy { degree, .. } => degree,
+                    _ => 2,
+                };
+                self.model = TrajectoryModel::Sindy { degree, threshold };
+                Ok(())
+            }
+            "esn_reservoir" => {
+                // 0 vuelve al predictor de deltas
+                let size = value.as_usize()
+                    .ok_or(ParamError::InvalidValue { name: "esn_reservoir", value })?;
+                if size > 0 {
+                    *self.echo_state_mut().0 = size;
+                } else {
+                    self.model = TrajectoryModel::Delta;
+                }
+                Ok(())
+            }
+            "esn_spectral_radius" => {
+                *self.echo_state_mut().1 = value.as_f64()
+                    .filter(|&rho| rho.is_finite() && rho > 0.0)
+                    .ok_or(ParamError::InvalidValue { name: "esn_spectral_radius", value })?;
+                Ok(())
+            }
+            "esn_leak" => {
+                *self.echo_state_mut().2 = value.as_f64()
+                    .filter(|&leak| leak > 0.0 && leak <= 1.0)
+                    .ok_or(ParamError::InvalidValue { name: "esn_leak", value })?;
+                Ok(())
+            }
+            "analogue_embedding" => {
+                // 0 vuelve al predictor de deltas
+                let embedding = value.as_usize()
+                    .ok_or(ParamError::InvalidValue { name: "analogue_embedding", value })?;
+                if embedding > 0 {
+                    *self.analogues_mut().0 = embedding;
+                } else {
+                    self.model = TrajectoryModel::Delta;
+                }
+                Ok(())
+            }
+            "analogue_delay" => {
+                *self.analogues_mut().1 = value.as_usize()
+                    .filter(|&delay| delay > 0)
+                    .ok_or(ParamError::InvalidValue { name: "analogue_delay", value })?;
+                Ok(())
+            }
+            "analogue_neighbors" => {
+                *self.analogues_mut().2 = value.as_usize()
+                    .filter(|&neighbors| neighbors > 0)
+                    .ok_or(ParamError::InvalidValue { name: "analogue_neighbors", value })?;
+                Ok(())
+            }
+            "esn_seed" => {
+                *self.echo_state_mut().3 = value.as_usize()
+                    .ok_or(ParamError::InvalidValue { name: "esn_seed", value })? as u64;
+                Ok(())
+            }
+            "n_components" => {
+                let k = value.as_usize()
+                    .filter(|&k| k > 0)
+                    .ok_or(ParamError::InvalidValue { name: "n_components", value })?;
+                self.components = ComponentCount::Fixed(k);
+                Ok(())
+            }
+            "explained_variance" => {
+                let target = value.as_f64()
+                    .filter(|&target| target > 0.0 && target <= 1.0)
+                    .ok_or(ParamError::InvalidValue { name: "explained_variance", value })?;
+                self.components = ComponentCount::ExplainedVariance(target);
+                Ok(())
+            }
+            "max_cosine_loss" => {
+                let loss = value.as_f64()
+                    .filter(|&loss| (0.0..1.0).contains(&loss))
+                    .ok_or(ParamError::InvalidValue { name: "max_cosine_loss", value })?;
+                self.components = ComponentCount::MaxCosineLoss(loss);
+                Ok(())
+            }
+            "randomized_svd" => {
+                let randomized = value.as_bool()
+                    .ok_or(ParamError::InvalidValue { name: "randomized_svd", value })?;
+                if randomized {
+                    self.randomized_mut();
+                } else {
+                    self.solver = PcaSolver::Exact;
+                }
+                Ok(())
+            }
+            "oversampling" => {
+                self.randomized_mut().oversampling = value.as_usize()
+                    .ok_or(ParamError::InvalidValue { name: "oversampling", value })?;
+                Ok(())
+            }
+            "power_iterations" => {
+                self.randomized_mut().power_iterations = value.as_usize()
+                    .ok_or(ParamError::InvalidValue { name: "power_iterations", value })?;
+                Ok(())
+            }
+            _ => Err(ParamError::UnknownParam { codec: self.id(), name: name.to_string() }),
+        }
+    }
+
+    fn compress(&self, vectors: &[Vec<f32>]) -> Result<Vec<u8>, CompressionError> {
+        attractor_compress_with_config(vectors, &self.config())
+    }
+
+    fn decompress(&self, compressed: &[u8]) -> Result<Vec<Vec<f32>>, CompressionError> {
+        attractor_decompress(compressed)
+    }
+
+    fn compress_view(&self, vectors: VectorsView<'_>) -> Result<Vec<u8>, CompressionError> {
+        attractor_compress_view_with_config(vectors, &self.config())
+    }
+
+    fn decompress_into(&self, compressed: &[u8], out: &mut [f32]) -> Result<ContainerHeader, CompressionError> {
+        container::decompress_as_into(compressed, CodecId::Attractor, out)
+    }
+}
+
+/// Descomposición en modos dinámicos (DMD) sobre una reducción PCA
+#[derive(Debug, Clone, Default)]
+pub struct DmdCodec {
+    pub config: DmdConfig,
+}
+
+impl Codec for DmdCodec {
+    fn id(&self) -> &'static str { "dmd" }
+    fn name(&self) -> String {
+        match self.config.modes {
+            ComponentCount::Fixed(k) => format!("DMD-{}", k),
+            ComponentCount::ExplainedVariance(target) => format!("DMD-EV{}", target),
+            ComponentCount::MaxCosineLoss(loss) => format!("DMD-cos{}", loss),
+        }
+    }
+    fn is_lossless(&self) -> bool { false }
+
+    fn params(&self) -> Vec<(&'static str, ParamValue)> {
+        let mut params = vec![match self.config.modes {
+            ComponentCount::Fixed(k) => ("n_modes", ParamValue::Int(k as i64)),
+            ComponentCount::ExplainedVariance(target) => ("explained_variance", ParamValue::Float(target)),
+            ComponentCount::MaxCosineLoss(loss) => ("max_cosine_loss", ParamValue::Float(loss)),
+        }];
+        match self.config.quantization {
+            DeltaQuantization::Adaptive => {}
+            DeltaQuantization::TargetDistortion(d) => params.push(("target_distortion", ParamValue::Float(d))),
+            DeltaQuantization::Fixed(step) => params.push(("delta_step", ParamValue::Float(step))),
+        }
+        params
+    }
+
+    fn set_param(&mut self, name: &str, value: ParamValue) -> Result<(), ParamError> {
+        match name {
+            "n_modes" => {
+                let k = value.as_usize()
+                    .filter(|&k| k > 0)
+                    .ok_or(ParamError::InvalidValue { name: "n_modes", value })?;
+                self.config.modes = ComponentCount::Fixed(k);
+                Ok(())
+            }
+            "explained_variance" => {
+                let target = value.as_f64()
+                    .filter(|&target| target > 0.0 && target <= 1.0)
+                    .ok_or(ParamError::InvalidValue { name: "explained_variance", value })?;
+                self.config.modes = ComponentCount::ExplainedVariance(target);
+                Ok(())
+            }
+            "max_cosine_loss" => {
+                let loss = value.as_f64()
+                    .filter(|&loss| (0.0..1.0).contains(&loss))
+                    .ok_or(ParamError::InvalidValue { name: "max_cosine_loss", value })?;
+                self.config.modes = ComponentCount::MaxCosineLoss(loss);
+                Ok(())
+            }
+            "target_distortion" | "delta_step" => {
+                // 0 vuelve al paso adaptativo
+                let param = if name == "delta_step" { "delta_step" } else { "target_distortion" };
+                let d = value.as_f64()
+                    .filter(|&d| d.is_finite() && d >= 0.0)
+                    .ok_or(ParamError::InvalidValue { name: param, value })?;
+                self.config.quantization = match (d > 0.0, param) {
+                    (false, _) => DeltaQuantization::Adaptive,
+                    (true, "delta_step") => DeltaQuantization::Fixed(d),
+                    (true, _) => DeltaQuantization::TargetDistortion(d),
+                };
+                Ok(())
+            }
+            _ => Err(ParamError::UnknownParam { codec: self.id(), name: name.to_string() }),
+        }
+    }
+
+    fn compress(&self, vectors: &[Vec<f32>]) -> Result<Vec<u8>, CompressionError> {
+        dmd_compress_with_config(vectors, &self.config)
+    }
+
+    fn decompress(&self, compressed: &[u8]) -> Result<Vec<Vec<f32>>, CompressionError> {
+        dmd_decompress(compressed)
+    }
+
+    fn compress_view(&self, vectors: VectorsView<'_>) -> Result<Vec<u8>, CompressionError> {
+        dmd_compress_view(vectors, &self.config)
+    }
+
+    fn decompress_into(&self, compressed: &[u8], out: &mut [f32]) -> Result<ContainerHeader, CompressionError> {
+        container::decompress_as_into(compressed, CodecId::Dmd, out)
+    }
+}
+
+/// Mezcla de PCA: k-means y una base local por grupo
+#[derive(Debug, Clone, Default)]
+pub struct MixturePcaCodec {
+    pub config: MixtureConfig,
+}
+
+impl Codec for MixturePcaCodec {
+    fn id(&self) -> &'static str { "mixture_pca" }
+    fn name(&self) -> String {
+        let components = match self.config.components {
+            ComponentCount::Fixed(k) => format!("PCA-{}", k),
+            ComponentCount::ExplainedVariance(target) => format!("PCA-EV{}", target),
+            ComponentCount::MaxCosineLoss(loss) => format!("PCA-cos{}", loss),
+        };
+        format!("MixPCA({}×{})", self.config.clusters, components)
+    }
+    fn is_lossless(&self) -> bool { false }
+
+    fn params(&self) -> Vec<(&'static str, ParamValue)> {
+        let mut params = vec![
+            ("n_clusters", ParamValue::Int(self.config.clusters as i64)),
+            match self.config.components {
+                ComponentCount::Fixed(k) => ("n_components", ParamValue::Int(k as i64)),
+                ComponentCount::ExplainedVariance(target) => ("explained_variance", ParamValue::Float(target)),
+                ComponentCount::MaxCosineLoss(loss) => ("max_cosine_loss", ParamValue::Float(loss)),
+            },
+            ("kmeans_iterations", ParamValue::Int(self.config.iterations as i64)),
+            ("seed", ParamValue::Int(self.config.seed as i64)),
+        ];
+        match self.config.quantization {
+            DeltaQuantization::Adaptive => {}
+            DeltaQuantization::TargetDistortion(d) => params.push(("target_distortion", ParamValue::Float(d))),
+            DeltaQuantization::Fixed(step) => params.push(("delta_step", ParamValue::Float(step))),
+        }
+        params
+    }
+
+    fn set_param(&mut self, name: &str, value: ParamValue) -> Result<(), ParamError> {
+        match name {
+            "n_clusters" => {
+                self.config.clusters = value.as_usize()
+                    .filter(|&c| c > 0 && c <= MAX_CLUSTERS)
+                    .ok_or(ParamError::InvalidValue { name: "n_clusters", value })?;
+                Ok(())
+            }
+            "n_components" => {
+                let k = value.as_usize()
+                    .filter(|&k| k > 0)
+                    .ok_or(ParamError::InvalidValue { name: "n_components", value })?;
+                self.config.components = ComponentCount::Fixed(k);
+                Ok(())
+            }
+            "explained_variance" => {
+                let target = value.as_f64()
+                    .filter(|&target| target > 0.0 && target <= 1.0)
+                    .ok_or(ParamError::InvalidValue { name: "explained_variance", value })?;
+                self.config.components = ComponentCount::ExplainedVariance(target);
+                Ok(())
+            }
+            "max_cosine_loss" => {
+                let loss = value.as_f64()
+                    .filter(|&loss| (0.0..1.0).contains(&loss))
+                    .ok_or(ParamError::InvalidValue { name: "max_cosine_loss", value })?;
+                self.config.components = ComponentCount::MaxCosineLoss(loss);
+                Ok(())
+            }
+            "kmeans_iterations" => {
+                self.config.iterations = value.as_usize()
+                    .ok_or(ParamError::InvalidValue { name: "kmeans_iterations", value })?;
+                Ok(())
+            }
+            "seed" => {
+                self.config.seed = value.as_usize()
+                    .ok_or(ParamError::InvalidValue { name: "seed", value })? as u64;
+                Ok(())
+            }
+            "target_distortion" | "delta_step" => {
+                // 0 vuelve al paso adaptativo
+                let param = if name == "delta_step" { "delta_step" } else { "target_distortion" };
+                let d = value.as_f64()
+                    .filter(|&d| d.is_finite() && d >= 0.0)
+                    .ok_or(ParamError::InvalidValue { name: param, value })?;
+                self.config.quantization = match (d > 0.0, param) {
+                    (false, _) => DeltaQuantization::Adaptive,
+                    (true, "delta_step") => DeltaQuantization::Fixed(d),
+                    (true, _) => DeltaQuantization::TargetDistortion(d),
+                };
+                Ok(())
+            }
+            _ => Err(ParamError::UnknownParam { codec: self.id(), name: name.to_string() }),
+        }
+    }
+
+    fn compress(&self, vectors: &[Vec<f32>]) -> Result<Vec<u8>, CompressionError> {
+        mixture_compress_with_config(vectors, &self.config)
+    }
+
+    fn decompress(&self, compressed: &[u8]) -> Result<Vec<Vec<f32>>, CompressionError> {
+        mixture_decompress(compressed)
+    }
+
+    fn compress_view(&self, vectors: VectorsView<'_>) -> Result<Vec<u8>, CompressionError> {
+        mixture_compress_view(vectors, &self.config)
+    }
+
+    fn decompress_into(&self, compressed: &[u8], out: &mut [f32]) -> Result<ContainerHeader, CompressionError> {
+        container::decompress_as_into(compressed, CodecId::MixturePca, out)
+    }
+}
+
+/// Autoencoder MLP entrenado al comprimir; guarda el decodificador
+#[derive(Debug, Clone, Default)]
+pub struct AutoencoderCodec {
+    pub config: AutoencoderConfig,
+}
+
+impl Codec for AutoencoderCodec {
+    fn id(&self) -> &'static str { "autoencoder" }
+    fn name(&self) -> String {
+        match self.config.error_bound {
+            Some(eps) => format!("AE-{}(H{}, ε={})", self.config.latent, self.config.hidden, eps),
+            None => format!("AE-{}(H{})", self.config.latent, self.config.hidden),
+        }
+    }
+    fn is_lossless(&self) -> bool { false }
+
+    fn params(&self) -> Vec<(&'static str, ParamValue)> {
+        let mut params = vec![
+            ("latent", ParamValue::Int(self.config.latent as i64)),
+            ("hidden", ParamValue::Int(self.config.hidden as i64)),
+            ("epochs", ParamValue::Int(self.config.epochs as i64)),
+            ("learning_rate", ParamValue::Float(self.config.learning_rate)),
+            ("seed", ParamValue::Int(self.config.seed as i64)),
+        ];
+        if let Some(eps) = self.config.error_bound {
+            params.push(("error_bound", ParamValue::Float(eps)));
+        }
+        match self.config.quantization {
+            DeltaQuantization::Adaptive => {}
+            DeltaQuantization::TargetDistortion(d) => params.push(("target_distortion", ParamValue::Float(d))),
+            DeltaQuantization::Fixed(step) => params.push(("delta_step", ParamValue::Float(step))),
+        }
+        params
+    }
+
+    fn set_param(&mut self, name: &str, value: ParamValue) -> Result<(), ParamError> {
+        match name {
+            "latent" | "hidden" => {
+                let param = if name == "latent" { "latent" } else { "hidden" };
+                let size = value.as_usize()
+                    .filter(|&size| size > 0 && size <= MAX_LAYER)
+                    .ok_or(ParamError::InvalidValue { name: param, value })?;
+                if param == "latent" {
+                    self.config.latent = size;
+                } else {
+                    self.config.hidden = size;
+                }
+                Ok(())
+            }
+            "epochs" => {
+                self.config.epochs = value.as_usize()
+                    .ok_or(ParamError::InvalidValue { name: "epochs", value })?;
+                Ok(())
+            }
+            "learning_rate" => {
+                self.config.learning_rate = value.as_f64()
+                    .filter(|&lr| lr.is_finite() && lr > 0.0)
+                    .ok_or(ParamError::InvalidValue { name: "learning_rate", value })?;
+                Ok(())
+            }
+            "seed" => {
+                self.config.seed = value.as_usize()
+                    .ok_or(ParamError::InvalidValue { name: "seed", value })? as u64;
+                Ok(())
+            }
+            "error_bound" => {
+                // 0 desactiva la capa de residuos
+                let eps = value.as_f64()
+                    .filter(|&eps| eps.is_finite() && eps >= 0.0)
+                    .ok_or(ParamError::InvalidValue { name: "error_bound", value })?;
+                self.config.error_bound = (eps > 0.0).then_some(eps);
+                Ok(())
+            }
+            "target_distortion" | "delta_step" => {
+                // 0 vuelve al paso adaptativo
+                let param = if name == "delta_step" { "delta_step" } else { "target_distortion" };
+                let d = value.as_f64()
+                    .filter(|&d| d.is_finite() && d >= 0.0)
+                    .ok_or(ParamError::InvalidValue { name: param, value })?;
+                self.config.quantization = match (d > 0.0, param) {
+                    (false, _) => DeltaQuantization::Adaptive,
+                    (true, "delta_step") => DeltaQuantization::Fixed(d),
+                    (true, _) => DeltaQuantization::TargetDistortion(d),
+                };
+                Ok(())
+            }
+            _ => Err(ParamError::UnknownParam { codec: self.id(), name: name.to_string() }),
+        }
+    }
+
+    fn compress(&self, vectors: &[Vec<f32>]) -> Result<Vec<u8>, CompressionError> {
+        autoencoder_compress_with_config(vectors, &self.config)
+    }
+
+    fn decompress(&self, compressed: &[u8]) -> Result<Vec<Vec<f32>>, CompressionError> {
+        autoencoder_decompress(compressed)
+    }
+
+    fn compress_view(&self, vectors: VectorsView<'_>) -> Result<Vec<u8>, CompressionError> {
+        autoencoder_compress_view(vectors, &self.config)
+    }
+
+    fn decompress_into(&self, compressed: &[u8], out: &mut [f32]) -> Result<ContainerHeader, CompressionError> {
+        container::decompress_as_into(compressed, CodecId::Autoencoder, out)
+    }
+}
+
+/// Dinámica simbólica: celdas de k-means, contexto de orden k y refinamientos
+#[derive(Debug, Clone, Default)]
+pub struct SymbolicCodec {
+    pub config: SymbolicConfig,
+}
+
+impl Codec for SymbolicCodec {
+    fn id(&self) -> &'static str { "symbolic" }
+    fn name(&self) -> String {
+        let components = match self.config.components {
+            ComponentCount::Fixed(k) => format!("PCA-{}", k),
+            ComponentCount::ExplainedVariance(target) => format!("PCA-EV{}", target),
+            ComponentCount::MaxCosineLoss(loss) => format!("PCA-cos{}", loss),
+        };
+        format!("Symbolic({}, C{}, ctx{})", components, self.config.cells, self.config.order)
+    }
+    fn is_lossless(&self) -> bool { false }
+
+    fn params(&self) -> Vec<(&'static str, ParamValue)> {
+        let mut params = vec![
+            match self.config.components {
+                ComponentCount::Fixed(k) => ("n_components", ParamValue::Int(k as i64)),
+                ComponentCount::ExplainedVariance(target) => ("explained_variance", ParamValue::Float(target)),
+                ComponentCount::MaxCosineLoss(loss) => ("max_cosine_loss", ParamValue::Float(loss)),
+            },
+            ("cells", ParamValue::Int(self.config.cells as i64)),
+            ("context_order", ParamValue::Int(self.config.order as i64)),
+            ("kmeans_iterations", ParamValue::Int(self.config.iterations as i64)),
+            ("seed", ParamValue::Int(self.config.seed as i64)),
+        ];
+        match self.config.quantization {
+            DeltaQuantization::Adaptive => {}
+            DeltaQuantization::TargetDistortion(d) => params.push(("target_distortion", ParamValue::Float(d))),
+            DeltaQuantization::Fixed(step) => params.push(("delta_step", ParamValue::Float(step))),
+        }
+        params
+    }
+
+    fn set_param(&mut self, name: &str, value: ParamValue) -> Result<(), ParamError> {
+        match name {
+            "n_components" => {
+                let k = value.as_usize()
+                    .filter(|&k| k > 0)
+                    .ok_or(ParamError::InvalidValue { name: "n_components", value })?;
+                self.config.components = ComponentCount::Fixed(k);
+                Ok(())
+            }
+            "explained_variance" => {
+                let target = value.as_f64()
+                    .filter(|&target| target > 0.0 && target <= 1.0)
+                    .ok_or(ParamError::InvalidValue { name: "explained_variance", value })?;
+                self.config.components = ComponentCount::ExplainedVariance(target);
+                Ok(())
+            }
+            "max_cosine_loss" => {
+                let loss = value.as_f64()
+                    .filter(|&loss| (0.0..1.0).contains(&loss))
+                    .ok_or(ParamError::InvalidValue { name: "max_cosine_loss", value })?;
+                self.config.components = ComponentCount::MaxCosineLoss(loss);
+                Ok(())
+            }
+            "cells" => {
+                self.config.cells = value.as_usize()
+                    .filter(|&c| c > 0 && c <= MAX_CELLS)
+                    .ok_or(ParamError::InvalidValue { name: "cells", value })?;
+                Ok(())
+            }
+            "context_order" => {
+                self.config.order = value.as_usize()
+                    .filter(|&order| order <= MAX_CONTEXT_ORDER)
+                    .ok_or(ParamError::InvalidValue { name: "context_order", value })?;
+                Ok(())
+            }
+            "kmeans_iterations" => {
+                self.config.iterations = value.as_usize()
+                    .ok_or(ParamError::InvalidValue { name: "kmeans_iterations", value })?;
+                Ok(())
+            }
+            "seed" => {
+                self.config.seed = value.as_usize()
+                    .ok_or(ParamError::InvalidValue { name: "seed", value })? as u64;
+                Ok(())
+            }
+            "target_distortion" | "delta_step" => {
+                // 0 vuelve al paso adaptativo
+                let param = if name == "delta_step" { "delta_step" } else { "target_distortion" };
+                let d = value.as_f64()
+                    .filter(|&d| d.is_finite() && d >= 0.0)
+                    .ok_or(ParamError::InvalidValue { name: param, value })?;
+                self.config.quantization = match (d > 0.0, param) {
+                    (false, _) => DeltaQuantization::Adaptive,
+                    (true, "delta_step") => DeltaQuantization::Fixed(d),
+                    (true, _) => DeltaQuantization::TargetDistortion(d),
+                };
+                Ok(())
+            }
+            _ => Err(ParamError::UnknownParam { codec: self.id(), name: name.to_string() }),
+        }
+    }
+
+    fn compress(&self, vectors: &[Vec<f32>]) -> Result<Vec<u8>, CompressionError> {
+        symbolic_compress_with_config(vectors, &self.config)
+    }
+
+    fn decompress(&self, compressed: &[u8]) -> Result<Vec<Vec<f32>>, CompressionError> {
+        symbolic_decompress(compressed)
+    }
+
+    fn compress_view(&self, vectors: VectorsView<'_>) -> Result<Vec<u8>, CompressionError> {
+        symbolic_compress_view(vectors, &self.config)
+    }
+
+    fn decompress_into(&self, compressed: &[u8], out: &mut [f32]) -> Result<ContainerHeader, CompressionError> {
+        container::decompress_as_into(compressed, CodecId::Symbolic, out)
+    }
+}
+
+/// Constructor de un codec con sus parámetros por defecto
+pub type CodecFactory = fn() -> Box<dyn Codec>;
+
+/// Registro de codecs indexado por id de texto
+///
+/// Conserva el orden de registro, que es el orden en que el benchmark
+/// enumera los métodos.
+#[derive(Clone)]
+pub struct CodecRegistry {
+    entries: Vec<(&'static str, CodecFactory)>,
+}
+
+impl CodecRegistry {
+    /// Registro vacío
+    pub fn new() -> Self {
+        CodecRegistry { entries: Vec::new() }
+    }
+
+    /// Registro con todos los métodos de `methods`
+    pub fn with_builtin() -> Self {
+        let mut registry = CodecRegistry::new();
+        registry.register("gzip", || Box::new(GzipCodec));
+        registry.register("int8_gzip", || Box::new(Int8Codec));
+        registry.register("delta_gzip", || Box::new(DeltaGzipCodec));
+        registry.register("zstd", || Box::new(ZstdCodec::default()));
+        registry.register("polar_delta", || Box::new(PolarDeltaCodec));
+        registry.register("delta_ans", || Box::new(DeltaAnsCodec));
+        registry.register("delta_rans", || Box::new(DeltaRansCodec));
+        registry.register("delta_lossless", || Box::new(DeltaLosslessCodec::default()));
+        registry.register("attractor", || Box::new(AttractorCodec::default()));
+        registry.register("dmd", || Box::new(DmdCodec::default()));
+        registry.register("mixture_pca", || Box::new(MixturePcaCodec::default()));
+        registry.register("autoencoder", || Box::new(AutoencoderCodec::default()));
+        registry.register("symbolic", || Box::new(SymbolicCodec::default()));
+        registry
+    }
+
+    /// Registra un codec; si el id ya existe, reemplaza su constructor
+    pub fn register(&mut self, id: &'static str, factory: CodecFactory) {
+        match self.entries.iter_mut().find(|(existing, _)| *existing == id) {
+            Some(entry) => entry.1 = factory,
+            None => self.entries.push((id, factory)),
+        }
+    }
+
+    /// Ids registrados, en orden de registro
+    pub fn ids(&self) -> Vec<&'static str> {
+        self.entries.iter().map(|(id, _)| *id).collect()
+    }
+
+    pub fn contains(&self, id: &str) -> bool {
+        self.entries.iter().any(|(existing, _)| *existing == id)
+    }
+
+    /// Crea el codec `id` con parámetros por defecto
+    pub fn create(&self, id: &str) -> Option<Box<dyn Codec>> {
+        self.entries.iter()
+            .find(|(existing, _)| *existing == id)
+            .map(|(_, factory)| factory())
+    }
+
+    /// Crea el codec `id` y aplica los parámetros indicados
+    pub fn create_with(
+        &self,
+        id: &str,
+        params: &[(&str, ParamValue)],
+    ) -> Result<Box<dyn Codec>, ParamError> {
+        let mut codec = self.create(id).ok_or_else(|| ParamError::UnknownCodec(id.to_string()))?;
+        for &(name, value) in params {
+            codec.set_param(name, value)?;
+        }
+        Ok(codec)
+    }
+
+    /// Crea un codec desde una especificación textual `id` o `id:clave=valor,clave=valor`
+    ///
+    /// Ejemplo: `"attractor:n_components=20"`, `"zstd:level=9"`.
+    pub fn create_from_spec(&self, spec: &str) -> Result<Box<dyn Codec>, ParamError> {
+        let (id, rest) = match spec.split_once(':') {
+            Some((id, rest)) => (id.trim(), rest),
+            None => (spec.trim(), ""),
+        };
+
+        let mut params = Vec::new();
+        for pair in rest.split(',').filter(|p| !p.trim().is_empty()) {
+            let (name, value) = pair.split_once('=')
+                .ok_or_else(|| ParamError::MalformedSpec(spec.to_string()))?;
+            let value = ParamValue::parse(value)
+                .ok_or_else(|| ParamError::MalformedSpec(spec.to_string()))?;
+            params.push((name.trim(), value));
+        }
+
+        self.create_with(id, &params)
+    }
+
+    /// Instancia todos los codecs registrados con parámetros por defecto
+    pub fn all(&self) -> Vec<Box<dyn Codec>> {
+        self.entries.iter().map(|(_, factory)| factory()).collect()
+    }
+}
+
+impl Default for CodecRegistry {
+    fn default() -> Self {
+        CodecRegistry::with_builtin()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_registry_lookup_and_params() {
+        let registry = CodecRegistry::with_builtin();
+        assert!(registry.contains("attractor"));
+        assert!(registry.create("no_existe").is_none());
+
+        let codec = registry.create_from_spec("attractor:n_components=4").unwrap();
+        assert_eq!(codec.name(), "Attractor(PCA-4)");
+        assert_eq!(
+            codec.params(),
+            vec![("n_components", ParamValue::Int(4)), ("randomized_svd", ParamValue::Bool(false))]
+        );
+        assert!(!codec.is_lossless());
+
+        let codec = registry.create_from_spec("attractor:n_components=4,power_iterations=1").unwrap();
+        assert_eq!(codec.name(), "Attractor(rPCA-4)");
+        assert!(codec.params().contains(&("oversampling", ParamValue::Int(10))));
+        assert!(codec.params().contains(&("power_iterations", ParamValue::Int(1))));
+
+        let codec = registry.create_from_spec("attractor:explained_variance=0.99").unwrap();
+        assert_eq!(codec.name(), "Attractor(PCA-EV0.99)");
+        assert_eq!(codec.params()[0], ("explained_variance", ParamValue::Float(0.99)));
+        assert!(registry.create_from_spec("attractor:explained_variance=1.5").is_err());
+
+        let codec = registry.create_from_spec("attractor:error_bound=0.01").unwrap();
+        assert_eq!(codec.name(), "Attractor(PCA-10, ε=0.01)");
+        assert!(codec.params().contains(&("error_bound", ParamValue::Float(0.01))));
+
+        let codec = registry.create_from_spec("attractor:target_distortion=0.001").unwrap();
+        assert!(codec.params().contains(&("target_distortion", ParamValue::Float(0.001))));
+        let codec = registry.create_from_spec("attractor:delta_step=0.001").unwrap();
+        assert!(codec.params().contains(&("delta_step", ParamValue::Float(0.001))));
+        assert!(registry.create_from_spec("attractor:delta_step=-1").is_err());
+
+        let codec = registry.create_from_spec("attractor:n_components=4,var_order=2").unwrap();
+        assert_eq!(codec.name(), "Attractor(PCA-4, VAR2)");
+        assert!(codec.params().contains(&("var_order", ParamValue::Int(2))));
+
+        let codec = registry.create_from_spec("attractor:n_components=3,sindy_degree=2").unwrap();
+        assert_eq!(codec.name(), "Attractor(PCA-3, SINDy2)");
+        assert!(codec.params().contains(&("sindy_threshold", ParamValue::Float(1e-3))));
+
+        let codec = registry.create_from_spec("attractor:n_components=3,esn_reservoir=50,esn_seed=7").unwrap();
+        assert_eq!(codec.name(), "Attractor(PCA-3, ESN50)");
+        assert!(codec.params().contains(&("esn_spectral_radius", ParamValue::Float(0.9))));
+        assert!(codec.params().contains(&("esn_seed", ParamValue::Int(7))));
+        assert!(registry.create_from_spec("attractor:esn_leak=1.5").is_err());
+
+        let codec = registry.create_from_spec("attractor:n_components=3,analogue_neighbors=8").unwrap();
+        assert_eq!(codec.name(), "Attractor(PCA-3, Analog3x8)");
+        assert!(codec.params().contains(&("analogue_delay", ParamValue::Int(1))));
+        assert!(registry.create_from_spec("attractor:analogue_delay=0").is_err());
+
+        let codec = registry.create_from_spec("attractor:n_components=3,sindy_degree=2,shadowing_tolerance=0.05").unwrap();
+        assert_eq!(codec.name(), "Attractor(PCA-3, SINDy2, shadow0.05)");
+        assert!(codec.params().contains(&("shadowing_tolerance", ParamValue::Float(0.05))));
+        assert!(registry.create_from_spec("attractor:shadowing_tolerance=-1").is_err());
+
+        let codec = registry.create_from_spec("attractor:n_components=3,latent_ans=true").unwrap();
+        assert_eq!(codec.name(), "Attractor(PCA-3, ANS)");
+        assert!(codec.params().contains(&("latent_ans", ParamValue::Bool(true))));
+        assert!(registry.create_from_spec("attractor:latent_ans=2").is_err());
+
+        let codec = registry.create_from_spec("mixture_pca:n_clusters=20,n_components=2").unwrap();
+        assert_eq!(codec.name(), "MixPCA(20×PCA-2)");
+        assert!(codec.params().contains(&("kmeans_iterations", ParamValue::Int(25))));
+        assert!(registry.create_from_spec("mixture_pca:n_clusters=0").is_err());
+
+        let codec = registry.create_from_spec("autoencoder:latent=4,error_bound=0").unwrap();
+        assert_eq!(codec.name(), "AE-4(H32)");
+        assert!(!codec.params().iter().any(|(name, _)| *name == "error_bound"));
+        assert!(registry.create_from_spec("autoencoder:learning_rate=0").is_err());
+
+        let codec = registry.create_from_spec("symbolic:n_components=3,cells=32,context_order=2").unwrap();
+        assert_eq!(codec.name(), "Symbolic(PCA-3, C32, ctx2)");
+        assert!(codec.params().contains(&("kmeans_iterations", ParamValue::Int(25))));
+        assert!(registry.create_from_spec("symbolic:context_order=9").is_err());
+
+        let codec = registry.create_from_spec("delta_rans").unwrap();
+        assert_eq!((codec.name(), codec.params()), ("Delta+rANS".to_string(), Vec::new()));
+
+        let codec = registry.create_from_spec("dmd:n_modes=6").unwrap();
+        assert_eq!(codec.name(), "DMD-6");
+        assert_eq!(codec.params(), vec![("n_modes", ParamValue::Int(6))]);
+
+        let err = registry.create_from_spec("zstd:nivel=3").err().unwrap();
+        assert_eq!(err, ParamError::UnknownParam { codec: "zstd", name: "nivel".to_string() });
+
+        let err = registry.create_from_spec("attractor:n_components=0").err().unwrap();
+        assert!(matches!(err, ParamError::InvalidValue { name: "n_components", .. }));
+    }
+
+    #[test]
+    fn test_registry_roundtrip_all() {
+        let vectors: Vec<Vec<f32>> = (0..20)
+            .map(|i| (0..8).map(|j| ((i * 8 + j) as f32 * 0.01).sin()).collect())
+            .collect();
+
+        for codec in CodecRegistry::with_builtin().all() {
+            let compressed = codec.compress(&vectors).unwrap();
+            let decompressed = codec.decompress(&compressed).unwrap();
+            assert_eq!(decompressed.len(), vectors.len(), "{}", codec.id());
+            assert!(decompressed.iter().all(|v| v.len() == 8), "{}", codec.id());
+
+            // Un blob truncado debe producir un error, nunca un pánico
+            let truncated = &compressed[..compressed.len() / 2];
+            assert!(codec.decompress(truncated).is_err(), "{} aceptó un blob truncado", codec.id());
+        }
+    }
+
+    #[test]
+    fn test_flat_view_matches_nested_api() {
+        let array = ndarray::Array2::from_shape_fn((20, 8), |(i, j)| ((i * 8 + j) as f32 * 0.01).sin());
+        let nested: Vec<Vec<f32>> = array.rows().into_iter().map(|row| row.to_vec()).collect();
+        let view = VectorsView::from_array(array.view()).unwrap();
+
+        for codec in CodecRegistry::with_builtin().all() {
+            let blob = codec.compress_view(view).unwrap();
+            assert_eq!(blob, codec.compress(&nested).unwrap(), "{}", codec.id());
+
+            // Descomprimir en un buffer reutilizado, mayor que lo necesario
+            let mut out = vec![f32::NAN; 200];
+            let header = codec.decompress_into(&blob, &mut out).unwrap();
+            assert_eq!((header.n, header.dim), (20, 8));
+            let expected: Vec<f32> = codec.decompress(&blob).unwrap().concat();
+            assert_eq!(&out[..160], expected.as_slice(), "{}", codec.id());
+
+            let as_array = crate::methods::decompress_to_array(&blob).unwrap();
+            assert_eq!(as_array.as_slice().unwrap(), expected.as_slice());
+
+            let mut small = vec![0.0f32; 100];
+            assert!(matches!(
+                codec.decompress_into(&blob, &mut small),
+                Err(CompressionError::BufferTooSmall { needed: 160, available: 100 })
+            ));
+        }
+    }
+}
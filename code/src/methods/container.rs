@@ -0,0 +1,482 @@
+//! Formato contenedor autodescriptivo común a todos los métodos
+//!
+//! Layout (little-endian):
+//!
+//! | campo        | bytes | contenido                                   |
+//! |--------------|-------|---------------------------------------------|
+//! | magic        | 4     | `b"CACZ"`                                   |
+//! | version      | 1     | `FORMAT_VERSION`                            |
+//! | codec        | 1     | `CodecId`                                   |
+//! | element      | 1     | `ElementType` de los vectores comprimidos   |
+//! | params_len   | 2     | longitud de los parámetros del codec        |
+//! | params       | var   | parámetros específicos del codec            |
+//! | n            | 4     | número de vectores                          |
+//! | dim          | 4     | dimensión de cada vector                    |
+//! | payload_len  | 4     | longitud del contenido                      |
+//! | crc32        | 4     | CRC32 de lo anterior y del contenido        |
+//! | payload      | var   | datos propios del codec                     |
+//!
+//! Así un blob no puede decodificarse con el método equivocado y una
+//! corrupción de la cabecera o del contenido se detecta antes de
+//! interpretarlo. Además n × dim debe ser plausible para el tamaño del
+//! contenido antes de reservar la salida.
+
+use flate2::Crc;
+use ndarray::Array2;
+use std::ops::Range;
+
+use super::bytes::ByteReader;
+use super::element::{Element, ElementType};
+use super::error::CompressionError;
+use super::vectors::{split_rows, to_array};
+use super::{
+    ans, ans_simple, attractor_compression, autoencoder_compression, delta_lossless, dmd_compression, mixture_compression, stream,
+    symbolic_compression,
+    decode_delta_payload, decode_gzip_payload, decode_int8_payload,
+    decode_polar_delta_payload, decode_zstd_payload,
+};
+
+/// Bytes mágicos al inicio de todo blob
+pub const MAGIC: [u8; 4] = *b"CACZ";
+
+/// Versión actual del formato
+///
+/// 2 añadió el tipo de elemento a la cabecera; 3, la disposición actual de
+/// los parámetros del codec de atractor (pasos por componente, modelo de
+/// trayectoria y secciones opcionales); 4 extendió el CRC a la cabecera; 5
+/// guarda los deltas de `delta_lossless` como diferencias de patrones de bits;
+/// 6, igual en los frames `DeltaLossless` del stream
+pub const FORMAT_VERSION: u8 = 6;
+
+/// Valores decodificados por byte de contenido que se aceptan como máximo:
+/// holgado incluso para zstd sobre datos constantes, pero impide que una
+/// cabecera manipulada reserve terabytes
+const MAX_VALUES_PER_BYTE: usize = 1 << 16;
+
+/// Identificador numérico de cada codec dentro del contenedor
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum CodecId {
+    Gzip = 1,
+    Int8Gzip = 2,
+    DeltaGzip = 3,
+    Zstd = 4,
+    PolarDelta = 5,
+    DeltaAns = 6,
+    DeltaLossless = 7,
+    Attractor = 8,
+    /// Frame de un stream append-only (ver `stream`)
+    Stream = 9,
+    Dmd = 10,
+    MixturePca = 11,
+    Autoencoder = 12,
+    Symbolic = 13,
+    DeltaRans = 14,
+}
+
+impl CodecId {
+    pub fn from_u8(value: u8) -> Option<CodecId> {
+        match value {
+            1 => Some(CodecId::Gzip),
+            2 => Some(CodecId::Int8Gzip),
+            3 => Some(CodecId::DeltaGzip),
+            4 => Some(CodecId::Zstd),
+            5 => Some(CodecId::PolarDelta),
+            6 => Some(CodecId::DeltaAns),
+            7 => Some(CodecId::DeltaLossless),
+            8 => Some(CodecId::Attractor),
+            9 => Some(CodecId::Stream),
+            10 => Some(CodecId::Dmd),
+            11 => Some(CodecId::MixturePca),
+            12 => Some(CodecId::Autoencoder),
+            13 => Some(CodecId::Symbolic),
+            14 => Some(CodecId::DeltaRans),
+            _ => None,
+        }
+    }
+
+    /// Id de texto equivalente en `CodecRegistry`
+    pub fn registry_id(&self) -> &'static str {
+        match self {
+            CodecId::Gzip => "gzip",
+            CodecId::Int8Gzip => "int8_gzip",
+            CodecId::DeltaGzip => "delta_gzip",
+            CodecId::Zstd => "zstd",
+            CodecId::PolarDelta => "polar_delta",
+            CodecId::DeltaAns => "delta_ans",
+            CodecId::DeltaLossless => "delta_lossless",
+            CodecId::Attractor => "attractor",
+            CodecId::Stream => "stream",
+            CodecId::Dmd => "dmd",
+            CodecId::MixturePca => "mixture_pca",
+            CodecId::Autoencoder => "autoencoder",
+            CodecId::Symbolic => "symbolic",
+            CodecId::DeltaRans => "delta_rans",
+        }
+    }
+}
+
+/// Cabecera decodificada de un blob
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContainerHeader {
+    pub version: u8,
+    pub codec: CodecId,
+    /// Tipo de elemento con que se comprimió
+    pub element: ElementType,
+    /// Parámetros específicos del codec (formato propio de cada método)
+    pub params: Vec<u8>,
+    pub n: usize,
+    pub dim: usize,
+}
+
+fn crc32(header: &[u8], payload: &[u8]) -> u32 {
+    let mut crc = Crc::new();
+    crc.update(header);
+    crc.update(payload);
+    crc.sum()
+}
+
+/// Rechaza `count` valores decodificados a partir de `available` bytes si
+/// supera `MAX_VALUES_PER_BYTE`; se aplica antes de reservar memoria según
+/// conteos leídos del blob
+pub(crate) fn check_decoded_len(field: &'static str, count: usize, available: usize) -> Result<(), CompressionError> {
+    if count > available.saturating_mul(MAX_VALUES_PER_BYTE) {
+        return Err(CompressionError::BadLength { field, value: count });
+    }
+    Ok(())
+}
+
+/// Envuelve el contenido de un codec en el contenedor
+pub(crate) fn wrap(
+    codec: CodecId,
+    element: ElementType,
+    params: &[u8],
+    n: usize,
+    dim: usize,
+    payload: &[u8],
+) -> Result<Vec<u8>, CompressionError> {
+    let params_len = u16::try_from(params.len())
+        .map_err(|_| CompressionError::BadLength { field: "params_len", value: params.len() })?;
+    let n_u32 = u32::try_from(n).map_err(|_| CompressionError::BadLength { field: "n", value: n })?;
+    let dim_u32 = u32::try_from(dim).map_err(|_| CompressionError::BadLength { field: "dim", value: dim })?;
+    let payload_len = u32::try_from(payload.len())
+        .map_err(|_| CompressionError::BadLength { field: "payload_len", value: payload.len() })?;
+
+    let mut blob = Vec::with_capacity(25 + params.len() + payload.len());
+    blob.extend(&MAGIC);
+    blob.push(FORMAT_VERSION);
+    blob.push(codec as u8);
+    blob.push(element as u8);
+    blob.extend(&params_len.to_le_bytes());
+    blob.extend(params);
+    blob.extend(&n_u32.to_le_bytes());
+    blob.extend(&dim_u32.to_le_bytes());
+    blob.extend(&payload_len.to_le_bytes());
+    let crc = crc32(&blob, payload);
+    blob.extend(&crc.to_le_bytes());
+    blob.extend(payload);
+
+    Ok(blob)
+}
+
+/// Campos de la cabecera ya leídos, antes de tocar el contenido
+struct RawHeader {
+    header: ContainerHeader,
+    header_len: usize,
+    payload_len: usize,
+    crc: u32,
+}
+
+fn parse_header(blob: &[u8]) -> Result<RawHeader, CompressionError> {
+    let mut reader = ByteReader::header(blob);
+
+    let magic = reader.take(4)?;
+    if magic != MAGIC {
+        return Err(CompressionError::BadMagic);
+    }
+
+    let version = reader.read_u8()?;
+    if version != FORMAT_VERSION {
+        return Err(CompressionError::UnsupportedVersion(version));
+    }
+
+    let codec_byte = reader.read_u8()?;
+    let codec = CodecId::from_u8(codec_byte).ok_or(CompressionError::UnknownCodecId(codec_byte))?;
+
+    let element_byte = reader.read_u8()?;
+    let element = ElementType::from_u8(element_byte)
+        .ok_or(CompressionError::UnknownElementType(element_byte))?;
+
+    let params_len = reader.read_u16()? as usize;
+    let params = reader.take(params_len)?.to_vec();
+    let n = reader.read_len()?;
+    let dim = reader.read_len()?;
+    let payload_len = reader.read_len()?;
+    let crc = reader.read_u32()?;
+
+    // Con dim = 0 algunos codecs aún reservan por vector
+    let values = n.checked_mul(dim.max(1)).ok_or(CompressionError::BadLength { field: "n", value: n })?;
+    check_decoded_len("n", values, payload_len)?;
+
+    Ok(RawHeader {
+        header: ContainerHeader { version, codec, element, params, n, dim },
+        header_len: blob.len() - reader.remaining(),
+        payload_len,
+        crc,
+    })
+}
+
+/// Longitud total (cabecera + contenido) del blob que empieza en `blob`
+///
+/// Solo necesita la cabecera completa: permite delimitar frames concatenados
+/// antes de que llegue todo su contenido.
+pub(crate) fn frame_len(blob: &[u8]) -> Result<usize, CompressionError> {
+    let raw = parse_header(blob)?;
+    raw.header_len.checked_add(raw.payload_len)
+        .ok_or(CompressionError::BadLength { field: "payload_len", value: raw.payload_len })
+}
+
+/// Lee y valida la cabecera; devuelve el contenido verificado por CRC
+///
+/// Los bytes posteriores al contenido se ignoran (ver `frame_len`).
+pub fn read_header(blob: &[u8]) -> Result<(ContainerHeader, &[u8]), CompressionError> {
+    let RawHeader { header, header_len, payload_len, crc: expected_crc } = parse_header(blob)?;
+
+    let mut reader = ByteReader::payload(&blob[header_len..]);
+    if payload_len > reader.remaining() {
+        return Err(CompressionError::TruncatedPayload {
+            needed: header_len + payload_len,
+            available: blob.len(),
+        });
+    }
+    let payload = reader.take(payload_len)?;
+
+    let computed = crc32(&blob[..header_len - 4], payload);
+    if computed != expected_crc {
+        return Err(CompressionError::ChecksumMismatch { expected: expected_crc, computed });
+    }
+
+    Ok((header, payload))
+}
+
+/// Abre un blob comprobando que fue producido por `expected`
+pub(crate) fn open(blob: &[u8], expected: CodecId) -> Result<(ContainerHeader, &[u8]), CompressionError> {
+    let (header, payload) = read_header(blob)?;
+    if header.codec != expected {
+        return Err(CompressionError::WrongCodec { expected, found: header.codec });
+    }
+    Ok((header, payload))
+}
+
+/// Número de valores (n × dim) que ocupa la salida decodificada
+fn output_len(header: &ContainerHeader) -> Result<usize, CompressionError> {
+    header.n.checked_mul(header.dim)
+        .ok_or(CompressionError::BadLength { field: "n", value: header.n })
+}
+
+/// Decodifica el contenido en `out`, que tiene exactamente n × dim valores
+fn decode_payload_into<T: Element>(
+    header: &ContainerHeader,
+    payload: &[u8],
+    out: &mut [T],
+) -> Result<(), CompressionError> {
+    match header.codec {
+        CodecId::Gzip => decode_gzip_payload(header, payload, out),
+        CodecId::Int8Gzip => decode_int8_payload(header, payload, out),
+        CodecId::DeltaGzip => decode_delta_payload(header, payload, out),
+        CodecId::Zstd => decode_zstd_payload(header, payload, out),
+        CodecId::PolarDelta => decode_polar_delta_payload(header, payload, out),
+        CodecId::DeltaAns => ans_simple::decode_payload(header, payload, out),
+        CodecId::DeltaRans => ans::decode_payload(header, payload, out),
+        CodecId::DeltaLossless => delta_lossless::decode_payload(header, payload, out),
+        CodecId::Attractor => attractor_compression::decode_payload(header, payload, out),
+        CodecId::Dmd => dmd_compression::decode_payload(header, payload, out),
+        CodecId::MixturePca => mixture_compression::decode_payload(header, payload, out),
+        CodecId::Autoencoder => autoencoder_compression::decode_payload(header, payload, out),
+        CodecId::Symbolic => symbolic_compression::decode_payload(header, payload, out),
+        // La cabecera de un frame solo describe ese frame: se decodifica el stream completo
+        CodecId::Stream => Err(CompressionError::WrongCodec { expected: CodecId::Stream, found: header.codec }),
+    }
+}
+
+/// Decodifica un blob en un buffer plano nuevo; devuelve (valores, n, dim)
+fn decode_flat(blob: &[u8], expected: Option<CodecId>) -> Result<(Vec<f32>, usize, usize), CompressionError> {
+    let (header, payload) = match expected {
+        Some(expected) => open(blob, expected)?,
+        None => read_header(blob)?,
+    };
+
+    if header.codec == CodecId::Stream {
+        let vectors = stream::decode_stream(blob)?;
+        let n = vectors.len();
+        return Ok((vectors.concat(), n, header.dim));
+    }
+
+    let mut values = vec![0.0f32; output_len(&header)?];
+    decode_payload_into(&header, payload, &mut values)?;
+    Ok((values, header.n, header.dim))
+}
+
+/// Decodifica un blob de `expected` en vectores anidados
+pub(crate) fn decompress_as(blob: &[u8], expected: CodecId) -> Result<Vec<Vec<f32>>, CompressionError> {
+    let (values, n, dim) = decode_flat(blob, Some(expected))?;
+    Ok(split_rows(&values, n, dim))
+}
+
+/// Decodifica un blob de `expected` en el buffer del llamador (ver `decompress_into`)
+pub(crate) fn decompress_as_into<T: Element>(
+    blob: &[u8],
+    expected: CodecId,
+    out: &mut [T],
+) -> Result<ContainerHeader, CompressionError> {
+    let (header, payload) = open(blob, expected)?;
+    decode_into(blob, header, payload, out)
+}
+
+/// Descomprime cualquier blob despachando según el codec de su cabecera
+///
+/// Un stream (frames concatenados de `StreamEncoder`) se decodifica completo.
+pub fn decompress_any(blob: &[u8]) -> Result<Vec<Vec<f32>>, CompressionError> {
+    let (values, n, dim) = decode_flat(blob, None)?;
+    Ok(split_rows(&values, n, dim))
+}
+
+/// Descomprime cualquier blob como `Array2` (n × dim)
+pub fn decompress_to_array(blob: &[u8]) -> Result<Array2<f32>, CompressionError> {
+    let (values, n, dim) = decode_flat(blob, None)?;
+    to_array(values, n, dim)
+}
+
+/// Descomprime cualquier blob en un buffer del llamador, fila mayor
+///
+/// Escribe los primeros n × dim valores de `out` y devuelve la cabecera (con
+/// el total de vectores en el caso de un stream). El tipo de `out` puede
+/// diferir del comprimido (`header.element`); si coincide, gzip y zstd
+/// descomprimen directamente en `out`.
+pub fn decompress_into<T: Element>(blob: &[u8], out: &mut [T]) -> Result<ContainerHeader, CompressionError> {
+    let (header, payload) = read_header(blob)?;
+    decode_into(blob, header, payload, out)
+}
+
+fn decode_into<T: Element>(
+    blob: &[u8],
+    mut header: ContainerHeader,
+    payload: &[u8],
+    out: &mut [T],
+) -> Result<ContainerHeader, CompressionError> {
+    if header.codec == CodecId::Stream {
+        let vectors = stream::decode_stream(blob)?;
+        let needed = vectors.len() * header.dim;
+        if out.len() < needed {
+            return Err(CompressionError::BufferTooSmall { needed, available: out.len() });
+        }
+        for (dest, &value) in out.iter_mut().zip(vectors.iter().flatten()) {
+            *dest = T::from_f64(value as f64);
+        }
+        header.n = vectors.len();
+        return Ok(header);
+    }
+
+    let needed = output_len(&header)?;
+    if out.len() < needed {
+        return Err(CompressionError::BufferTooSmall { needed, available: out.len() });
+    }
+    decode_payload_into(&header, payload, &mut out[..needed])?;
+    Ok(header)
+}
+
+/// Descomprime solo los vectores `range` de un blob
+///
+/// Los codecs con keyframes (`delta_lossless`) reconstruyen únicamente los
+/// bloques que cubren el rango, con coste O(intervalo de keyframes); el resto
+/// decodifica el blob completo y recorta.
+pub fn decompress_range(blob: &[u8], range: Range<usize>) -> Result<Vec<Vec<f32>>, CompressionError> {
+    let (header, payload) = read_header(blob)?;
+
+    match header.codec {
+        CodecId::DeltaLossless => delta_lossless::decode_range(&header, payload, range),
+        _ => {
+            let mut vectors = decompress_any(blob)?;
+            if range.start > range.end || range.end > vectors.len() {
+                return Err(CompressionError::RangeOutOfBounds {
+                    start: range.start,
+                    end: range.end,
+                    len: vectors.len(),
+                });
+            }
+            vectors.truncate(range.end);
+            Ok(vectors.split_off(range.start))
+        }
+    }
+}
+
+/// Descomprime el vector `index` de un blob (ver `decompress_range`)
+pub fn decompress_one(blob: &[u8], index: usize) -> Result<Vec<f32>, CompressionError> {
+    let end = index.saturating_add(1);
+    let mut vectors = decompress_range(blob, index..end)?;
+    vectors.pop().ok_or(CompressionError::RangeOutOfBounds { start: index, end, len: 0 })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::methods::CodecRegistry;
+
+    fn sample_vectors() -> Vec<Vec<f32>> {
+        (0..30)
+            .map(|i| (0..6).map(|j| ((i * 6 + j) as f32 * 0.05).cos()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn test_decompress_any_dispatches_on_header() {
+        let vectors = sample_vectors();
+
+        for codec in CodecRegistry::with_builtin().all() {
+            let blob = codec.compress(&vectors).unwrap();
+            let (header, _) = read_header(&blob).unwrap();
+            assert_eq!(header.codec.registry_id(), codec.id());
+            assert_eq!((header.n, header.dim), (30, 6));
+
+            let direct = codec.decompress(&blob).unwrap();
+            let dispatched = decompress_any(&blob).unwrap();
+            assert_eq!(direct, dispatched, "{}", codec.id());
+        }
+    }
+
+    #[test]
+    fn test_rejects_wrong_codec_and_corruption() {
+        let vectors = sample_vectors();
+        let blob = crate::methods::delta_lossless_compress(&vectors).unwrap();
+
+        // Decodificar con el método equivocado falla en lugar de producir basura
+        assert!(matches!(
+            crate::methods::attractor_decompress(&blob),
+            Err(CompressionError::WrongCodec { expected: CodecId::Attractor, found: CodecId::DeltaLossless })
+        ));
+
+        // Un bit alterado en el contenido se detecta por CRC
+        let mut corrupted = blob.clone();
+        let last = corrupted.len() - 1;
+        corrupted[last] ^= 0x01;
+        assert!(matches!(decompress_any(&corrupted), Err(CompressionError::ChecksumMismatch { .. })));
+
+        // También en la cabecera: n con un bit alterado
+        let n_offset = 9 + read_header(&blob).unwrap().0.params.len();
+        let mut corrupted = blob.clone();
+        corrupted[n_offset + 2] ^= 0x10;
+        assert!(matches!(decompress_any(&corrupted), Err(CompressionError::ChecksumMismatch { .. })));
+
+        // Una cabecera válida con n × dim imposible para el contenido no reserva la salida
+        let huge = wrap(CodecId::Gzip, ElementType::F32, &[], 1 << 20, 1 << 20, &[0u8; 16]).unwrap();
+        assert!(matches!(decompress_any(&huge), Err(CompressionError::BadLength { field: "n", .. })));
+
+        let mut old_version = blob.clone();
+        old_version[4] = 2;
+        assert!(matches!(decompress_any(&old_version), Err(CompressionError::UnsupportedVersion(2))));
+
+        let mut bad_magic = blob;
+        bad_magic[0] = b'X';
+        assert!(matches!(decompress_any(&bad_magic), Err(CompressionError::BadMagic)));
+    }
+}
@@ -1,50 +1,160 @@
 //! Delta Encoding LOSSLESS con RLE + GZIP
 //!
 //! Estrategia:
-//! 1. Calcular deltas (float32)
+//! 1. Calcular deltas exactos (diferencia de los patrones de bits)
 //! 2. Aplicar Run-Length Encoding para deltas repetidos
 //! 3. Comprimir con GZIP
 //! 4. Sin pérdida de información
+//!
+//! Los deltas se encadenan dentro de bloques que empiezan con un keyframe
+//! (vector completo); un índice de offsets permite acceso aleatorio.
 
 use flate2::Compression;
-use flate2::write::GzEncoder;
-use flate2::read::GzDecoder;
-use std::io::{Write, Read};
+use std::ops::Range;
+
+use super::bytes::{check_count, gzip_decode, gzip_encode, ByteReader};
+use super::container::{self, CodecId, ContainerHeader};
+use super::element::{element_le_bytes, Element, ElementType};
+use super::error::CompressionError;
+use super::vectors::{compress_nested, split_rows, VectorsView};
+
+/// Intervalo de keyframes por defecto (vectores por bloque)
+pub const DEFAULT_KEYFRAME_INTERVAL: usize = 256;
 
 /// Delta Encoding lossless con RLE + GZIP
-pub fn delta_lossless_compress(vectors: &[Vec<f32>]) -> Vec<u8> {
+pub fn delta_lossless_compress(vectors: &[Vec<f32>]) -> Result<Vec<u8>, CompressionError> {
+    delta_lossless_compress_with_keyframes(vectors, DEFAULT_KEYFRAME_INTERVAL)
+}
+
+/// Delta Encoding lossless con un keyframe (vector completo) cada
+/// `keyframe_interval` vectores
+///
+/// Cada bloque de `keyframe_interval` vectores se codifica por separado y el
+/// contenido empieza con un índice de offsets, así que `decompress_range`
+/// solo reconstruye los bloques que tocan el rango pedido. Con
+/// `keyframe_interval = 0` todo el blob es un único bloque.
+///
+/// Layout: params = intervalo (u32); contenido = offsets de cada bloque
+/// (u32, relativos al fin del índice) + bloques, cada uno con su primer
+/// vector (en el tipo de elemento original) + GZIP(RLE de deltas).
+pub fn delta_lossless_compress_with_keyframes(
+    vectors: &[Vec<f32>],
+    keyframe_interval: usize,
+) -> Result<Vec<u8>, CompressionError> {
+    compress_nested(vectors, |view| delta_lossless_compress_view(view, keyframe_interval))
+}
+
+/// Igual que `delta_lossless_compress_with_keyframes`, sobre una vista plana
+///
+/// Cada delta es la diferencia entera de los patrones de bits de dos valores
+/// consecutivos, en el ancho del tipo original: la reconstrucción es exacta
+/// bit a bit para cualquier par de valores.
+pub fn delta_lossless_compress_view<T: Element>(
+    vectors: VectorsView<'_, T>,
+    keyframe_interval: usize,
+) -> Result<Vec<u8>, CompressionError> {
+    let interval = u32::try_from(keyframe_interval)
+        .map_err(|_| CompressionError::BadLength { field: "keyframe_interval", value: keyframe_interval })?;
+    let params = interval.to_le_bytes();
+
     if vectors.is_empty() {
-        return vec![];
+        return container::wrap(CodecId::DeltaLossless, T::TYPE, &params, 0, 0, &[]);
     }
+    let dim = vectors.dim();
+    let n_vectors = vectors.n();
+    let block_len = if keyframe_interval == 0 { n_vectors } else { keyframe_interval };
+
+    let mut index = Vec::new();
+    let mut blocks = Vec::new();
+    for block in vectors.as_slice().chunks(block_len * dim) {
+        let offset = u32::try_from(blocks.len())
+            .map_err(|_| CompressionError::BadLength { field: "block_offset", value: blocks.len() })?;
+        index.extend(&offset.to_le_bytes());
+        blocks.extend(encode_block(block, dim)?);
+    }
+
+    let mut payload = index;
+    payload.extend(&blocks);
 
-    let dim = vectors[0].len();
-    let n_vectors = vectors.len();
+    container::wrap(CodecId::DeltaLossless, T::TYPE, &params, n_vectors, dim, &payload)
+}
 
-    // 1. Calcular deltas (float32, sin cuantización)
-    let mut deltas_f32 = Vec::new();
+/// Codifica un bloque (valores planos): primer vector (keyframe) + deltas encadenados desde él
+fn encode_block<T: Element>(values: &[T], dim: usize) -> Result<Vec<u8>, CompressionError> {
+    let element = T::TYPE;
 
-    for i in 1..n_vectors {
-        for j in 0..dim {
-            let delta = vectors[i][j] - vectors[i - 1][j];
-            deltas_f32.push(delta);
+    // 1. Calcular deltas exactos respecto al vector anterior
+    let mut deltas = Vec::with_capacity(values.len() - dim);
+    for (prev, row) in values.chunks_exact(dim).zip(values[dim..].chunks_exact(dim)) {
+        for (&p, &x) in prev.iter().zip(row) {
+            deltas.push(element.bits_delta(p.to_f64(), x.to_f64()));
         }
     }
 
     // 2. Aplicar Run-Length Encoding simple
-    // Si hay deltas repetidos consecutivos, codificar como (count, value)
+    let rle_encoded = rle_encode(&deltas, element);
+
+    // 3. Comprimir con GZIP
+    let compressed_deltas = gzip_encode(&rle_encoded, Compression::best())?;
+
+    // 4. Serializar bloque: primer vector (tipo original) + deltas comprimidos
+    let mut block = Vec::with_capacity(dim * element.size() + compressed_deltas.len());
+    block.extend(element_le_bytes(&values[..dim]).iter());
+    block.extend(&compressed_deltas);
+
+    Ok(block)
+}
+
+/// Decodifica un bloque en `out` (exactamente n_vectors × dim valores)
+fn decode_block<T: Element>(
+    block: &[u8],
+    element: ElementType,
+    dim: usize,
+    out: &mut [T],
+) -> Result<(), CompressionError> {
+    let mut reader = ByteReader::payload(block);
+
+    // Leer primer vector
+    let mut prev = element.read_vec(&mut reader, dim)?;
+
+    // Deltas comprimidos: resto del bloque
+    let rle_encoded = gzip_decode(reader.rest())?;
+
+    // Decodificar RLE, sin expandir más deltas de los que declara la cabecera
+    let deltas = rle_decode(&rle_encoded, out.len() - dim, element)?;
+
+    // Reconstruir vectores desde deltas
+    for (o, &p) in out[..dim].iter_mut().zip(&prev) {
+        *o = T::from_f64(p);
+    }
+    for (row, step) in out[dim..].chunks_exact_mut(dim).zip(deltas.chunks_exact(dim)) {
+        for ((o, p), &delta) in row.iter_mut().zip(prev.iter_mut()).zip(step) {
+            *p = element.apply_bits_delta(*p, delta);
+            *o = T::from_f64(*p);
+        }
+    }
+
+    Ok(())
+}
+
+/// Run-Length Encoding de deltas
+///
+/// Si hay deltas repetidos consecutivos, se codifican como (count u32, value)
+/// con el valor en el ancho de `value_type`
+pub(crate) fn rle_encode(deltas: &[u64], value_type: ElementType) -> Vec<u8> {
     let mut rle_encoded = Vec::new();
 
-    if !deltas_f32.is_empty() {
-        let mut current_value = deltas_f32[0];
+    if !deltas.is_empty() {
+        let mut current_value = deltas[0];
         let mut count = 1u32;
 
-        for &delta in &deltas_f32[1..] {
-            if (delta - current_value).abs() < 1e-10 && count < u32::MAX {
+        for &delta in &deltas[1..] {
+            if delta == current_value && count < u32::MAX {
                 count += 1;
             } else {
                 // Escribir (count, value)
                 rle_encoded.extend(&count.to_le_bytes());
-                rle_encoded.extend(&current_value.to_le_bytes());
+                value_type.write_bits(current_value, &mut rle_encoded);
                 current_value = delta;
                 count = 1;
             }
@@ -52,135 +162,136 @@ pub fn delta_lossless_compress(vectors: &[Vec<f32>]) -> Vec<u8> {
 
         // Último valor
         rle_encoded.extend(&count.to_le_bytes());
-        rle_encoded.extend(&current_value.to_le_bytes());
+        value_type.write_bits(current_value, &mut rle_encoded);
     }
 
-    // 3. Comprimir con GZIP
-    let mut encoder = GzEncoder::new(Vec::new(), Compression::best());
-    encoder.write_all(&rle_encoded).unwrap();
-    let compressed_deltas = encoder.finish().unwrap();
-
-    // 4. Serializar resultado
-    let mut result = Vec::new();
+    rle_encoded
+}
 
-    // Metadata
-    result.extend(&(n_vectors as u32).to_le_bytes());
-    result.extend(&(dim as u32).to_le_bytes());
+/// Decodifica RLE verificando que se expandan exactamente `expected` deltas
+pub(crate) fn rle_decode(
+    rle_encoded: &[u8],
+    expected: usize,
+    value_type: ElementType,
+) -> Result<Vec<u64>, CompressionError> {
+    let mut deltas = Vec::new();
+    let mut rle = ByteReader::payload(rle_encoded);
+    while rle.remaining() >= 4 + value_type.size() {
+        let count = rle.read_len()?;
+        let value = value_type.read_bits(&mut rle)?;
+
+        if count > expected - deltas.len() {
+            return Err(CompressionError::BadLength { field: "rle_count", value: count });
+        }
 
-    // Primer vector (float32)
-    for &val in &vectors[0] {
-        result.extend(&val.to_le_bytes());
+        // Expandir RLE
+        deltas.extend(std::iter::repeat_n(value, count));
     }
 
-    // Deltas comprimidos
-    result.extend(&(compressed_deltas.len() as u32).to_le_bytes());
-    result.extend(&compressed_deltas);
+    if deltas.len() != expected {
+        return Err(CompressionError::DimensionMismatch { expected, found: deltas.len() });
+    }
 
-    result
+    Ok(deltas)
 }
 
 /// Descompresión de Delta lossless
-pub fn delta_lossless_decompress(compressed: &[u8]) -> Vec<Vec<f32>> {
-    if compressed.len() < 8 {
-        return vec![];
-    }
-
-    let mut offset = 0;
-
-    // Leer metadata
-    let n_vectors = u32::from_le_bytes([
-        compressed[offset],
-        compressed[offset + 1],
-        compressed[offset + 2],
-        compressed[offset + 3],
-    ]) as usize;
-    offset += 4;
-
-    let dim = u32::from_le_bytes([
-        compressed[offset],
-        compressed[offset + 1],
-        compressed[offset + 2],
-        compressed[offset + 3],
-    ]) as usize;
-    offset += 4;
+pub fn delta_lossless_decompress(compressed: &[u8]) -> Result<Vec<Vec<f32>>, CompressionError> {
+    container::decompress_as(compressed, CodecId::DeltaLossless)
+}
 
-    // Leer primer vector
-    let mut first_vec = Vec::with_capacity(dim);
-    for _ in 0..dim {
-        let val = f32::from_le_bytes([
-            compressed[offset],
-            compressed[offset + 1],
-            compressed[offset + 2],
-            compressed[offset + 3],
-        ]);
-        first_vec.push(val);
-        offset += 4;
-    }
-
-    // Leer deltas comprimidos
-    let compressed_size = u32::from_le_bytes([
-        compressed[offset],
-        compressed[offset + 1],
-        compressed[offset + 2],
-        compressed[offset + 3],
-    ]) as usize;
-    offset += 4;
-
-    let compressed_deltas = &compressed[offset..offset + compressed_size];
-
-    // Descomprimir GZIP
-    let mut decoder = GzDecoder::new(compressed_deltas);
-    let mut rle_encoded = Vec::new();
-    decoder.read_to_end(&mut rle_encoded).unwrap();
-
-    // Decodificar RLE
-    let mut deltas_f32 = Vec::new();
-    let mut i = 0;
-    while i + 7 < rle_encoded.len() {
-        let count = u32::from_le_bytes([
-            rle_encoded[i],
-            rle_encoded[i + 1],
-            rle_encoded[i + 2],
-            rle_encoded[i + 3],
-        ]) as usize;
-        i += 4;
-
-        let value = f32::from_le_bytes([
-            rle_encoded[i],
-            rle_encoded[i + 1],
-            rle_encoded[i + 2],
-            rle_encoded[i + 3],
-        ]);
-        i += 4;
+pub(crate) fn decode_payload<T: Element>(
+    header: &ContainerHeader,
+    payload: &[u8],
+    out: &mut [T],
+) -> Result<(), CompressionError> {
+    decode_range_into(header, payload, 0..header.n, out)
+}
 
-        // Expandir RLE
-        for _ in 0..count {
-            deltas_f32.push(value);
-        }
+/// Decodifica solo los vectores `range`, reconstruyendo únicamente los
+/// bloques que lo cubren (coste O(intervalo de keyframes + longitud del rango))
+pub(crate) fn decode_range(
+    header: &ContainerHeader,
+    payload: &[u8],
+    range: Range<usize>,
+) -> Result<Vec<Vec<f32>>, CompressionError> {
+    check_range(&range, header.n)?;
+    let n = range.len();
+    let mut values = vec![0.0f32; n.saturating_mul(header.dim)];
+    decode_range_into(header, payload, range, &mut values)?;
+    Ok(split_rows(&values, n, header.dim))
+}
+
+fn check_range(range: &Range<usize>, len: usize) -> Result<(), CompressionError> {
+    if range.start > range.end || range.end > len {
+        return Err(CompressionError::RangeOutOfBounds { start: range.start, end: range.end, len });
     }
+    Ok(())
+}
 
-    // Reconstruir vectores desde deltas
-    let mut vectors = Vec::with_capacity(n_vectors);
-    vectors.push(first_vec.clone());
-
-    let mut prev = first_vec;
-    let mut delta_idx = 0;
-
-    for _ in 1..n_vectors {
-        let mut current = Vec::with_capacity(dim);
-        for _ in 0..dim {
-            if delta_idx < deltas_f32.len() {
-                current.push(prev[current.len()] + deltas_f32[delta_idx]);
-                delta_idx += 1;
-            } else {
-                break;
-            }
+/// Como `decode_range`, escribiendo en `out` (exactamente len(range) × dim valores)
+fn decode_range_into<T: Element>(
+    header: &ContainerHeader,
+    payload: &[u8],
+    range: Range<usize>,
+    out: &mut [T],
+) -> Result<(), CompressionError> {
+    let n_vectors = header.n;
+    let dim = header.dim;
+    check_range(&range, n_vectors)?;
+    if n_vectors == 0 {
+        return Ok(());
+    }
+    if dim == 0 {
+        return Err(CompressionError::UnsupportedDimension { dim, min: 1 });
+    }
+
+    let interval = ByteReader::header(&header.params).read_len()?;
+    let block_len = if interval == 0 { n_vectors } else { interval };
+    let n_blocks = n_vectors.div_ceil(block_len);
+
+    // Índice de offsets de bloque
+    let mut reader = ByteReader::payload(payload);
+    check_count("n_blocks", n_blocks, 4, reader.remaining())?;
+    let mut offsets = Vec::with_capacity(n_blocks + 1);
+    for _ in 0..n_blocks {
+        offsets.push(reader.read_len()?);
+    }
+    let blocks = reader.rest();
+    offsets.push(blocks.len());
+    if offsets.windows(2).any(|w| w[0] > w[1]) {
+        return Err(CompressionError::BadLength { field: "block_offset", value: blocks.len() });
+    }
+
+    if range.is_empty() {
+        return Ok(());
+    }
+
+    let first_block = range.start / block_len;
+    let last_block = (range.end - 1) / block_len;
+    let mut block_values = Vec::new();
+    let element = header.element;
+    for b in first_block..=last_block {
+        let block_start = b * block_len;
+        let block_vectors = block_len.min(n_vectors - block_start);
+        let block = &blocks[offsets[b]..offsets[b + 1]];
+
+        // Intersección del bloque con el rango pedido
+        let from = range.start.max(block_start);
+        let to = range.end.min(block_start + block_vectors);
+        let dest = &mut out[(from - range.start) * dim..(to - range.start) * dim];
+
+        if from == block_start && to == block_start + block_vectors {
+            // Bloque completo: decodificar directamente en la salida
+            decode_block(block, element, dim, dest)?;
+        } else {
+            block_values.resize(block_vectors * dim, T::from_f64(0.0));
+            decode_block(block, element, dim, &mut block_values)?;
+            dest.copy_from_slice(&block_values[(from - block_start) * dim..(to - block_start) * dim]);
         }
-        vectors.push(current.clone());
-        prev = current;
     }
 
-    vectors
+    Ok(())
 }
 
 #[cfg(test)]
@@ -200,8 +311,8 @@ mod tests {
             vectors.push(current.clone());
         }
 
-        let compressed = delta_lossless_compress(&vectors);
-        let decompressed = delta_lossless_decompress(&compressed);
+        let compressed = delta_lossless_compress(&vectors).unwrap();
+        let decompressed = delta_lossless_decompress(&compressed).unwrap();
 
         assert_eq!(vectors.len(), decompressed.len());
 
@@ -213,4 +324,34 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_random_access_matches_full_decode() {
+        let mut vectors: Vec<Vec<f32>> = (0..300)
+            .map(|i| (0..6).map(|j| ((i * 6 + j) as f32 * 0.013).sin()).collect())
+            .collect();
+        // Magnitudes muy dispares entre vectores consecutivos (delta aritmético inexacto)
+        vectors[150] = vec![1e30, -1e-30, f32::MIN_POSITIVE, -0.0, f32::MAX, 1e-45];
+        vectors[151] = vec![1e-30, 1e30, -f32::MAX, 3.0e-39, 1.0, -7.5];
+
+        for interval in [0, 1, 7, 64, DEFAULT_KEYFRAME_INTERVAL, 1000] {
+            let blob = delta_lossless_compress_with_keyframes(&vectors, interval).unwrap();
+            let full = delta_lossless_decompress(&blob).unwrap();
+            // Exacto bit a bit (incluido el signo de -0.0)
+            let bits = |v: &[Vec<f32>]| v.iter().flatten().map(|x| x.to_bits()).collect::<Vec<_>>();
+            assert_eq!(bits(&full), bits(&vectors), "intervalo {}", interval);
+
+            for range in [0..0, 0..1, 5..9, 60..130, 299..300, 0..300] {
+                let partial = crate::methods::decompress_range(&blob, range.clone()).unwrap();
+                assert_eq!(partial, full[range.clone()].to_vec(), "intervalo {} rango {:?}", interval, range);
+            }
+            assert_eq!(crate::methods::decompress_one(&blob, 199).unwrap(), full[199]);
+        }
+
+        let blob = delta_lossless_compress(&vectors).unwrap();
+        assert!(matches!(
+            crate::methods::decompress_one(&blob, 300),
+            Err(CompressionError::RangeOutOfBounds { start: 300, end: 301, len: 300 })
+        ));
+    }
 }
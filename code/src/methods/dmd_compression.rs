@@ -0,0 +1,420 @@
+//! Compresión por Descomposición en Modos Dinámicos (DMD / Koopman)
+//!
+//! Estrategia:
+//! 1. Reducir dimensión con PCA a r componentes
+//! 2. Ajustar el operador lineal z_{t+1} ≈ A · z_t (mínimos cuadrados) y
+//!    diagonalizarlo: modos dinámicos y valores propios
+//! 3. Almacenar: modos + valores propios + amplitudes iniciales + residuos
+//!
+//! Cada modo evoluciona como λ^t (oscila y crece o decae), así que una serie
+//! casi periódica queda descrita por un puñado de modos. Los modos complejos
+//! se guardan en forma real: un par conjugado (w, w̄) ocupa las columnas
+//! Re w e Im w, y su coeficiente evoluciona por la rotación-escala del par.
+//! Los residuos se cuantizan en lazo cerrado contra la evolución de un paso,
+//! como la trayectoria del codec de atractor.
+//!
+//! Formato del contenido: media (tipo de elemento, × dim) + valores propios
+//! (float32: uno por modo real, parte real e imaginaria por par) + modos
+//! (float32, r × dim) + longitud (u32) + GZIP(amplitudes float32 + residuos
+//! int16). Parámetros: r (u32) + modos reales (u32) + varianza explicada
+//! (f32) + residuos saturados (u32) + paso de cuantización por modo (f32 × r).
+
+use flate2::Compression;
+use nalgebra::{Complex, DMatrix, Schur, SVD};
+use ndarray::{s, Array2, ArrayView2};
+
+use super::attractor_compression::{decode_trajectory, delta_steps, encode_trajectory, reconstruct_row, DeltaQuantization};
+use super::bytes::{gzip_decode, gzip_encode, ByteReader};
+use super::container::{self, CodecId, ContainerHeader};
+use super::dynamics::{ridge_solve, solve_normal, Predictor};
+use super::element::Element;
+use super::error::CompressionError;
+use super::pca::{ComponentCount, Pca, PcaSolver};
+use super::vectors::{compress_nested, VectorsView};
+
+/// Configuración del codec DMD
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DmdConfig {
+    /// Número de modos (rango de la reducción PCA previa)
+    pub modes: ComponentCount,
+
+    /// Paso de cuantización de los residuos de los coeficientes
+    pub quantization: DeltaQuantization,
+}
+
+impl Default for DmdConfig {
+    fn default() -> Self {
+        DmdConfig { modes: ComponentCount::Fixed(10), quantization: DeltaQuantization::Adaptive }
+    }
+}
+
+/// Metadatos de un blob DMD
+#[derive(Debug, Clone, PartialEq)]
+pub struct DmdInfo {
+    /// Número de modos (columnas reales)
+    pub rank: usize,
+    /// Fracción de la varianza retenida por la reducción previa
+    pub explained_variance: f64,
+    /// Residuos saturados al rango de int16 al codificar
+    pub clipped: usize,
+    /// Valores propios (re, im): los reales primero, luego un representante
+    /// (Im > 0) por par conjugado
+    pub eigenvalues: Vec<(f32, f32)>,
+}
+
+/// Compresión DMD con la configuración por defecto
+pub fn dmd_compress(vectors: &[Vec<f32>]) -> Result<Vec<u8>, CompressionError> {
+    dmd_compress_with_config(vectors, &DmdConfig::default())
+}
+
+/// Compresión DMD con configuración completa
+pub fn dmd_compress_with_config(vectors: &[Vec<f32>], config: &DmdConfig) -> Result<Vec<u8>, CompressionError> {
+    compress_nested(vectors, |view| dmd_compress_view(view, config))
+}
+
+/// Compresión DMD sobre una vista plana
+pub fn dmd_compress_view<T: Element>(
+    vectors: VectorsView<'_, T>,
+    config: &DmdConfig,
+) -> Result<Vec<u8>, CompressionError> {
+    if vectors.is_empty() {
+        let params = encode_params(0, 0, 1.0, 0, &[]);
+        return container::wrap(CodecId::Dmd, T::TYPE, &params, 0, 0, &[]);
+    }
+
+    let n = vectors.n();
+    let dim = vectors.dim();
+    let data = ArrayView2::from_shape((n, dim), vectors.as_slice())
+        .map_err(|_| CompressionError::BadLength { field: "n", value: n })?;
+
+    // 1. Reducción PCA (media en el tipo de elemento original)
+    let mut pca = Pca::fit_count(data, config.modes, PcaSolver::Exact);
+    let explained_variance = pca.explained_variance();
+    pca.round_to_f32();
+    pca.mean.mapv_inplace(|m| T::TYPE.round(m));
+    let z = pca.project(data);
+    let r = pca.n_components();
+
+    // 2. Operador lineal z_{t+1} = A · z_t y su descomposición propia
+    let a = if n > 1 {
+        ridge_solve(&z.slice(s![..n - 1, ..]).to_owned(), &z.slice(s![1.., ..]).to_owned()).reversed_axes()
+    } else {
+        Array2::zeros((r, r))
+    };
+    let (eigenvalues, basis) = real_eigen_form(&a);
+    let real_modes = eigenvalues.iter().filter(|&&(_, im)| im == 0.0).count();
+
+    // 3. Modos en el espacio original: Ψ = Uᵀ · M (r × dim por filas, float32)
+    let mut modes = basis.t().dot(&pca.components);
+    modes.mapv_inplace(|v| v as f32 as f64);
+
+    // 4. Coeficientes por mínimos cuadrados sobre los modos tal como se guardan
+    pca.components = modes;
+    let projected = pca.project(data);
+    let gram = pca.components.dot(&pca.components.t());
+    let coefficients = solve_normal(&gram, &projected.t().to_owned()).reversed_axes();
+
+    // 5. Evolución de un paso por los valores propios (guardados en float32)
+    //    y residuos en lazo cerrado; el primer punto son las amplitudes
+    let predictor = Predictor::linear(&transition(&eigenvalues, real_modes));
+    let steps = delta_steps(&coefficients, &predictor, config.quantization);
+    let (trajectory, clipped) = encode_trajectory(&coefficients, &steps, &predictor);
+    let compressed_trajectory = gzip_encode(&trajectory, Compression::best())?;
+
+    let mut payload = Vec::new();
+    for &val in pca.mean.iter() {
+        T::TYPE.write(val, &mut payload);
+    }
+    for &(re, im) in &eigenvalues {
+        payload.extend(&re.to_le_bytes());
+        if im != 0.0 {
+            payload.extend(&im.to_le_bytes());
+        }
+    }
+    for &val in pca.components.iter() {
+        payload.extend(&(val as f32).to_le_bytes());
+    }
+    payload.extend(&(compressed_trajectory.len() as u32).to_le_bytes());
+    payload.extend(&compressed_trajectory);
+
+    let params = encode_params(r, real_modes, explained_variance, clipped, &steps);
+    container::wrap(CodecId::Dmd, T::TYPE, &params, n, dim, &payload)
+}
+
+/// Parámetros del contenedor: r (u32) + modos reales (u32) + varianza
+/// explicada (f32) + residuos saturados (u32) + pasos (f32 × r)
+fn encode_params(r: usize, real_modes: usize, explained_variance: f64, clipped: usize, steps: &[f32]) -> Vec<u8> {
+    let mut params = (r as u32).to_le_bytes().to_vec();
+    params.extend(&(real_modes as u32).to_le_bytes());
+    params.extend(&(explained_variance as f32).to_le_bytes());
+    params.extend(&(clipped.min(u32::MAX as usize) as u32).to_le_bytes());
+    for step in steps {
+        params.extend(&step.to_le_bytes());
+    }
+    params
+}
+
+/// Valores propios de A (reales primero, luego un representante con Im > 0
+/// por par conjugado, redondeados a float32) y base real M (r × r): una
+/// columna w por valor propio real, dos (Re w, Im w) por par, de norma ≤ 1
+///
+/// Valores propios repetidos toman varios vectores del mismo núcleo. Si la
+/// descomposición de Schur no converge, la base es la identidad y los
+/// valores propios 1: la evolución degenera en el predictor de deltas.
+fn real_eigen_form(a: &Array2<f64>) -> (Vec<(f32, f32)>, Array2<f64>) {
+    let r = a.nrows();
+    if r == 0 {
+        return (Vec::new(), Array2::zeros((0, 0)));
+    }
+    let matrix = DMatrix::from_fn(r, r, |i, j| a[[i, j]]);
+    let Some(schur) = Schur::try_new(matrix.clone(), f64::EPSILON, 10_000) else {
+        return (vec![(1.0, 0.0); r], Array2::eye(r));
+    };
+
+    let lambdas = schur.complex_eigenvalues();
+    let scale = lambdas.iter().map(|l| l.norm()).fold(0.0f64, f64::max);
+    let tolerance = 1e-9 * scale.max(1e-300);
+    let mut real: Vec<f64> = lambdas.iter().filter(|l| l.im.abs() <= tolerance).map(|l| l.re).collect();
+    let mut pairs: Vec<Complex<f64>> = lambdas.iter().filter(|l| l.im > tolerance).copied().collect();
+    real.sort_by(|a, b| a.total_cmp(b));
+    pairs.sort_by(|a, b| a.re.total_cmp(&b.re).then(a.im.total_cmp(&b.im)));
+
+    let mut eigenvalues = Vec::with_capacity(r);
+    let mut columns: Vec<Vec<f64>> = Vec::with_capacity(r);
+
+    for group in clusters(&real, |a, b| (a - b).abs() <= tolerance) {
+        let lambda = group.iter().sum::<f64>() / group.len() as f64;
+        let shifted = DMatrix::from_fn(r, r, |i, j| matrix[(i, j)] - if i == j { lambda } else { 0.0 });
+        for v in null_vectors(shifted, group.len()) {
+            eigenvalues.push((lambda as f32, 0.0));
+            columns.push(v.iter().map(|x| x.re).collect());
+        }
+    }
+    for group in clusters(&pairs, |a, b| (a - b).norm() <= tolerance) {
+        let lambda = group.iter().sum::<Complex<f64>>() / group.len() as f64;
+        let shifted = DMatrix::from_fn(r, r, |i, j| {
+            Complex::new(matrix[(i, j)], 0.0) - if i == j { lambda } else { Complex::new(0.0, 0.0) }
+        });
+        for v in null_vectors(shifted, group.len()) {
+            // Par conjugado: la parte imaginaria queda estrictamente positiva
+            let im = (lambda.im as f32).max(f32::MIN_POSITIVE);
+            eigenvalues.push((lambda.re as f32, im));
+            columns.push(v.iter().map(|x| x.re).collect());
+            columns.push(v.iter().map(|x| x.im).collect());
+        }
+    }
+
+    let basis = Array2::from_shape_fn((r, columns.len()), |(i, j)| columns[j][i]);
+    (eigenvalues, basis)
+}
+
+/// Agrupa valores consecutivos (ya ordenados) que `close` considera iguales
+fn clusters<V: Copy>(values: &[V], close: impl Fn(V, V) -> bool) -> Vec<Vec<V>> {
+    let mut groups: Vec<Vec<V>> = Vec::new();
+    for &value in values {
+        match groups.last_mut() {
+            Some(group) if close(group[0], value) => group.push(value),
+            _ => groups.push(vec![value]),
+        }
+    }
+    groups
+}
+
+/// Los `count` vectores singulares derechos de menor valor singular: base
+/// (aproximada) del núcleo, normalizada por la SVD
+fn null_vectors<T: nalgebra::ComplexField<RealField = f64> + Copy>(matrix: DMatrix<T>, count: usize) -> Vec<Vec<Complex<f64>>>
+where
+    Complex<f64>: From<T>,
+{
+    let svd = SVD::new(matrix, false, true);
+    let Some(v_t) = svd.v_t else { return Vec::new() };
+    let mut order: Vec<usize> = (0..svd.singular_values.len()).collect();
+    order.sort_by(|&a, &b| svd.singular_values[a].total_cmp(&svd.singular_values[b]));
+    order
+        .into_iter()
+        .take(count)
+        .map(|row| v_t.row(row).iter().map(|&x| Complex::from(x.conjugate())).collect())
+        .collect()
+}
+
+/// Matriz de evolución de un paso en la base real: escalar por modo real,
+/// bloque [[Re λ, Im λ], [-Im λ, Re λ]] por par conjugado
+fn transition(eigenvalues: &[(f32, f32)], real_modes: usize) -> Array2<f64> {
+    let r = real_modes + 2 * (eigenvalues.len() - real_modes);
+    let mut d = Array2::zeros((r, r));
+    let mut i = 0;
+    for &(re, im) in eigenvalues {
+        let (re, im) = (re as f64, im as f64);
+        if im == 0.0 {
+            d[[i, i]] = re;
+            i += 1;
+        } else {
+            d[[i, i]] = re;
+            d[[i, i + 1]] = im;
+            d[[i + 1, i]] = -im;
+            d[[i + 1, i + 1]] = re;
+            i += 2;
+        }
+    }
+    d
+}
+
+fn decode_params(params: &[u8]) -> Result<(usize, usize, f64, usize, Vec<f32>), CompressionError> {
+    let mut params = ByteReader::header(params);
+    let r = params.read_len()?;
+    let real_modes = params.read_len()?;
+    let explained_variance = params.read_f32()? as f64;
+    let clipped = params.read_len()?;
+    let steps = params.read_f32_vec(r)?;
+    if real_modes > r || (r - real_modes) % 2 != 0 {
+        return Err(CompressionError::BadLength { field: "real_modes", value: real_modes });
+    }
+    Ok((r, real_modes, explained_variance, clipped, steps))
+}
+
+/// Lee los valores propios: uno por modo real, (re, im) por par
+fn read_eigenvalues(reader: &mut ByteReader<'_>, r: usize, real_modes: usize) -> Result<Vec<(f32, f32)>, CompressionError> {
+    let pairs = (r - real_modes) / 2;
+    let mut eigenvalues = Vec::with_capacity(real_modes + pairs);
+    for _ in 0..real_modes {
+        eigenvalues.push((reader.read_f32()?, 0.0));
+    }
+    for _ in 0..pairs {
+        let re = reader.read_f32()?;
+        let im = reader.read_f32()?;
+        if im.is_nan() || im <= 0.0 {
+            return Err(CompressionError::BadLength { field: "eigenvalues", value: r });
+        }
+        eigenvalues.push((re, im));
+    }
+    Ok(eigenvalues)
+}
+
+/// Metadatos de un blob DMD (cabecera y valores propios)
+pub fn dmd_info(compressed: &[u8]) -> Result<DmdInfo, CompressionError> {
+    let (header, payload) = container::open(compressed, CodecId::Dmd)?;
+    let (rank, real_modes, explained_variance, clipped, _) = decode_params(&header.params)?;
+    let mut reader = ByteReader::payload(payload);
+    reader.take(header.dim.checked_mul(header.element.size()).ok_or(CompressionError::BadLength {
+        field: "dim",
+        value: header.dim,
+    })?)?;
+    let eigenvalues = if header.n == 0 { Vec::new() } else { read_eigenvalues(&mut reader, rank, real_modes)? };
+    Ok(DmdInfo { rank, explained_variance, clipped, eigenvalues })
+}
+
+/// Descompresión DMD
+pub fn dmd_decompress(compressed: &[u8]) -> Result<Vec<Vec<f32>>, CompressionError> {
+    container::decompress_as(compressed, CodecId::Dmd)
+}
+
+pub(crate) fn decode_payload<T: Element>(
+    header: &ContainerHeader,
+    payload: &[u8],
+    out: &mut [T],
+) -> Result<(), CompressionError> {
+    let n = header.n;
+    let dim = header.dim;
+    if n == 0 {
+        return Ok(());
+    }
+    if dim == 0 {
+        return Err(CompressionError::UnsupportedDimension { dim, min: 1 });
+    }
+
+    let (r, real_modes, _, _, steps) = decode_params(&header.params)?;
+    if r > dim {
+        return Err(CompressionError::DimensionMismatch { expected: dim, found: r });
+    }
+
+    let mut reader = ByteReader::payload(payload);
+    let mean = header.element.read_vec(&mut reader, dim)?;
+    let eigenvalues = read_eigenvalues(&mut reader, r, real_modes)?;
+    let modes: Vec<f64> = reader.read_f32_vec(r * dim)?.into_iter().map(|v| v as f64).collect();
+
+    let trajectory_len = reader.read_len()?;
+    let trajectory = gzip_decode(reader.take(trajectory_len)?)?;
+    let predictor = Predictor::linear(&transition(&eigenvalues, real_modes));
+    let coefficients = decode_trajectory(&trajectory, n, &steps, &predictor)?;
+
+    let mut row = vec![0.0f64; dim];
+    for (vec, c) in out.chunks_exact_mut(dim).zip(coefficients.rows()) {
+        reconstruct_row(&mean, &modes, c.iter().copied(), &mut row);
+        for (o, &x) in vec.iter_mut().zip(&row) {
+            *o = T::from_f64(x);
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::methods::attractor_compress_with_components;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    #[test]
+    fn test_dmd_captures_quasi_periodic_series() {
+        // Dos frecuencias inconmensurables en direcciones aleatorias de 256D:
+        // cuatro modos (dos pares conjugados sobre el círculo unidad)
+        let (n, dim) = (1500, 256);
+        let (w1, w2) = (0.05f64, 0.05 * std::f64::consts::SQRT_2);
+        let mut rng = StdRng::seed_from_u64(5);
+        let directions: Vec<Vec<f64>> = (0..4).map(|_| (0..dim).map(|_| rng.gen::<f64>() - 0.5).collect()).collect();
+        let vectors: Vec<Vec<f32>> = (0..n)
+            .map(|t| {
+                let t = t as f64;
+                let z = [(w1 * t).cos(), (w1 * t).sin(), 0.5 * (w2 * t).cos(), 0.5 * (w2 * t).sin()];
+                (0..dim).map(|j| (0.2 + (0..4).map(|m| z[m] * directions[m][j]).sum::<f64>()) as f32).collect()
+            })
+            .collect();
+
+        let config = DmdConfig { modes: ComponentCount::Fixed(4), ..DmdConfig::default() };
+        let blob = dmd_compress_with_config(&vectors, &config).unwrap();
+        let info = dmd_info(&blob).unwrap();
+        assert_eq!((info.rank, info.clipped), (4, 0));
+
+        // Valores propios e^{±iω}: módulo 1, ángulos ω1 y ω2
+        assert_eq!(info.eigenvalues.len(), 2);
+        let mut angles: Vec<f64> = info.eigenvalues.iter().map(|&(re, im)| (im as f64).atan2(re as f64)).collect();
+        angles.sort_by(f64::total_cmp);
+        assert!((angles[0] - w1).abs() < 1e-4 && (angles[1] - w2).abs() < 1e-4, "{:?}", angles);
+        for &(re, im) in &info.eigenvalues {
+            assert!(((re as f64).hypot(im as f64) - 1.0).abs() < 1e-4);
+        }
+
+        // Reconstrucción fiel y mucho más compacta que los deltas del atractor
+        let decompressed = dmd_decompress(&blob).unwrap();
+        let max_error = vectors.iter().flatten()
+            .zip(decompressed.iter().flatten())
+            .map(|(a, b)| (a - b).abs())
+            .fold(0.0f32, f32::max);
+        assert!(max_error < 1e-3, "max_error = {}", max_error);
+
+        let deltas = attractor_compress_with_components(&vectors, 4).unwrap();
+        println!("DMD {} bytes, atractor (deltas) {} bytes", blob.len(), deltas.len());
+        assert!(blob.len() * 2 < deltas.len());
+    }
+
+    #[test]
+    fn test_dmd_roundtrip_edge_cases() {
+        // Datos constantes (valores propios repetidos), un solo vector y f16
+        let constant = vec![vec![0.5f32; 16]; 20];
+        let decompressed = dmd_decompress(&dmd_compress(&constant).unwrap()).unwrap();
+        assert!(decompressed.iter().flatten().all(|&x| (x - 0.5).abs() < 1e-6));
+
+        let single = vec![vec![1.0f32, -2.0, 3.0]];
+        let decompressed = dmd_decompress(&dmd_compress(&single).unwrap()).unwrap();
+        assert!(single[0].iter().zip(&decompressed[0]).all(|(a, b)| (a - b).abs() < 1e-4));
+
+        assert!(dmd_decompress(&dmd_compress(&[]).unwrap()).unwrap().is_empty());
+
+        let data: Vec<half::f16> = (0..40 * 8).map(|i| half::f16::from_f32((i as f32 * 0.1).sin())).collect();
+        let blob = dmd_compress_view(VectorsView::new(&data, 8).unwrap(), &DmdConfig::default()).unwrap();
+        let mut out = vec![half::f16::ZERO; data.len()];
+        crate::methods::decompress_into(&blob, &mut out).unwrap();
+        assert!(data.iter().zip(&out).all(|(a, b)| (a.to_f32() - b.to_f32()).abs() < 0.01));
+    }
+}
@@ -0,0 +1,893 @@
+//! Modelos dinámicos de la trayectoria en espacio reducido
+//!
+//! El codec de atractor predice cada punto latente a partir de los anteriores
+//! ya reconstruidos y solo guarda el residuo de predicción cuantizado. Los
+//! coeficientes se guardan en float32: codificador y decodificador predicen
+//! con los mismos valores redondeados y en el mismo orden de operaciones.
+
+use nalgebra::{DMatrix, Schur};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use ndarray::{s, Array1, Array2, ArrayView1, ArrayView2, ArrayViewMut1};
+
+use super::bytes::{write_varint, ByteReader};
+use super::error::CompressionError;
+
+/// Modelo de la trayectoria latente
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum TrajectoryModel {
+    /// Deltas de primer orden: predice z_t = z_{t-1}
+    #[default]
+    Delta,
+
+    /// Autorregresivo vectorial de orden p: z_t = c + Σ_l A_l · z_{t-l}
+    Var { order: usize },
+
+    /// Mapa polinómico disperso (SINDy): z_t = z_{t-1} + Θ(z_{t-1}) · Ξ, con
+    /// Θ los monomios de grado ≤ `degree` y Ξ ajustada por mínimos cuadrados
+    /// con umbral secuencial (STLSQ). Se descartan los términos cuya
+    /// contribución RMS no llega a `threshold` × RMS del incremento.
+    Sindy { degree: usize, threshold: f64 },
+
+    /// Red de eco (reservoir computing): reservorio aleatorio fijo de
+    /// `reservoir` neuronas generado desde `seed`, escalado a radio espectral
+    /// `spectral_radius`, con integración de fuga `leak`; solo la lectura
+    /// lineal (ridge) se entrena y se guarda
+    EchoState { reservoir: usize, spectral_radius: f64, leak: f64, seed: u64 },
+
+    /// Método de análogos en el espacio de Takens: busca los `neighbors`
+    /// vectores de retardo [z_{e-(m-1)τ}, …, z_e] (m = `embedding`,
+    /// τ = `delay`) del historial ya decodificado más cercanos al actual y
+    /// predice el incremento con un ajuste lineal local sobre sus sucesores.
+    /// No guarda coeficientes; el coste es cuadrático en la longitud.
+    Analogues { embedding: usize, delay: usize, neighbors: usize },
+}
+
+impl TrajectoryModel {
+    /// Puntos previos que usa cada predicción
+    pub(crate) fn order(&self) -> usize {
+        match self {
+            TrajectoryModel::Delta | TrajectoryModel::Sindy { .. } | TrajectoryModel::EchoState { .. } => 1,
+            TrajectoryModel::Var { order } => *order,
+            TrajectoryModel::Analogues { embedding, delay, .. } => (embedding - 1) * delay + 1,
+        }
+    }
+
+    pub(crate) fn validate(&self) -> Result<(), CompressionError> {
+        match *self {
+            TrajectoryModel::Delta => Ok(()),
+            TrajectoryModel::Var { order: 0 } => {
+                Err(CompressionError::InvalidParameter { name: "var_order", value: 0.0 })
+            }
+            TrajectoryModel::Var { .. } => Ok(()),
+            TrajectoryModel::Sindy { degree: 0, .. } => {
+                Err(CompressionError::InvalidParameter { name: "sindy_degree", value: 0.0 })
+            }
+            TrajectoryModel::Sindy { threshold, .. } if !(threshold.is_finite() && threshold >= 0.0) => {
+                Err(CompressionError::InvalidParameter { name: "sindy_threshold", value: threshold })
+            }
+            TrajectoryModel::Sindy { .. } => Ok(()),
+            TrajectoryModel::EchoState { reservoir, .. } if reservoir == 0 || reservoir > MAX_RESERVOIR => {
+                Err(CompressionError::InvalidParameter { name: "esn_reservoir", value: reservoir as f64 })
+            }
+            TrajectoryModel::EchoState { spectral_radius, .. } if !(spectral_radius.is_finite() && spectral_radius > 0.0) => {
+                Err(CompressionError::InvalidParameter { name: "esn_spectral_radius", value: spectral_radius })
+            }
+            TrajectoryModel::EchoState { leak, .. } if !(leak > 0.0 && leak <= 1.0) => {
+                Err(CompressionError::InvalidParameter { name: "esn_leak", value: leak })
+            }
+            TrajectoryModel::EchoState { .. } => Ok(()),
+            TrajectoryModel::Analogues { embedding, .. } if embedding == 0 || embedding > MAX_EMBEDDING => {
+                Err(CompressionError::InvalidParameter { name: "analogue_embedding", value: embedding as f64 })
+            }
+            TrajectoryModel::Analogues { delay: 0, .. } => {
+                Err(CompressionError::InvalidParameter { name: "analogue_delay", value: 0.0 })
+            }
+            TrajectoryModel::Analogues { neighbors: 0, .. } => {
+                Err(CompressionError::InvalidParameter { name: "analogue_neighbors", value: 0.0 })
+            }
+            TrajectoryModel::Analogues { .. } => Ok(()),
+        }
+    }
+
+    /// Parámetros del contenedor: id (u8) + parámetros propios del modelo
+    pub(crate) fn write(&self, out: &mut Vec<u8>) {
+        match *self {
+            TrajectoryModel::Delta => out.push(0),
+            TrajectoryModel::Var { order } => {
+                out.push(1);
+                out.extend(&(order as u32).to_le_bytes());
+            }
+            TrajectoryModel::Sindy { degree, threshold } => {
+                out.push(2);
+                out.extend(&(degree as u32).to_le_bytes());
+                out.extend(&threshold.to_le_bytes());
+            }
+            TrajectoryModel::EchoState { reservoir, spectral_radius, leak, seed } => {
+                out.push(3);
+                out.extend(&(reservoir as u32).to_le_bytes());
+                out.extend(&spectral_radius.to_le_bytes());
+                out.extend(&leak.to_le_bytes());
+                out.extend(&seed.to_le_bytes());
+            }
+            TrajectoryModel::Analogues { embedding, delay, neighbors } => {
+                out.push(4);
+                for value in [embedding, delay, neighbors] {
+                    out.extend(&(value as u32).to_le_bytes());
+                }
+            }
+        }
+    }
+
+    pub(crate) fn read(reader: &mut ByteReader<'_>) -> Result<Self, CompressionError> {
+        let model = match reader.read_u8()? {
+            0 => TrajectoryModel::Delta,
+            1 => TrajectoryModel::Var { order: reader.read_len()? },
+            2 => TrajectoryModel::Sindy { degree: reader.read_len()?, threshold: reader.read_f64()? },
+            3 => TrajectoryModel::EchoState {
+                reservoir: reader.read_len()?,
+                spectral_radius: reader.read_f64()?,
+                leak: reader.read_f64()?,
+                seed: reader.read_u64()?,
+            },
+            4 => TrajectoryModel::Analogues {
+                embedding: reader.read_len()?,
+                delay: reader.read_len()?,
+                neighbors: reader.read_len()?,
+            },
+            tag => return Err(CompressionError::UnknownModel(tag)),
+        };
+        model.validate()?;
+        Ok(model)
+    }
+}
+
+/// Umbral de STLSQ cuando no se indica otro
+pub(crate) const DEFAULT_SINDY_THRESHOLD: f64 = 1e-3;
+
+/// Tamaño máximo de la librería de monomios de SINDy
+const MAX_LIBRARY_TERMS: usize = 1024;
+
+/// Iteraciones de STLSQ (suele converger en pocas)
+const STLSQ_ITERATIONS: usize = 10;
+
+/// Red de eco por defecto al activar `esn_*` en el codec: 100 neuronas,
+/// radio espectral 0.9, sin fuga, semilla 0
+pub(crate) const DEFAULT_ECHO_STATE: TrajectoryModel =
+    TrajectoryModel::EchoState { reservoir: 100, spectral_radius: 0.9, leak: 1.0, seed: 0 };
+
+/// Método de análogos por defecto al activar `analogue_*` en el codec
+pub(crate) const DEFAULT_ANALOGUES: TrajectoryModel =
+    TrajectoryModel::Analogues { embedding: 3, delay: 1, neighbors: 16 };
+
+/// Dimensión máxima de embedding del método de análogos
+const MAX_EMBEDDING: usize = 64;
+
+/// Regularización del ajuste lineal local: con pocos vecinos el sistema
+/// suele estar infradeterminado y debe degradar a la media de los sucesores
+const ANALOGUE_RIDGE: f64 = 1e-3;
+
+/// Neuronas máximas del reservorio de la red de eco
+const MAX_RESERVOIR: usize = 2000;
+
+/// Pasos iniciales que la red de eco descarta al entrenar (el estado aún
+/// depende de la condición inicial)
+const ESN_WASHOUT: usize = 50;
+
+/// Regularización de la lectura de la red de eco: con cientos de regresores
+/// correlacionados hace falta más que en los modelos pequeños
+const ESN_RIDGE: f64 = 1e-6;
+
+/// Regularización ridge del ajuste, sobre la matriz normal con las
+/// columnas normalizadas
+const RIDGE: f64 = 1e-9;
+
+/// Modelo ajustado, listo para predecir
+#[derive(Debug, Clone)]
+pub(crate) enum Predictor {
+    Delta,
+
+    /// Ajustado sobre los incrementos, z_t = z_{t-1} + c + Σ_l A_l · z_{t-l},
+    /// para que la regularización lleve hacia el predictor de deltas.
+    /// Coeficientes: (1 + p·k) × k, fila 0 = c, fila 1 + (l-1)·k + i = A_l[·, i]
+    Var { order: usize, coefficients: Array2<f64> },
+
+    /// Términos no nulos de Ξ por componente: (índice en la librería, coeficiente)
+    Sindy { degree: usize, threshold: f64, library: Vec<Vec<usize>>, terms: Vec<Vec<(usize, f64)>> },
+
+    EchoState(Box<EchoState>),
+
+    Analogues { embedding: usize, delay: usize, neighbors: usize },
+}
+
+/// Red de eco ajustada
+///
+/// Estado: x ← (1 - a)·x + a·tanh(W·x + W_in·u + b), con u la entrada
+/// normalizada (z - centro)·escala. Predice el incremento z_t - z_{t-1}
+/// como lectura lineal de [1, u_{t-1}, x] tras ingerir z_{t-1}.
+#[derive(Debug, Clone)]
+pub(crate) struct EchoState {
+    model: TrajectoryModel,
+    leak: f64,
+    /// Reservorio N × N ya escalado al radio espectral pedido
+    weights: Array2<f64>,
+    input_weights: Array2<f64>,
+    bias: Vec<f64>,
+    /// Factor aplicado al reservorio generado (float32, se guarda)
+    scale: f32,
+    input_center: Vec<f64>,
+    input_scale: Vec<f64>,
+    /// Lectura: (1 + k + N) × k
+    readout: Array2<f64>,
+}
+
+/// Estado interno de un predictor que recorre la trayectoria en orden
+/// (el reservorio de la red de eco; vacío para los demás modelos)
+pub(crate) struct PredictorState {
+    reservoir: Vec<f64>,
+}
+
+impl Predictor {
+    /// Ajusta el modelo por mínimos cuadrados sobre la trayectoria (n × k);
+    /// los coeficientes quedan redondeados a float32, tal como se guardan
+    pub(crate) fn fit(model: TrajectoryModel, z: ArrayView2<'_, f64>) -> Result<Predictor, CompressionError> {
+        model.validate()?;
+        match model {
+            TrajectoryModel::Delta => Ok(Predictor::Delta),
+            TrajectoryModel::Var { order } => {
+                let features = var_features(z, order);
+                let targets = increments(z, order);
+                let mut coefficients = ridge_solve(&features, &targets);
+                coefficients.mapv_inplace(|c| c as f32 as f64);
+                Ok(Predictor::Var { order, coefficients })
+            }
+            TrajectoryModel::Sindy { degree, threshold } => {
+                let library = monomials(z.ncols(), degree)?;
+                let samples = z.nrows().saturating_sub(1);
+                let mut features = Array2::zeros((samples, library.len()));
+                for (t, row) in features.rows_mut().into_iter().enumerate() {
+                    evaluate_library(&library, z.row(t), row);
+                }
+                let terms = stlsq(&features, &increments(z, 1), threshold);
+                Ok(Predictor::Sindy { degree, threshold, library, terms })
+            }
+            TrajectoryModel::EchoState { .. } => Ok(Predictor::EchoState(Box::new(EchoState::fit(model, z)))),
+            TrajectoryModel::Analogues { embedding, delay, neighbors } => {
+                Ok(Predictor::Analogues { embedding, delay, neighbors })
+            }
+        }
+    }
+
+    /// Predictor lineal de orden 1 con matriz de transición dada, z_t = D · z_{t-1}
+    /// (en la forma de `Var`, sin intercepto)
+    pub(crate) fn linear(transition: &Array2<f64>) -> Predictor {
+        let k = transition.nrows();
+        let coefficients = Array2::from_shape_fn((1 + k, k), |(row, j)| match row {
+            0 => 0.0,
+            row => transition[[j, row - 1]] - if j == row - 1 { 1.0 } else { 0.0 },
+        });
+        Predictor::Var { order: 1, coefficients }
+    }
+
+    pub(crate) fn model(&self) -> TrajectoryModel {
+        match self {
+            Predictor::Delta => TrajectoryModel::Delta,
+            Predictor::Var { order, .. } => TrajectoryModel::Var { order: *order },
+            Predictor::Sindy { degree, threshold, .. } => {
+                TrajectoryModel::Sindy { degree: *degree, threshold: *threshold }
+            }
+            Predictor::EchoState(esn) => esn.model,
+            Predictor::Analogues { embedding, delay, neighbors } => {
+                TrajectoryModel::Analogues { embedding: *embedding, delay: *delay, neighbors: *neighbors }
+            }
+        }
+    }
+
+    /// Estado inicial para recorrer una trayectoria con `predict`
+    pub(crate) fn state(&self) -> PredictorState {
+        let reservoir = match self {
+            Predictor::EchoState(esn) => vec![0.0; esn.bias.len()],
+            _ => Vec::new(),
+        };
+        PredictorState { reservoir }
+    }
+
+    /// Puntos iniciales que se guardan completos (sin historia suficiente)
+    pub(crate) fn warmup(&self) -> usize {
+        self.model().order()
+    }
+
+    /// Predicción del punto `t` desde las filas `t - warmup .. t` de `z`
+    ///
+    /// Se llama con t = warmup, warmup + 1, … en orden y el mismo `state`:
+    /// los modelos con estado (red de eco) ingieren la fila `t - 1` en cada paso.
+    pub(crate) fn predict(&self, state: &mut PredictorState, z: ArrayView2<'_, f64>, t: usize, out: &mut [f64]) {
+        let k = out.len();
+        for (o, &prev) in out.iter_mut().zip(z.row(t - 1)) {
+            *o = prev;
+        }
+        match self {
+            Predictor::Delta => {}
+            Predictor::Var { order, coefficients } => {
+                for (o, &c) in out.iter_mut().zip(coefficients.row(0)) {
+                    *o += c;
+                }
+                for lag in 1..=*order {
+                    for (i, &x) in z.row(t - lag).iter().enumerate() {
+                        let row = coefficients.row(1 + (lag - 1) * k + i);
+                        for (o, &c) in out.iter_mut().zip(row) {
+                            *o += x * c;
+                        }
+                    }
+                }
+            }
+            Predictor::Sindy { library, terms, .. } => {
+                let mut theta = Array1::zeros(library.len());
+                evaluate_library(library, z.row(t - 1), theta.view_mut());
+                for (o, terms) in out.iter_mut().zip(terms) {
+                    for &(f, c) in terms {
+                        *o += theta[f] * c;
+                    }
+                }
+            }
+            Predictor::EchoState(esn) => {
+                let input = esn.input(z.row(t - 1));
+                esn.step(&mut state.reservoir, &input);
+                esn.readout(&input, &state.reservoir, out);
+            }
+            Predictor::Analogues { embedding, delay, neighbors } => {
+                analogue_increment(z, t - 1, *embedding, *delay, *neighbors, out);
+            }
+        }
+    }
+
+    /// Residuos de predicción en lazo abierto (desde la trayectoria original),
+    /// filas `warmup .. n`
+    pub(crate) fn residuals(&self, z: ArrayView2<'_, f64>) -> Array2<f64> {
+        let (n, k) = z.dim();
+        let start = self.warmup().min(n);
+        let mut residuals = Array2::zeros((n - start, k));
+        let mut prediction = vec![0.0f64; k];
+        let mut state = self.state();
+        for (t, mut r) in (start..n).zip(residuals.rows_mut()) {
+            self.predict(&mut state, z, t, &mut prediction);
+            for ((r, &x), &p) in r.iter_mut().zip(z.row(t)).zip(&prediction) {
+                *r = x - p;
+            }
+        }
+        residuals
+    }
+
+    /// Coeficientes (float32) para el contenido del blob. SINDy guarda solo
+    /// los términos no nulos: por componente, cuántos (varint) y cada uno
+    /// como índice en la librería (varint) + coeficiente (f32)
+    pub(crate) fn write(&self, out: &mut Vec<u8>) {
+        match self {
+            Predictor::Delta => {}
+            Predictor::Var { coefficients, .. } => {
+                for &c in coefficients.iter() {
+                    out.extend(&(c as f32).to_le_bytes());
+                }
+            }
+            Predictor::Sindy { terms, .. } => {
+                for terms in terms {
+                    write_varint(terms.len() as i64, out);
+                    for &(f, c) in terms {
+                        write_varint(f as i64, out);
+                        out.extend(&(c as f32).to_le_bytes());
+                    }
+                }
+            }
+            Predictor::EchoState(esn) => esn.write(out),
+            Predictor::Analogues { .. } => {}
+        }
+    }
+
+    pub(crate) fn read(model: TrajectoryModel, k: usize, reader: &mut ByteReader<'_>) -> Result<Predictor, CompressionError> {
+        match model {
+            TrajectoryModel::Delta => Ok(Predictor::Delta),
+            TrajectoryModel::Var { order } => {
+                let rows = order
+                    .checked_mul(k)
+                    .and_then(|n| n.checked_add(1))
+                    .ok_or(CompressionError::BadLength { field: "var_order", value: order })?;
+                let values = reader.read_f32_vec(rows.saturating_mul(k))?;
+                let coefficients = Array2::from_shape_vec((rows, k), values.into_iter().map(|c| c as f64).collect())
+                    .map_err(|_| CompressionError::BadLength { field: "var_order", value: order })?;
+                Ok(Predictor::Var { order, coefficients })
+            }
+            TrajectoryModel::Sindy { degree, threshold } => {
+                let library = monomials(k, degree)?;
+                let mut terms = Vec::with_capacity(k);
+                for _ in 0..k {
+                    let count = read_index(reader, library.len() + 1, "sindy_terms")?;
+                    let mut component = Vec::with_capacity(count);
+                    for _ in 0..count {
+                        let f = read_index(reader, library.len(), "sindy_term")?;
+                        component.push((f, reader.read_f32()? as f64));
+                    }
+                    terms.push(component);
+                }
+                Ok(Predictor::Sindy { degree, threshold, library, terms })
+            }
+            TrajectoryModel::EchoState { .. } => Ok(Predictor::EchoState(Box::new(EchoState::read(model, k, reader)?))),
+            TrajectoryModel::Analogues { embedding, delay, neighbors } => {
+                Ok(Predictor::Analogues { embedding, delay, neighbors })
+            }
+        }
+    }
+}
+
+impl EchoState {
+    /// Entrena la lectura por ridge sobre los estados recorridos con la
+    /// trayectoria original (teacher forcing), descartando el transitorio
+    fn fit(model: TrajectoryModel, z: ArrayView2<'_, f64>) -> EchoState {
+        let (n, k) = z.dim();
+        let TrajectoryModel::EchoState { reservoir, spectral_radius, .. } = model else { unreachable!() };
+
+        // Normalización de la entrada: media y desviación por componente
+        let samples = n.max(1) as f64;
+        let input_center: Vec<f64> = z.columns().into_iter().map(|c| (c.sum() / samples) as f32 as f64).collect();
+        let input_scale: Vec<f64> = z
+            .columns()
+            .into_iter()
+            .zip(&input_center)
+            .map(|(c, &m)| {
+                let std = (c.iter().map(|x| (x - m).powi(2)).sum::<f64>() / samples).sqrt();
+                (if std > 0.0 { 1.0 / std } else { 1.0 }) as f32 as f64
+            })
+            .collect();
+
+        // Escala del reservorio generado para el radio espectral pedido
+        let raw = reservoir_matrices(reservoir, k, model);
+        let radius = spectral_radius_of(&raw.0);
+        let scale = if radius > 0.0 { (spectral_radius / radius) as f32 } else { 1.0 };
+        let mut esn = EchoState::from_parts(model, raw, scale, input_center, input_scale, Array2::zeros((1 + k + reservoir, k)));
+
+        // Estados con teacher forcing: fila t - 1 predice el incremento t
+        let rows = n.saturating_sub(1);
+        let mut features = Array2::zeros((rows, 1 + k + reservoir));
+        let mut state = vec![0.0; reservoir];
+        for (t, mut row) in features.rows_mut().into_iter().enumerate() {
+            let input = esn.input(z.row(t));
+            esn.step(&mut state, &input);
+            row[0] = 1.0;
+            for (r, &x) in row.iter_mut().skip(1).zip(input.iter().chain(&state)) {
+                *r = x;
+            }
+        }
+        let washout = ESN_WASHOUT.min(rows / 4);
+        let features = features.slice(s![washout.., ..]);
+        let targets = increments(z, 1 + washout);
+        let mut readout =
+            solve_regularized(&features.t().dot(&features), &features.t().dot(&targets), ESN_RIDGE);
+        readout.mapv_inplace(|c| c as f32 as f64);
+        esn.readout = readout;
+        esn
+    }
+
+    fn from_parts(
+        model: TrajectoryModel,
+        (weights, input_weights, bias): (Array2<f64>, Array2<f64>, Vec<f64>),
+        scale: f32,
+        input_center: Vec<f64>,
+        input_scale: Vec<f64>,
+        readout: Array2<f64>,
+    ) -> EchoState {
+        let TrajectoryModel::EchoState { leak, .. } = model else { unreachable!() };
+        let weights = weights.mapv(|w| w * scale as f64);
+        EchoState { model, leak, weights, input_weights, bias, scale, input_center, input_scale, readout }
+    }
+
+    fn input(&self, z: ArrayView1<'_, f64>) -> Vec<f64> {
+        z.iter().zip(&self.input_center).zip(&self.input_scale).map(|((x, c), s)| (x - c) * s).collect()
+    }
+
+    /// Un paso del reservorio con la entrada normalizada `u`
+    fn step(&self, state: &mut [f64], u: &[f64]) {
+        let activation: Vec<f64> = self
+            .weights
+            .rows()
+            .into_iter()
+            .zip(self.input_weights.rows())
+            .zip(&self.bias)
+            .map(|((w, w_in), &b)| {
+                let recurrent = w.iter().zip(state.iter()).map(|(w, x)| w * x).sum::<f64>();
+                let input = w_in.iter().zip(u).map(|(w, x)| w * x).sum::<f64>();
+                (recurrent + input + b).tanh()
+            })
+            .collect();
+        for (x, a) in state.iter_mut().zip(activation) {
+            *x = (1.0 - self.leak) * *x + self.leak * a;
+        }
+    }
+
+    /// z_{t-1} + lectura de [1, u, x]; `out` llega con z_{t-1}
+    fn readout(&self, u: &[f64], state: &[f64], out: &mut [f64]) {
+        for (o, &c) in out.iter_mut().zip(self.readout.row(0)) {
+            *o += c;
+        }
+        for (&x, row) in u.iter().chain(state).zip(self.readout.rows().into_iter().skip(1)) {
+            for (o, &c) in out.iter_mut().zip(row) {
+                *o += x * c;
+            }
+        }
+    }
+
+    /// Normalización (float32, 2k) + escala del reservorio (f32) + lectura
+    /// (float32, (1 + k + N) × k); el reservorio se regenera desde la semilla
+    fn write(&self, out: &mut Vec<u8>) {
+        for &v in self.input_center.iter().chain(&self.input_scale) {
+            out.extend(&(v as f32).to_le_bytes());
+        }
+        out.extend(&self.scale.to_le_bytes());
+        for &c in self.readout.iter() {
+            out.extend(&(c as f32).to_le_bytes());
+        }
+    }
+
+    fn read(model: TrajectoryModel, k: usize, reader: &mut ByteReader<'_>) -> Result<EchoState, CompressionError> {
+        let TrajectoryModel::EchoState { reservoir, .. } = model else { unreachable!() };
+        let widen = |v: Vec<f32>| v.into_iter().map(|x| x as f64).collect::<Vec<f64>>();
+        let input_center = widen(reader.read_f32_vec(k)?);
+        let input_scale = widen(reader.read_f32_vec(k)?);
+        let scale = reader.read_f32()?;
+        let rows = 1 + k + reservoir;
+        let readout = Array2::from_shape_vec((rows, k), widen(reader.read_f32_vec(rows * k)?))
+            .map_err(|_| CompressionError::BadLength { field: "esn_reservoir", value: reservoir })?;
+        let raw = reservoir_matrices(reservoir, k, model);
+        Ok(EchoState::from_parts(model, raw, scale, input_center, input_scale, readout))
+    }
+}
+
+/// Reservorio (N × N), pesos de entrada (N × k) y sesgos, uniformes en
+/// [-1, 1) (sesgos en [-0.1, 0.1)) desde la semilla del modelo
+fn reservoir_matrices(reservoir: usize, k: usize, model: TrajectoryModel) -> (Array2<f64>, Array2<f64>, Vec<f64>) {
+    let TrajectoryModel::EchoState { seed, .. } = model else { unreachable!() };
+    let mut rng = StdRng::seed_from_u64(seed);
+    let weights = Array2::from_shape_simple_fn((reservoir, reservoir), || rng.gen_range(-1.0..1.0));
+    let input_weights = Array2::from_shape_simple_fn((reservoir, k), || rng.gen_range(-1.0..1.0));
+    let bias = (0..reservoir).map(|_| rng.gen_range(-0.1..0.1)).collect();
+    (weights, input_weights, bias)
+}
+
+/// Vector de retardo que termina en la fila `end`, en el orden de
+/// `attractor_analysis::takens_embedding`: [z_{end-(m-1)τ}, …, z_end]
+fn delay_vector(z: ArrayView2<'_, f64>, end: usize, embedding: usize, delay: usize) -> Vec<f64> {
+    let start = end - (embedding - 1) * delay;
+    (0..embedding).flat_map(|j| z.row(start + j * delay).to_vec()).collect()
+}
+
+/// Suma a `out` (que llega con z_last) el incremento predicho para la fila
+/// `last + 1`: ajuste lineal local Δ = Δ̄ + (x - x̄)·J sobre los vecinos más
+/// cercanos del vector de retardo que termina en `last`, entre los que
+/// terminan antes y cuyo sucesor ya se conoce. Sin candidatos predice deltas.
+fn analogue_increment(z: ArrayView2<'_, f64>, last: usize, embedding: usize, delay: usize, neighbors: usize, out: &mut [f64]) {
+    let first = (embedding - 1) * delay;
+    if last <= first {
+        return;
+    }
+    let query = delay_vector(z, last, embedding, delay);
+
+    // Distancias a todos los candidatos; empates por índice (determinista)
+    let mut candidates: Vec<(f64, usize)> = (first..last)
+        .map(|end| {
+            let distance = (0..embedding)
+                .map(|j| {
+                    let offset = (embedding - 1 - j) * delay;
+                    z.row(end - offset).iter().zip(z.row(last - offset)).map(|(a, b)| (a - b).powi(2)).sum::<f64>()
+                })
+                .sum::<f64>();
+            (distance, end)
+        })
+        .collect();
+    let count = neighbors.min(candidates.len());
+    let order = |a: &(f64, usize), b: &(f64, usize)| a.0.total_cmp(&b.0).then(a.1.cmp(&b.1));
+    if count < candidates.len() {
+        candidates.select_nth_unstable_by(count, order);
+        candidates.truncate(count);
+    }
+    candidates.sort_unstable_by(order);
+
+    // Medias de los vectores de retardo y de los incrementos sucesores
+    let size = query.len();
+    let k = out.len();
+    let points: Vec<Vec<f64>> = candidates.iter().map(|&(_, end)| delay_vector(z, end, embedding, delay)).collect();
+    let steps: Vec<Vec<f64>> = candidates
+        .iter()
+        .map(|&(_, end)| z.row(end + 1).iter().zip(z.row(end)).map(|(a, b)| a - b).collect())
+        .collect();
+    let mean = |rows: &[Vec<f64>], width: usize| -> Vec<f64> {
+        (0..width).map(|i| rows.iter().map(|r| r[i]).sum::<f64>() / count as f64).collect()
+    };
+    let (point_mean, step_mean) = (mean(&points, size), mean(&steps, k));
+
+    let features = Array2::from_shape_fn((count, size), |(r, i)| points[r][i] - point_mean[i]);
+    let targets = Array2::from_shape_fn((count, k), |(r, i)| steps[r][i] - step_mean[i]);
+    let jacobian = solve_regularized(&features.t().dot(&features), &features.t().dot(&targets), ANALOGUE_RIDGE);
+    for (i, o) in out.iter_mut().enumerate() {
+        *o += step_mean[i]
+            + (0..size).map(|f| (query[f] - point_mean[f]) * jacobian[[f, i]]).sum::<f64>();
+    }
+}
+
+/// Radio espectral (mayor |λ|); 0 si la descomposición no converge
+fn spectral_radius_of(matrix: &Array2<f64>) -> f64 {
+    let size = matrix.nrows();
+    let matrix = DMatrix::from_fn(size, size, |i, j| matrix[[i, j]]);
+    Schur::try_new(matrix, f64::EPSILON, 10_000)
+        .map(|schur| schur.complex_eigenvalues().iter().map(|l| l.norm()).fold(0.0, f64::max))
+        .unwrap_or(0.0)
+}
+
+/// Varint no negativo y menor que `bound`
+fn read_index(reader: &mut ByteReader<'_>, bound: usize, field: &'static str) -> Result<usize, CompressionError> {
+    let value = reader.read_varint()?;
+    usize::try_from(value)
+        .ok()
+        .filter(|&v| v < bound)
+        .ok_or(CompressionError::BadLength { field, value: value.unsigned_abs() as usize })
+}
+
+/// Librería de monomios de grado ≤ `degree` en k variables, en orden graduado:
+/// 1, z_0, …, z_{k-1}, z_0², z_0·z_1, … (cada monomio como lista de índices)
+fn monomials(k: usize, degree: usize) -> Result<Vec<Vec<usize>>, CompressionError> {
+    // C(k + d, d) sin desbordar
+    let mut size = 1usize;
+    for d in 1..=degree {
+        size = size.saturating_mul(k + d) / d;
+        if size > MAX_LIBRARY_TERMS {
+            return Err(CompressionError::InvalidParameter { name: "sindy_degree", value: degree as f64 });
+        }
+    }
+
+    let mut library = vec![Vec::new()];
+    let mut current: Vec<Vec<usize>> = vec![Vec::new()];
+    for _ in 0..degree {
+        current = current
+            .iter()
+            .flat_map(|m| {
+                let first = m.last().copied().unwrap_or(0);
+                (first..k).map(move |i| {
+                    let mut m = m.clone();
+                    m.push(i);
+                    m
+                })
+            })
+            .collect();
+        library.extend(current.iter().cloned());
+    }
+    Ok(library)
+}
+
+/// Θ(z): valor de cada monomio de la librería
+fn evaluate_library(library: &[Vec<usize>], z: ArrayView1<'_, f64>, mut out: ArrayViewMut1<'_, f64>) {
+    for (o, monomial) in out.iter_mut().zip(library) {
+        *o = monomial.iter().fold(1.0, |p, &i| p * z[i]);
+    }
+}
+
+/// Mínimos cuadrados con umbral secuencial (STLSQ), componente a componente:
+/// ajustar, anular los términos de contribución pequeña y reajustar sobre
+/// los que quedan hasta que el soporte no cambie
+fn stlsq(features: &Array2<f64>, targets: &Array2<f64>, threshold: f64) -> Vec<Vec<(usize, f64)>> {
+    let samples = features.nrows().max(1) as f64;
+    let gram = features.t().dot(features);
+    let cross = features.t().dot(targets);
+    let scale: Vec<f64> = gram.diag().iter().map(|&g| (g / samples).sqrt()).collect();
+
+    (0..targets.ncols())
+        .map(|j| {
+            let target_rms = (targets.column(j).iter().map(|y| y * y).sum::<f64>() / samples).sqrt();
+            let mut active: Vec<usize> = (0..features.ncols()).collect();
+            let mut xi = solve_subset(&gram, &cross, j, &active);
+            for _ in 0..STLSQ_ITERATIONS {
+                let keep: Vec<usize> = active
+                    .iter()
+                    .zip(&xi)
+                    .filter(|&(&f, &c)| c.abs() * scale[f] >= threshold * target_rms)
+                    .map(|(&f, _)| f)
+                    .collect();
+                if keep.len() == active.len() {
+                    break;
+                }
+                active = keep;
+                xi = solve_subset(&gram, &cross, j, &active);
+            }
+            active
+                .into_iter()
+                .zip(xi)
+                .map(|(f, c)| (f, c as f32 as f64))
+                .filter(|&(_, c)| c != 0.0)
+                .collect()
+        })
+        .collect()
+}
+
+/// Ajuste de la componente `j` restringido a las columnas `active`
+fn solve_subset(gram: &Array2<f64>, cross: &Array2<f64>, j: usize, active: &[usize]) -> Vec<f64> {
+    let gram = Array2::from_shape_fn((active.len(), active.len()), |(a, b)| gram[[active[a], active[b]]]);
+    let cross = Array2::from_shape_fn((active.len(), 1), |(a, _)| cross[[active[a], j]]);
+    solve_normal(&gram, &cross).column(0).to_vec()
+}
+
+/// Matriz de regresores [1, z_{t-1}, …, z_{t-p}] para t = p..n
+fn var_features(z: ArrayView2<'_, f64>, order: usize) -> Array2<f64> {
+    let (n, k) = z.dim();
+    let samples = n.saturating_sub(order);
+    let mut features = Array2::zeros((samples, 1 + order * k));
+    for (s, mut row) in features.rows_mut().into_iter().enumerate() {
+        let t = s + order;
+        row[0] = 1.0;
+        for lag in 1..=order {
+            for (i, &x) in z.row(t - lag).iter().enumerate() {
+                row[1 + (lag - 1) * k + i] = x;
+            }
+        }
+    }
+    features
+}
+
+/// Incrementos z_t - z_{t-1} para t = start..n
+fn increments(z: ArrayView2<'_, f64>, start: usize) -> Array2<f64> {
+    let (n, k) = z.dim();
+    let start = start.min(n);
+    Array2::from_shape_fn((n - start, k), |(s, j)| z[[s + start, j]] - z[[s + start - 1, j]])
+}
+
+/// Mínimos cuadrados con regularización ridge: (XᵀX + λI)⁻¹ XᵀY
+pub(crate) fn ridge_solve(features: &Array2<f64>, targets: &Array2<f64>) -> Array2<f64> {
+    solve_normal(&features.t().dot(features), &features.t().dot(targets))
+}
+
+/// Resuelve (G + λI) X = C con las columnas normalizadas (diagonal de G a 1),
+/// así la regularización no depende de la escala de cada regresor
+///
+/// Sin muestras (o si la factorización falla) devuelve ceros: el modelo
+/// degenera en el predictor de deltas.
+pub(crate) fn solve_normal(gram: &Array2<f64>, cross: &Array2<f64>) -> Array2<f64> {
+    solve_regularized(gram, cross, RIDGE)
+}
+
+fn solve_regularized(gram: &Array2<f64>, cross: &Array2<f64>, ridge: f64) -> Array2<f64> {
+    let (size, outputs) = cross.dim();
+    let scale: Vec<f64> = gram.diag().iter().map(|&g| if g > 0.0 { g.sqrt() } else { 1.0 }).collect();
+    let normalized = DMatrix::from_fn(size, size, |i, j| {
+        gram[[i, j]] / (scale[i] * scale[j]) + if i == j { ridge } else { 0.0 }
+    });
+    let cross = DMatrix::from_fn(size, outputs, |i, j| cross[[i, j]] / scale[i]);
+
+    match normalized.cholesky() {
+        Some(cholesky) => {
+            let solution = cholesky.solve(&cross);
+            Array2::from_shape_fn((size, outputs), |(i, j)| solution[(i, j)] / scale[i])
+        }
+        None => Array2::zeros((size, outputs)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_var_recovers_linear_system() {
+        // Rotación amortiguada con deriva constante: z_t = A·z_{t-1} + c
+        let (angle, decay) = (0.1f64, 0.999);
+        let a = [[decay * angle.cos(), -decay * angle.sin()], [decay * angle.sin(), decay * angle.cos()]];
+        let c = [0.01, -0.02];
+        let mut z = Array2::<f64>::zeros((300, 2));
+        z[[0, 0]] = 1.0;
+        for t in 1..300 {
+            for i in 0..2 {
+                z[[t, i]] = c[i] + a[i][0] * z[[t - 1, 0]] + a[i][1] * z[[t - 1, 1]];
+            }
+        }
+
+        let predictor = Predictor::fit(TrajectoryModel::Var { order: 1 }, z.view()).unwrap();
+        let residuals = predictor.residuals(z.view());
+        assert_eq!(residuals.nrows(), 299);
+        let max_residual = residuals.iter().fold(0.0f64, |m, r| m.max(r.abs()));
+        assert!(max_residual < 1e-5, "max_residual = {}", max_residual);
+
+        // Round-trip de los coeficientes
+        let mut bytes = Vec::new();
+        predictor.write(&mut bytes);
+        let read = Predictor::read(predictor.model(), 2, &mut ByteReader::payload(&bytes)).unwrap();
+        assert_eq!(read.residuals(z.view()), predictor.residuals(z.view()));
+
+        // Sin muestras suficientes el ajuste degenera en deltas
+        let short = Predictor::fit(TrajectoryModel::Var { order: 3 }, z.slice(ndarray::s![..2, ..])).unwrap();
+        assert_eq!(short.residuals(z.slice(ndarray::s![..2, ..])).nrows(), 0);
+    }
+
+    #[test]
+    fn test_sindy_finds_sparse_lorenz_map() {
+        // Un paso de RK4 de Lorenz: dominado por el campo (términos x, y, xz,
+        // xy, z) más correcciones O(dt²) que el umbral descarta en parte
+        let points = crate::datasets::lorenz_trajectory(2000, 0.01);
+        let z = Array2::from_shape_fn((points.len(), 3), |(t, i)| points[t][i]);
+        let model = TrajectoryModel::Sindy { degree: 2, threshold: 0.05 };
+        let predictor = Predictor::fit(model, z.view()).unwrap();
+        let Predictor::Sindy { library, terms, .. } = &predictor else { unreachable!() };
+        let has = |j: usize, monomial: &[usize]| terms[j].iter().any(|&(f, _)| library[f] == monomial);
+
+        assert_eq!(library.len(), 10);
+        let nonzero: usize = terms.iter().map(Vec::len).sum();
+        assert!(nonzero <= 15, "{} términos de 30", nonzero);
+        assert!(has(0, &[0]) && has(0, &[1]) && !has(0, &[]));
+        assert!(has(1, &[0, 2]));
+        assert!(has(2, &[0, 1]) && has(2, &[2]));
+
+        // Mucho mejor que un predictor lineal
+        let var = Predictor::fit(TrajectoryModel::Var { order: 1 }, z.view()).unwrap();
+        let rms = |p: &Predictor| (p.residuals(z.view()).iter().map(|r| r * r).sum::<f64>() / z.len() as f64).sqrt();
+        assert!(rms(&predictor) * 10.0 < rms(&var), "SINDy {} vs VAR {}", rms(&predictor), rms(&var));
+
+        // Round-trip de los términos dispersos
+        let mut bytes = Vec::new();
+        predictor.write(&mut bytes);
+        let read = Predictor::read(model, 3, &mut ByteReader::payload(&bytes)).unwrap();
+        assert_eq!(read.residuals(z.view()), predictor.residuals(z.view()));
+
+        // Librería demasiado grande
+        let wide = Array2::<f64>::zeros((10, 40));
+        assert!(Predictor::fit(TrajectoryModel::Sindy { degree: 3, threshold: 0.0 }, wide.view()).is_err());
+    }
+
+    #[test]
+    fn test_echo_state_predicts_lorenz() {
+        // Paso grande (dt = 0.05): lejos del régimen lineal
+        let points = crate::datasets::lorenz_trajectory(3000, 0.05);
+        let z = Array2::from_shape_fn((points.len(), 3), |(t, i)| points[t][i]);
+        let model = TrajectoryModel::EchoState { reservoir: 200, spectral_radius: 0.9, leak: 1.0, seed: 3 };
+        let predictor = Predictor::fit(model, z.view()).unwrap();
+
+        let var = Predictor::fit(TrajectoryModel::Var { order: 1 }, z.view()).unwrap();
+        let rms = |p: &Predictor| (p.residuals(z.view()).iter().map(|r| r * r).sum::<f64>() / z.len() as f64).sqrt();
+        assert!(rms(&predictor) * 10.0 < rms(&var), "ESN {} vs VAR {}", rms(&predictor), rms(&var));
+
+        // Solo la lectura viaja: el reservorio se regenera desde la semilla
+        let mut bytes = Vec::new();
+        predictor.write(&mut bytes);
+        assert_eq!(bytes.len(), 4 * (2 * 3 + 1 + (1 + 3 + 200) * 3));
+        let read = Predictor::read(model, 3, &mut ByteReader::payload(&bytes)).unwrap();
+        assert_eq!(read.residuals(z.view()), predictor.residuals(z.view()));
+
+        // Parámetros inválidos
+        let bad = TrajectoryModel::EchoState { reservoir: 10, spectral_radius: 0.9, leak: 0.0, seed: 0 };
+        assert!(Predictor::fit(bad, z.view()).is_err());
+    }
+
+    #[test]
+    fn test_analogues_predict_from_scalar_observable() {
+        // Solo x de Lorenz: el embedding de retardos reconstruye el atractor
+        let points = crate::datasets::lorenz_trajectory(3000, 0.05);
+        let z = Array2::from_shape_fn((points.len(), 1), |(t, _)| points[t][0]);
+
+        // Mismo orden de coordenadas que attractor_analysis::takens_embedding
+        let series: Vec<Vec<f32>> = points.iter().map(|p| vec![p[0] as f32]).collect();
+        let embedded = crate::attractor_analysis::takens_embedding(&series, 2, 3);
+        let expected: Vec<f32> = delay_vector(z.view(), 104, 3, 2).iter().map(|&x| x as f32).collect();
+        assert_eq!(embedded[100], expected);
+
+        let model = TrajectoryModel::Analogues { embedding: 3, delay: 2, neighbors: 16 };
+        let predictor = Predictor::fit(model, z.view()).unwrap();
+        assert_eq!(predictor.warmup(), 5);
+        let var = Predictor::fit(TrajectoryModel::Var { order: 5 }, z.view()).unwrap();
+        // Sin los primeros 500 pasos, en los que el historial aún es escaso
+        let rms = |p: &Predictor| {
+            let r = p.residuals(z.view());
+            let tail = r.slice(s![500.., ..]);
+            (tail.iter().map(|r| r * r).sum::<f64>() / tail.len() as f64).sqrt()
+        };
+        assert!(rms(&predictor) * 5.0 < rms(&var), "análogos {} vs VAR {}", rms(&predictor), rms(&var));
+
+        // Nada que guardar: el decodificador repite la búsqueda
+        let mut bytes = Vec::new();
+        predictor.write(&mut bytes);
+        assert!(bytes.is_empty());
+        let read = Predictor::read(model, 1, &mut ByteReader::payload(&bytes)).unwrap();
+        assert_eq!(read.residuals(z.view()), predictor.residuals(z.view()));
+    }
+}
@@ -0,0 +1,328 @@
+//! Tipos de elemento soportados: f32, f16, bf16 y f64
+//!
+//! Los codecs son genéricos sobre `Element`. El tipo con que se comprimió
+//! queda en la cabecera del contenedor (`ElementType`), y los valores que
+//! se guardan tal cual (primer vector, media) se escriben en ese tipo, sin
+//! pasar por f32. La aritmética interna se hace en f64 y se redondea al
+//! tipo almacenado en cada paso, así codificador y decodificador coinciden.
+
+use half::{bf16, f16};
+
+use super::bytes::ByteReader;
+use super::error::CompressionError;
+
+/// Tipo de elemento con que se comprimió un blob
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum ElementType {
+    F32 = 0,
+    F16 = 1,
+    BF16 = 2,
+    F64 = 3,
+}
+
+impl ElementType {
+    pub fn from_u8(value: u8) -> Option<ElementType> {
+        match value {
+            0 => Some(ElementType::F32),
+            1 => Some(ElementType::F16),
+            2 => Some(ElementType::BF16),
+            3 => Some(ElementType::F64),
+            _ => None,
+        }
+    }
+
+    /// Bytes por valor
+    pub fn size(self) -> usize {
+        match self {
+            ElementType::F16 | ElementType::BF16 => 2,
+            ElementType::F32 => 4,
+            ElementType::F64 => 8,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ElementType::F32 => "f32",
+            ElementType::F16 => "f16",
+            ElementType::BF16 => "bf16",
+            ElementType::F64 => "f64",
+        }
+    }
+
+    /// Tipo en que se guardan los deltas: f32, salvo para f64
+    ///
+    /// Un delta entre dos valores f16/bf16 cabe en f32 con holgura para que,
+    /// redondeado al tipo almacenado, la reconstrucción sea exacta.
+    pub(crate) fn delta_type(self) -> ElementType {
+        match self {
+            ElementType::F64 => ElementType::F64,
+            _ => ElementType::F32,
+        }
+    }
+
+    /// Redondea `value` al valor representable más cercano de este tipo
+    pub(crate) fn round(self, value: f64) -> f64 {
+        match self {
+            ElementType::F32 => value as f32 as f64,
+            ElementType::F16 => f16::from_f64(value).to_f64(),
+            ElementType::BF16 => bf16::from_f64(value).to_f64(),
+            ElementType::F64 => value,
+        }
+    }
+
+    /// Cota del error al redondear a este tipo un valor de magnitud ≤ `magnitude`
+    /// (medio ulp relativo, más el medio ulp subnormal)
+    pub(crate) fn rounding_bound(self, magnitude: f64) -> f64 {
+        let (mantissa_bits, min_exponent) = match self {
+            ElementType::F32 => (24, -149),
+            ElementType::F16 => (11, -24),
+            ElementType::BF16 => (8, -133),
+            ElementType::F64 => (53, -1074),
+        };
+        magnitude * 2f64.powi(-mantissa_bits) + 2f64.powi(min_exponent - 1)
+    }
+
+    /// Escribe `value` little-endian en este tipo
+    pub(crate) fn write(self, value: f64, out: &mut Vec<u8>) {
+        match self {
+            ElementType::F32 => out.extend(&(value as f32).to_le_bytes()),
+            ElementType::F16 => out.extend(&f16::from_f64(value).to_le_bytes()),
+            ElementType::BF16 => out.extend(&bf16::from_f64(value).to_le_bytes()),
+            ElementType::F64 => out.extend(&value.to_le_bytes()),
+        }
+    }
+
+    /// Interpreta un valor little-endian de `size()` bytes
+    pub(crate) fn decode_le(self, bytes: &[u8]) -> f64 {
+        match self {
+            ElementType::F32 => f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as f64,
+            ElementType::F16 => f16::from_le_bytes([bytes[0], bytes[1]]).to_f64(),
+            ElementType::BF16 => bf16::from_le_bytes([bytes[0], bytes[1]]).to_f64(),
+            ElementType::F64 => {
+                let mut array = [0u8; 8];
+                array.copy_from_slice(&bytes[..8]);
+                f64::from_le_bytes(array)
+            }
+        }
+    }
+
+    /// Patrón de bits de `value`, que debe ser representable en este tipo
+    pub(crate) fn bits_of(self, value: f64) -> u64 {
+        match self {
+            ElementType::F32 => (value as f32).to_bits() as u64,
+            ElementType::F16 => f16::from_f64(value).to_bits() as u64,
+            ElementType::BF16 => bf16::from_f64(value).to_bits() as u64,
+            ElementType::F64 => value.to_bits(),
+        }
+    }
+
+    pub(crate) fn value_of(self, bits: u64) -> f64 {
+        match self {
+            ElementType::F32 => f32::from_bits(bits as u32) as f64,
+            ElementType::F16 => f16::from_bits(bits as u16).to_f64(),
+            ElementType::BF16 => bf16::from_bits(bits as u16).to_f64(),
+            ElementType::F64 => f64::from_bits(bits),
+        }
+    }
+
+    fn bits_mask(self) -> u64 {
+        u64::MAX >> (64 - 8 * self.size())
+    }
+
+    /// Delta exacto entre dos valores de este tipo: diferencia de sus patrones
+    /// de bits módulo 2^bits (pequeña si son cercanos y del mismo signo)
+    pub(crate) fn bits_delta(self, from: f64, to: f64) -> u64 {
+        self.bits_of(to).wrapping_sub(self.bits_of(from)) & self.bits_mask()
+    }
+
+    /// Inversa de `bits_delta`: `apply_bits_delta(a, bits_delta(a, b)) == b`
+    pub(crate) fn apply_bits_delta(self, from: f64, delta: u64) -> f64 {
+        self.value_of(self.bits_of(from).wrapping_add(delta) & self.bits_mask())
+    }
+
+    /// Escribe un patrón de bits en `size()` bytes little-endian
+    pub(crate) fn write_bits(self, bits: u64, out: &mut Vec<u8>) {
+        out.extend(&bits.to_le_bytes()[..self.size()]);
+    }
+
+    pub(crate) fn read_bits(self, reader: &mut ByteReader<'_>) -> Result<u64, CompressionError> {
+        let mut array = [0u8; 8];
+        array[..self.size()].copy_from_slice(reader.take(self.size())?);
+        Ok(u64::from_le_bytes(array))
+    }
+
+    pub(crate) fn read(self, reader: &mut ByteReader<'_>) -> Result<f64, CompressionError> {
+        Ok(self.decode_le(reader.take(self.size())?))
+    }
+
+    /// Lee `count` valores, verificando antes que caben en el buffer
+    pub(crate) fn read_vec(self, reader: &mut ByteReader<'_>, count: usize) -> Result<Vec<f64>, CompressionError> {
+        let len = count.checked_mul(self.size())
+            .ok_or(CompressionError::BadLength { field: "count", value: count })?;
+        Ok(reader.take(len)?.chunks_exact(self.size()).map(|chunk| self.decode_le(chunk)).collect())
+    }
+}
+
+/// Tipo numérico que los codecs aceptan como componente de un vector
+pub trait Element: bytemuck::Pod + Send + Sync + 'static {
+    const TYPE: ElementType;
+
+    fn to_f64(self) -> f64;
+
+    fn from_f64(value: f64) -> Self;
+}
+
+impl Element for f32 {
+    const TYPE: ElementType = ElementType::F32;
+
+    fn to_f64(self) -> f64 {
+        self as f64
+    }
+
+    fn from_f64(value: f64) -> Self {
+        value as f32
+    }
+}
+
+impl Element for f64 {
+    const TYPE: ElementType = ElementType::F64;
+
+    fn to_f64(self) -> f64 {
+        self
+    }
+
+    fn from_f64(value: f64) -> Self {
+        value
+    }
+}
+
+impl Element for f16 {
+    const TYPE: ElementType = ElementType::F16;
+
+    fn to_f64(self) -> f64 {
+        f16::to_f64(self)
+    }
+
+    fn from_f64(value: f64) -> Self {
+        f16::from_f64(value)
+    }
+}
+
+impl Element for bf16 {
+    const TYPE: ElementType = ElementType::BF16;
+
+    fn to_f64(self) -> f64 {
+        bf16::to_f64(self)
+    }
+
+    fn from_f64(value: f64) -> Self {
+        bf16::from_f64(value)
+    }
+}
+
+/// Escribe valores de tipo `T` little-endian (sin copia en hosts little-endian)
+pub(crate) fn element_le_bytes<T: Element>(values: &[T]) -> std::borrow::Cow<'_, [u8]> {
+    if cfg!(target_endian = "little") {
+        std::borrow::Cow::Borrowed(bytemuck::cast_slice(values))
+    } else {
+        let mut bytes = Vec::with_capacity(values.len() * T::TYPE.size());
+        for &value in values {
+            T::TYPE.write(value.to_f64(), &mut bytes);
+        }
+        std::borrow::Cow::Owned(bytes)
+    }
+}
+
+/// Convierte valores little-endian de tipo `stored` al tipo de salida
+pub(crate) fn convert_le_bytes<T: Element>(
+    bytes: &[u8],
+    stored: ElementType,
+    out: &mut [T],
+) -> Result<(), CompressionError> {
+    let found = bytes.len() / stored.size();
+    if found != out.len() || !bytes.len().is_multiple_of(stored.size()) {
+        return Err(CompressionError::DimensionMismatch { expected: out.len(), found });
+    }
+
+    if cfg!(target_endian = "little") && stored == T::TYPE {
+        bytemuck::cast_slice_mut::<T, u8>(out).copy_from_slice(bytes);
+    } else {
+        for (value, chunk) in out.iter_mut().zip(bytes.chunks_exact(stored.size())) {
+            *value = T::from_f64(stored.decode_le(chunk));
+        }
+    }
+    Ok(())
+}
+
+/// Si el buffer de salida tiene el tipo almacenado, devuelve sus bytes para
+/// descomprimir directamente en él
+pub(crate) fn direct_bytes_mut<T: Element>(stored: ElementType, out: &mut [T]) -> Option<&mut [u8]> {
+    if cfg!(target_endian = "little") && stored == T::TYPE {
+        Some(bytemuck::cast_slice_mut(out))
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::methods::container::read_header;
+    use crate::methods::{
+        attractor_compress_view, decompress_into, delta_compress_view, delta_lossless_compress_view,
+        VectorsView,
+    };
+
+    fn half_data(n: usize, dim: usize) -> Vec<f16> {
+        (0..n * dim)
+            .map(|i| f16::from_f32(((i / dim) as f32 * 0.05 + (i % dim) as f32).sin()))
+            .collect()
+    }
+
+    #[test]
+    fn test_delta_lossless_exact_for_half_types() {
+        let (n, dim) = (300, 16);
+        let data = half_data(n, dim);
+        let blob = delta_lossless_compress_view(VectorsView::new(&data, dim).unwrap(), 64).unwrap();
+        assert_eq!(read_header(&blob).unwrap().0.element, ElementType::F16);
+
+        let mut out = vec![f16::ZERO; n * dim];
+        decompress_into(&blob, &mut out).unwrap();
+        assert_eq!(out, data);
+
+        let bdata: Vec<bf16> = data.iter().map(|x| bf16::from_f32(x.to_f32())).collect();
+        let blob = delta_lossless_compress_view(VectorsView::new(&bdata, dim).unwrap(), 64).unwrap();
+        let mut out = vec![bf16::ZERO; n * dim];
+        decompress_into(&blob, &mut out).unwrap();
+        assert_eq!(out, bdata);
+
+        let ddata: Vec<f64> = data.iter().map(|x| x.to_f64() + 1e-9).collect();
+        let blob = delta_lossless_compress_view(VectorsView::new(&ddata, dim).unwrap(), 64).unwrap();
+        let mut out = vec![0.0f64; n * dim];
+        decompress_into(&blob, &mut out).unwrap();
+        for (a, b) in out.iter().zip(&ddata) {
+            assert!((a - b).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn test_half_precision_stores_header_values_natively() {
+        let (n, dim) = (200, 64);
+        let data = half_data(n, dim);
+        let upcast: Vec<f32> = data.iter().map(|x| x.to_f32()).collect();
+
+        // La media (atractor) y el primer vector (delta) ocupan 2 bytes por valor
+        let half_blob = attractor_compress_view(VectorsView::new(&data, dim).unwrap(), 8).unwrap();
+        let f32_blob = attractor_compress_view(VectorsView::new(&upcast, dim).unwrap(), 8).unwrap();
+        assert!(half_blob.len() < f32_blob.len());
+
+        let mut out = vec![f16::ZERO; n * dim];
+        let header = decompress_into(&half_blob, &mut out).unwrap();
+        assert_eq!(header.element, ElementType::F16);
+
+        let half_blob = delta_compress_view(VectorsView::new(&data, dim).unwrap()).unwrap();
+        let f32_blob = delta_compress_view(VectorsView::new(&upcast, dim).unwrap()).unwrap();
+        assert!(half_blob.len() < f32_blob.len());
+    }
+}
@@ -0,0 +1,151 @@
+//! Errores de compresión y descompresión
+
+use std::fmt;
+
+use super::container::CodecId;
+
+/// Backend de codificación de entropía que produjo un error
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Backend {
+    Gzip,
+    Zstd,
+    Ans,
+}
+
+impl fmt::Display for Backend {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Backend::Gzip => write!(f, "gzip"),
+            Backend::Zstd => write!(f, "zstd"),
+            Backend::Ans => write!(f, "ANS"),
+        }
+    }
+}
+
+/// Error de compresión o descompresión
+#[derive(Debug)]
+pub enum CompressionError {
+    /// El blob termina antes de completar la cabecera
+    TruncatedHeader { needed: usize, available: usize },
+
+    /// El contenido (ya sin cabecera) es más corto de lo que declara la cabecera
+    TruncatedPayload { needed: usize, available: usize },
+
+    /// Un campo de longitud o conteo es imposible para el tamaño del blob
+    BadLength { field: &'static str, value: usize },
+
+    /// Vectores de distinta dimensión, o datos que no cuadran con n × dim
+    DimensionMismatch { expected: usize, found: usize },
+
+    /// El método no admite vectores de esta dimensión
+    UnsupportedDimension { dim: usize, min: usize },
+
+    /// Fallo del backend de entropía (gzip, zstd, ANS)
+    Backend { backend: Backend, message: String },
+
+    /// El blob no empieza con los bytes mágicos del contenedor
+    BadMagic,
+
+    /// Versión de formato desconocida
+    UnsupportedVersion(u8),
+
+    /// Id de codec desconocido en la cabecera
+    UnknownCodecId(u8),
+
+    /// Tipo de elemento desconocido en la cabecera
+    UnknownElementType(u8),
+
+    /// Modelo de trayectoria desconocido en los parámetros del codec de atractor
+    UnknownModel(u8),
+
+    /// El blob fue producido por otro codec
+    WrongCodec { expected: CodecId, found: CodecId },
+
+    /// El CRC32 del contenido no coincide con el de la cabecera
+    ChecksumMismatch { expected: u32, computed: u32 },
+
+    /// El codec no admite codificación en streaming
+    UnsupportedStreamCodec(CodecId),
+
+    /// Un frame de stream llegó fuera de orden
+    StreamOutOfOrder { expected: u32, found: u32 },
+
+    /// Rango de vectores fuera de `0..len`
+    RangeOutOfBounds { start: usize, end: usize, len: usize },
+
+    /// La vista de entrada no es contigua en fila mayor
+    NonContiguous,
+
+    /// El buffer de salida no alcanza para n × dim valores
+    BufferTooSmall { needed: usize, available: usize },
+
+    /// Parámetro de compresión fuera de su rango válido
+    InvalidParameter { name: &'static str, value: f64 },
+}
+
+impl CompressionError {
+    pub(crate) fn backend(backend: Backend, err: impl fmt::Display) -> Self {
+        CompressionError::Backend { backend, message: err.to_string() }
+    }
+
+    /// Error del codificador ANS (`constriction`)
+    pub(crate) fn ans(err: impl fmt::Display) -> Self {
+        Self::backend(Backend::Ans, err)
+    }
+}
+
+impl fmt::Display for CompressionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CompressionError::TruncatedHeader { needed, available } => {
+                write!(f, "cabecera truncada: se necesitan {} bytes, hay {}", needed, available)
+            }
+            CompressionError::TruncatedPayload { needed, available } => {
+                write!(f, "contenido truncado: se necesitan {} bytes, hay {}", needed, available)
+            }
+            CompressionError::BadLength { field, value } => {
+                write!(f, "campo de longitud inválido {}: {}", field, value)
+            }
+            CompressionError::DimensionMismatch { expected, found } => {
+                write!(f, "dimensión inconsistente: se esperaba {}, se encontró {}", expected, found)
+            }
+            CompressionError::UnsupportedDimension { dim, min } => {
+                write!(f, "dimensión {} no soportada (mínimo {})", dim, min)
+            }
+            CompressionError::Backend { backend, message } => {
+                write!(f, "error de {}: {}", backend, message)
+            }
+            CompressionError::BadMagic => write!(f, "bytes mágicos inválidos: no es un blob del contenedor"),
+            CompressionError::UnsupportedVersion(version) => {
+                write!(f, "versión de formato no soportada: {}", version)
+            }
+            CompressionError::UnknownCodecId(id) => write!(f, "id de codec desconocido: {}", id),
+            CompressionError::UnknownElementType(id) => write!(f, "tipo de elemento desconocido: {}", id),
+            CompressionError::UnknownModel(id) => write!(f, "modelo de trayectoria desconocido: {}", id),
+            CompressionError::WrongCodec { expected, found } => {
+                write!(f, "blob de {} decodificado como {}", found.registry_id(), expected.registry_id())
+            }
+            CompressionError::ChecksumMismatch { expected, computed } => {
+                write!(f, "CRC32 inválido: cabecera {:08x}, calculado {:08x}", expected, computed)
+            }
+            CompressionError::UnsupportedStreamCodec(codec) => {
+                write!(f, "el codec {} no admite streaming", codec.registry_id())
+            }
+            CompressionError::StreamOutOfOrder { expected, found } => {
+                write!(f, "frame de stream fuera de orden: se esperaba {}, llegó {}", expected, found)
+            }
+            CompressionError::RangeOutOfBounds { start, end, len } => {
+                write!(f, "rango {}..{} fuera de los {} vectores del blob", start, end, len)
+            }
+            CompressionError::NonContiguous => write!(f, "la matriz de entrada no es contigua en fila mayor"),
+            CompressionError::BufferTooSmall { needed, available } => {
+                write!(f, "buffer de salida insuficiente: se necesitan {} valores, hay {}", needed, available)
+            }
+            CompressionError::InvalidParameter { name, value } => {
+                write!(f, "parámetro {} inválido: {}", name, value)
+            }
+        }
+    }
+}
+
+impl std::error::Error for CompressionError {}
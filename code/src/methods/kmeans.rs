@@ -0,0 +1,134 @@
+//! K-means determinista (inicialización k-means++ desde una semilla)
+//!
+//! Lo usan los codecs que parten los vectores en regiones: la misma semilla
+//! y los mismos datos dan siempre los mismos centros.
+
+use ndarray::{Array2, ArrayView1, ArrayView2};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+/// Particiones ajustadas
+#[derive(Debug, Clone)]
+pub(crate) struct KMeans {
+    /// Centros (c × dim)
+    pub(crate) centers: Array2<f64>,
+
+    /// Centro asignado a cada fila de los datos
+    pub(crate) assignments: Vec<usize>,
+}
+
+impl KMeans {
+    /// Lloyd con a lo sumo `iterations` pasadas (para antes si ninguna
+    /// asignación cambia); c = min(`clusters`, filas)
+    ///
+    /// Un centro que se queda sin puntos conserva su posición anterior.
+    pub(crate) fn fit(data: ArrayView2<'_, f64>, clusters: usize, iterations: usize, seed: u64) -> KMeans {
+        let (n, dim) = data.dim();
+        let centers = plus_plus(data, clusters.min(n), seed);
+        let mut kmeans = KMeans { centers, assignments: vec![0; n] };
+        kmeans.assign(data);
+
+        for _ in 0..iterations {
+            let c = kmeans.centers.nrows();
+            let mut sums = Array2::<f64>::zeros((c, dim));
+            let mut counts = vec![0usize; c];
+            for (row, &j) in data.rows().into_iter().zip(&kmeans.assignments) {
+                let mut sum = sums.row_mut(j);
+                sum += &row;
+                counts[j] += 1;
+            }
+            for ((mut center, sum), &count) in kmeans.centers.rows_mut().into_iter().zip(sums.rows()).zip(&counts) {
+                if count > 0 {
+                    center.assign(&(&sum / count as f64));
+                }
+            }
+            if !kmeans.assign(data) {
+                break;
+            }
+        }
+        kmeans
+    }
+
+    /// Reasigna cada fila a su centro más cercano; `true` si alguna cambió
+    fn assign(&mut self, data: ArrayView2<'_, f64>) -> bool {
+        let mut changed = false;
+        for (row, assignment) in data.rows().into_iter().zip(self.assignments.iter_mut()) {
+            let j = nearest(&self.centers, row).0;
+            changed |= j != *assignment;
+            *assignment = j;
+        }
+        changed
+    }
+}
+
+/// k-means++: el primer centro uniforme, el resto con probabilidad
+/// proporcional a la distancia² al centro más cercano ya elegido
+fn plus_plus(data: ArrayView2<'_, f64>, clusters: usize, seed: u64) -> Array2<f64> {
+    let (n, dim) = data.dim();
+    let mut centers = Array2::zeros((clusters, dim));
+    if clusters == 0 {
+        return centers;
+    }
+    let mut rng = StdRng::seed_from_u64(seed);
+    centers.row_mut(0).assign(&data.row(rng.gen_range(0..n)));
+    let mut distances: Vec<f64> = data.rows().into_iter().map(|row| squared_distance(row, centers.row(0))).collect();
+
+    for j in 1..clusters {
+        let total: f64 = distances.iter().sum();
+        // Todos los puntos ya coinciden con un centro: se repite el último
+        let chosen = if total > 0.0 {
+            let mut target = rng.gen::<f64>() * total;
+            distances.iter().position(|&d| {
+                target -= d;
+                target < 0.0
+            }).unwrap_or(n - 1)
+        } else {
+            n - 1
+        };
+        centers.row_mut(j).assign(&data.row(chosen));
+        for (d, row) in distances.iter_mut().zip(data.rows()) {
+            *d = d.min(squared_distance(row, centers.row(j)));
+        }
+    }
+    centers
+}
+
+/// (índice, distancia²) del centro más cercano a `row` (empates al de menor
+/// índice)
+fn nearest(centers: &Array2<f64>, row: ArrayView1<'_, f64>) -> (usize, f64) {
+    centers
+        .rows()
+        .into_iter()
+        .map(|center| squared_distance(row, center))
+        .enumerate()
+        .fold((0, f64::INFINITY), |best, (j, d)| if d < best.1 { (j, d) } else { best })
+}
+
+fn squared_distance(a: ArrayView1<'_, f64>, b: ArrayView1<'_, f64>) -> f64 {
+    a.iter().zip(b.iter()).map(|(x, y)| (x - y).powi(2)).sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_kmeans_separates_blobs() {
+        // Tres nubes separadas, en orden mezclado
+        let offsets = [[0.0, 0.0], [10.0, 0.0], [0.0, 10.0]];
+        let data = Array2::from_shape_fn((300, 2), |(i, j)| offsets[i % 3][j] + ((i * 7 + j * 13) % 17) as f64 * 0.01);
+
+        let kmeans = KMeans::fit(data.view(), 3, 50, 1);
+        for i in 3..300 {
+            assert_eq!(kmeans.assignments[i], kmeans.assignments[i % 3]);
+        }
+        let mut labels = kmeans.assignments[..3].to_vec();
+        labels.sort_unstable();
+        assert_eq!(labels, vec![0, 1, 2]);
+        assert_eq!(nearest(&kmeans.centers, ndarray::arr1(&[9.0, 1.0]).view()).0, kmeans.assignments[1]);
+
+        // Determinista con la misma semilla; más centros que filas se recorta
+        assert_eq!(KMeans::fit(data.view(), 3, 50, 1).centers, kmeans.centers);
+        assert_eq!(KMeans::fit(data.slice(ndarray::s![..2, ..]), 5, 10, 0).centers.nrows(), 2);
+    }
+}
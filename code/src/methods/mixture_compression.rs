@@ -0,0 +1,354 @@
+//! Compresión por mezcla de PCA (subespacios locales)
+//!
+//! Estrategia:
+//! 1. Agrupar los vectores con k-means (semilla fija)
+//! 2. Ajustar una base PCA propia en cada grupo
+//! 3. Almacenar: grupo de cada vector + coordenadas locales cuantizadas
+//!
+//! Datos repartidos en varios parches de baja dimensión (temas distintos)
+//! necesitan muchas componentes en una base global; con una base por parche
+//! bastan unas pocas coordenadas por vector. Las coordenadas se cuantizan a
+//! int16 sin predicción: dentro de un grupo no hay orden temporal útil.
+//!
+//! Formato del contenido: por grupo, media (tipo de elemento, × dim) +
+//! componentes (float32, k_j × dim) + paso por componente (f32 × k_j); luego
+//! longitud (u32) + GZIP(grupo u16 × n + coordenadas int16 en orden de
+//! filas). Parámetros: grupos (u32) + varianza explicada (f32) + coordenadas
+//! saturadas (u32) + componentes por grupo (u32 × grupos).
+
+use flate2::Compression;
+use ndarray::{Array2, ArrayView2, Axis};
+
+use super::attractor_compression::{reconstruct_row, DeltaQuantization, ADAPTIVE_LEVELS, MAX_QUANTIZED_DELTA};
+use super::bytes::{gzip_decode, gzip_encode, ByteReader};
+use super::container::{self, CodecId, ContainerHeader};
+use super::element::Element;
+use super::error::CompressionError;
+use super::kmeans::KMeans;
+use super::pca::{ComponentCount, Pca, PcaSolver};
+use super::vectors::{compress_nested, VectorsView};
+
+/// Grupos máximos (el índice de grupo se guarda en u16 y los parámetros
+/// deben caber en la cabecera)
+pub const MAX_CLUSTERS: usize = 4096;
+
+/// Configuración del codec de mezcla de PCA
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MixtureConfig {
+    /// Grupos de k-means (menos si hay menos vectores)
+    pub clusters: usize,
+
+    /// Componentes de la base local de cada grupo
+    pub components: ComponentCount,
+
+    /// Pasadas máximas de Lloyd
+    pub iterations: usize,
+
+    /// Semilla de la inicialización k-means++
+    pub seed: u64,
+
+    /// Paso de cuantización de las coordenadas locales
+    pub quantization: DeltaQuantization,
+}
+
+impl Default for MixtureConfig {
+    fn default() -> Self {
+        MixtureConfig {
+            clusters: 8,
+            components: ComponentCount::Fixed(4),
+            iterations: 25,
+            seed: 0,
+            quantization: DeltaQuantization::Adaptive,
+        }
+    }
+}
+
+/// Metadatos de un blob de mezcla de PCA
+#[derive(Debug, Clone, PartialEq)]
+pub struct MixtureInfo {
+    /// Componentes de la base local de cada grupo
+    pub components: Vec<usize>,
+    /// Fracción de la varianza total retenida (medias de grupo + bases)
+    pub explained_variance: f64,
+    /// Coordenadas saturadas al rango de int16 al codificar
+    pub clipped: usize,
+}
+
+/// Compresión por mezcla de PCA con la configuración por defecto
+pub fn mixture_compress(vectors: &[Vec<f32>]) -> Result<Vec<u8>, CompressionError> {
+    mixture_compress_with_config(vectors, &MixtureConfig::default())
+}
+
+/// Compresión por mezcla de PCA con configuración completa
+pub fn mixture_compress_with_config(vectors: &[Vec<f32>], config: &MixtureConfig) -> Result<Vec<u8>, CompressionError> {
+    compress_nested(vectors, |view| mixture_compress_view(view, config))
+}
+
+/// Compresión por mezcla de PCA sobre una vista plana
+pub fn mixture_compress_view<T: Element>(
+    vectors: VectorsView<'_, T>,
+    config: &MixtureConfig,
+) -> Result<Vec<u8>, CompressionError> {
+    if config.clusters == 0 || config.clusters > MAX_CLUSTERS {
+        return Err(CompressionError::InvalidParameter { name: "n_clusters", value: config.clusters as f64 });
+    }
+    if let DeltaQuantization::Fixed(step) | DeltaQuantization::TargetDistortion(step) = config.quantization {
+        if !(step.is_finite() && step > 0.0) {
+            return Err(CompressionError::InvalidParameter { name: "delta_step", value: step });
+        }
+    }
+    if vectors.is_empty() {
+        let params = encode_params(1.0, 0, &[]);
+        return container::wrap(CodecId::MixturePca, T::TYPE, &params, 0, 0, &[]);
+    }
+
+    let n = vectors.n();
+    let dim = vectors.dim();
+    let data = ArrayView2::from_shape((n, dim), vectors.as_slice())
+        .map_err(|_| CompressionError::BadLength { field: "n", value: n })?;
+    let wide = data.mapv(|x| x.to_f64());
+
+    // 1. Grupos
+    let kmeans = KMeans::fit(wide.view(), config.clusters, config.iterations, config.seed);
+    let clusters = kmeans.centers.nrows();
+    let members: Vec<Vec<usize>> = (0..clusters)
+        .map(|j| (0..n).filter(|&i| kmeans.assignments[i] == j).collect())
+        .collect();
+
+    // 2. Base local por grupo (media en el tipo de elemento original, base
+    //    en float32) y coordenadas con esa base redondeada
+    let mut payload = Vec::new();
+    let mut coordinates: Vec<Array2<f64>> = Vec::with_capacity(clusters);
+    let mut all_steps: Vec<Vec<f32>> = Vec::with_capacity(clusters);
+    let mut ranks = Vec::with_capacity(clusters);
+    let mut residual_variance = 0.0;
+    for (rows, center) in members.iter().zip(kmeans.centers.rows()) {
+        let local = wide.select(Axis(0), rows);
+        let mut pca = if rows.is_empty() {
+            Pca { mean: center.to_owned(), components: Array2::zeros((0, dim)), variances: Vec::new(), total_variance: 0.0 }
+        } else {
+            Pca::fit_count(local.view(), config.components, PcaSolver::Exact)
+        };
+        residual_variance += rows.len() as f64 * (pca.total_variance - pca.variances.iter().sum::<f64>()).max(0.0);
+        pca.round_to_f32();
+        pca.mean.mapv_inplace(|m| T::TYPE.round(m));
+        let z = pca.project(local.view());
+        let steps = coordinate_steps(&z, config.quantization);
+
+        for &val in pca.mean.iter() {
+            T::TYPE.write(val, &mut payload);
+        }
+        for &val in pca.components.iter() {
+            payload.extend(&(val as f32).to_le_bytes());
+        }
+        for step in &steps {
+            payload.extend(&step.to_le_bytes());
+        }
+        ranks.push(pca.n_components());
+        coordinates.push(z);
+        all_steps.push(steps);
+    }
+
+    // 3. Grupo de cada vector y coordenadas int16, en orden de filas
+    let mut stream = Vec::with_capacity(n * 2);
+    for &j in &kmeans.assignments {
+        stream.extend(&(j as u16).to_le_bytes());
+    }
+    let mut next = vec![0usize; clusters];
+    let mut clipped = 0;
+    for &j in &kmeans.assignments {
+        for (&c, &step) in coordinates[j].row(next[j]).iter().zip(&all_steps[j]) {
+            let q = (c / step as f64).round();
+            if q.abs() > i16::MAX as f64 {
+                clipped += 1;
+            }
+            stream.extend(&(q.clamp(i16::MIN as f64, i16::MAX as f64) as i16).to_le_bytes());
+        }
+        next[j] += 1;
+    }
+    let compressed = gzip_encode(&stream, Compression::best())?;
+    payload.extend(&(compressed.len() as u32).to_le_bytes());
+    payload.extend(&compressed);
+
+    let total_variance: f64 = wide.columns().into_iter().map(|c| {
+        let mean = c.sum() / n as f64;
+        c.iter().map(|x| (x - mean).powi(2)).sum::<f64>()
+    }).sum();
+    let explained_variance = if total_variance > 0.0 { (1.0 - residual_variance / total_variance).max(0.0) } else { 1.0 };
+
+    let params = encode_params(explained_variance, clipped, &ranks);
+    container::wrap(CodecId::MixturePca, T::TYPE, &params, n, dim, &payload)
+}
+
+/// Paso por componente local: como el de los deltas del atractor, pero sobre
+/// la escala de las propias coordenadas (centradas en la media del grupo)
+fn coordinate_steps(z: &Array2<f64>, quantization: DeltaQuantization) -> Vec<f32> {
+    let n = z.nrows().max(1) as f64;
+    z.columns()
+        .into_iter()
+        .map(|c| {
+            let step = match quantization {
+                DeltaQuantization::Fixed(step) => step,
+                DeltaQuantization::TargetDistortion(d) => d * 12f64.sqrt(),
+                DeltaQuantization::Adaptive => {
+                    let sigma = (c.iter().map(|x| x * x).sum::<f64>() / n).sqrt();
+                    let max = c.iter().fold(0.0f64, |m, x| m.max(x.abs()));
+                    (sigma / ADAPTIVE_LEVELS).max(max / MAX_QUANTIZED_DELTA)
+                }
+            } as f32;
+            if step.is_normal() { step } else { 1.0 }
+        })
+        .collect()
+}
+
+/// Parámetros del contenedor: grupos (u32) + varianza explicada (f32) +
+/// coordenadas saturadas (u32) + componentes por grupo (u32 × grupos)
+fn encode_params(explained_variance: f64, clipped: usize, ranks: &[usize]) -> Vec<u8> {
+    let mut params = (ranks.len() as u32).to_le_bytes().to_vec();
+    params.extend(&(explained_variance as f32).to_le_bytes());
+    params.extend(&(clipped.min(u32::MAX as usize) as u32).to_le_bytes());
+    for &k in ranks {
+        params.extend(&(k as u32).to_le_bytes());
+    }
+    params
+}
+
+fn decode_params(params: &[u8]) -> Result<MixtureInfo, CompressionError> {
+    let mut params = ByteReader::header(params);
+    let clusters = params.read_len()?;
+    if clusters > MAX_CLUSTERS {
+        return Err(CompressionError::BadLength { field: "n_clusters", value: clusters });
+    }
+    let explained_variance = params.read_f32()? as f64;
+    let clipped = params.read_len()?;
+    let components = (0..clusters).map(|_| params.read_len()).collect::<Result<_, _>>()?;
+    Ok(MixtureInfo { components, explained_variance, clipped })
+}
+
+/// Metadatos de un blob de mezcla de PCA (de su cabecera)
+pub fn mixture_info(compressed: &[u8]) -> Result<MixtureInfo, CompressionError> {
+    let header = container::read_header(compressed)?.0;
+    if header.codec != CodecId::MixturePca {
+        return Err(CompressionError::WrongCodec { expected: CodecId::MixturePca, found: header.codec });
+    }
+    decode_params(&header.params)
+}
+
+/// Descompresión por mezcla de PCA
+pub fn mixture_decompress(compressed: &[u8]) -> Result<Vec<Vec<f32>>, CompressionError> {
+    container::decompress_as(compressed, CodecId::MixturePca)
+}
+
+pub(crate) fn decode_payload<T: Element>(
+    header: &ContainerHeader,
+    payload: &[u8],
+    out: &mut [T],
+) -> Result<(), CompressionError> {
+    let n = header.n;
+    let dim = header.dim;
+    if n == 0 {
+        return Ok(());
+    }
+    if dim == 0 {
+        return Err(CompressionError::UnsupportedDimension { dim, min: 1 });
+    }
+
+    let info = decode_params(&header.params)?;
+    let mut reader = ByteReader::payload(payload);
+    let mut bases = Vec::with_capacity(info.components.len());
+    for &k in &info.components {
+        if k > dim {
+            return Err(CompressionError::DimensionMismatch { expected: dim, found: k });
+        }
+        let mean = header.element.read_vec(&mut reader, dim)?;
+        let components: Vec<f64> = reader.read_f32_vec(k * dim)?.into_iter().map(|v| v as f64).collect();
+        let steps = reader.read_f32_vec(k)?;
+        bases.push((mean, components, steps));
+    }
+
+    let stream_len = reader.read_len()?;
+    let stream = gzip_decode(reader.take(stream_len)?)?;
+    let mut stream = ByteReader::payload(&stream);
+    let mut assignments = Vec::with_capacity(n);
+    for _ in 0..n {
+        let j = stream.read_u16()? as usize;
+        if j >= bases.len() {
+            return Err(CompressionError::BadLength { field: "cluster", value: j });
+        }
+        assignments.push(j);
+    }
+
+    let mut row = vec![0.0f64; dim];
+    for (vec, &j) in out.chunks_exact_mut(dim).zip(&assignments) {
+        let (mean, components, steps) = &bases[j];
+        let z = steps.iter().map(|&step| stream.read_i16().map(|q| q as f64 * step as f64)).collect::<Result<Vec<_>, _>>()?;
+        reconstruct_row(mean, components, z.into_iter(), &mut row);
+        for (o, &x) in vec.iter_mut().zip(&row) {
+            *o = T::from_f64(x);
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::datasets::generate_clustered_topics_with_rng;
+    use crate::methods::{attractor_compress_with_components, attractor_decompress};
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    fn mse(a: &[Vec<f32>], b: &[Vec<f32>]) -> f64 {
+        let sum: f64 = a.iter().flatten().zip(b.iter().flatten()).map(|(x, y)| ((x - y) as f64).powi(2)).sum();
+        sum / a.len() as f64
+    }
+
+    #[test]
+    fn test_mixture_beats_global_basis_on_clustered_topics() {
+        // 20 temas en 128D: con 2 coordenadas por vector la base global no
+        // distingue los temas; la mezcla los separa con la media de cada grupo
+        let vectors = generate_clustered_topics_with_rng(2000, 128, 100, &mut StdRng::seed_from_u64(29));
+        let config = MixtureConfig { clusters: 20, components: ComponentCount::Fixed(2), ..MixtureConfig::default() };
+        let blob = mixture_compress_with_config(&vectors, &config).unwrap();
+        let info = mixture_info(&blob).unwrap();
+        assert_eq!((info.components, info.clipped), (vec![2; 20], 0));
+        assert!(info.explained_variance > 0.95, "{}", info.explained_variance);
+        let mixture_error = mse(&vectors, &mixture_decompress(&blob).unwrap());
+
+        let global = attractor_compress_with_components(&vectors, 2).unwrap();
+        let global_error = mse(&vectors, &attractor_decompress(&global).unwrap());
+        let wide = attractor_compress_with_components(&vectors, 20).unwrap();
+        let wide_error = mse(&vectors, &attractor_decompress(&wide).unwrap());
+        println!(
+            "mezcla 20×PCA-2: {} bytes, MSE {:.2e}; atractor PCA-2: {} bytes, MSE {:.2e}; PCA-20: {} bytes, MSE {:.2e}",
+            blob.len(), mixture_error, global.len(), global_error, wide.len(), wide_error
+        );
+        // El resto es el ruido isótropo de cada tema: la base global necesita
+        // una componente por tema (y más bytes) para llegar al mismo error
+        assert!(mixture_error * 20.0 < global_error);
+        assert!(mixture_error < wide_error * 1.5 && blob.len() < wide.len());
+    }
+
+    #[test]
+    fn test_mixture_roundtrip_edge_cases() {
+        // Más grupos que vectores, datos constantes, vacío y f16
+        let few = vec![vec![1.0f32, -2.0, 3.0], vec![0.5, 0.5, 0.5]];
+        let decompressed = mixture_decompress(&mixture_compress(&few).unwrap()).unwrap();
+        assert!(few.iter().flatten().zip(decompressed.iter().flatten()).all(|(a, b)| (a - b).abs() < 1e-6));
+
+        let constant = vec![vec![0.25f32; 16]; 30];
+        let decompressed = mixture_decompress(&mixture_compress(&constant).unwrap()).unwrap();
+        assert!(decompressed.iter().flatten().all(|&x| (x - 0.25).abs() < 1e-6));
+
+        assert!(mixture_decompress(&mixture_compress(&[]).unwrap()).unwrap().is_empty());
+        let config = MixtureConfig { clusters: 0, ..MixtureConfig::default() };
+        assert!(mixture_compress_with_config(&few, &config).is_err());
+
+        let data: Vec<half::f16> = (0..60 * 8).map(|i| half::f16::from_f32((i as f32 * 0.1).sin())).collect();
+        let blob = mixture_compress_view(VectorsView::new(&data, 8).unwrap(), &MixtureConfig::default()).unwrap();
+        let mut out = vec![half::f16::ZERO; data.len()];
+        crate::methods::decompress_into(&blob, &mut out).unwrap();
+        assert!(data.iter().zip(&out).all(|(a, b)| (a.to_f32() - b.to_f32()).abs() < 0.05));
+    }
+}
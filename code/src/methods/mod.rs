@@ -1,118 +1,271 @@
 use flate2::Compression;
-use flate2::write::GzEncoder;
-use flate2::read::GzDecoder;
-use std::io::{Write, Read};
+
+pub mod error;
+pub use error::{Backend, CompressionError};
+
+pub(crate) mod bytes;
+use bytes::{check_count, gzip_decode, gzip_decode_into, gzip_encode, ByteReader};
+
+pub mod element;
+pub use element::{Element, ElementType};
+use element::{convert_le_bytes, direct_bytes_mut, element_le_bytes};
+
+pub mod vectors;
+pub use vectors::VectorsView;
+use vectors::compress_nested;
+
+pub mod container;
+pub use container::{
+    decompress_any, decompress_into, decompress_one, decompress_range, decompress_to_array, CodecId,
+    ContainerHeader,
+};
 
 pub mod ans_simple;
-pub use ans_simple::{delta_ans_compress, delta_ans_decompress};
+pub use ans_simple::{delta_ans_compress, delta_ans_compress_view, delta_ans_decompress};
+
+pub mod ans;
+pub use ans::{delta_rans_compress, delta_rans_compress_view, delta_rans_decompress};
 
 pub mod delta_lossless;
-pub use delta_lossless::{delta_lossless_compress, delta_lossless_decompress};
+pub use delta_lossless::{
+    delta_lossless_compress, delta_lossless_compress_view, delta_lossless_compress_with_keyframes,
+    delta_lossless_decompress, DEFAULT_KEYFRAME_INTERVAL,
+};
+
+pub(crate) mod pca;
+pub use pca::{ComponentCount, PcaSolver, RandomizedSvd};
+
+pub(crate) mod dynamics;
+pub use dynamics::TrajectoryModel;
 
 pub mod attractor_compression;
-pub use attractor_compression::{attractor_compress, attractor_decompress};
+pub use attractor_compression::{
+    attractor_compress, attractor_compress_view, attractor_compress_view_with_config,
+    attractor_compress_view_with_report, attractor_compress_with_components, attractor_compress_with_config,
+    attractor_decompress, attractor_explained_variance, attractor_info, AttractorConfig, AttractorInfo, AttractorSizes,
+    DeltaQuantization, LatentAnsInfo, LatentCoder, ShadowingInfo,
+};
+
+pub mod dmd_compression;
+pub use dmd_compression::{
+    dmd_compress, dmd_compress_view, dmd_compress_with_config, dmd_decompress, dmd_info, DmdConfig, DmdInfo,
+};
+
+pub(crate) mod kmeans;
+
+pub mod mixture_compression;
+pub use mixture_compression::{
+    mixture_compress, mixture_compress_view, mixture_compress_with_config, mixture_decompress, mixture_info,
+    MixtureConfig, MixtureInfo,
+};
+
+pub mod autoencoder_compression;
+pub use autoencoder_compression::{
+    autoencoder_compress, autoencoder_compress_view, autoencoder_compress_view_with_report,
+    autoencoder_compress_with_config, autoencoder_decompress, autoencoder_info, AutoencoderConfig, AutoencoderInfo,
+    AutoencoderReport,
+};
+
+pub mod symbolic_compression;
+pub use symbolic_compression::{
+    symbolic_compress, symbolic_compress_view, symbolic_compress_with_config, symbolic_decompress, symbolic_info,
+    SymbolicConfig, SymbolicInfo,
+};
+
+pub mod stream;
+pub use stream::{StreamDecoder, StreamEncoder};
+
+pub mod codec;
+pub use codec::{Codec, CodecRegistry, ParamError, ParamValue};
+
+pub fn gzip_compress(vectors: &[Vec<f32>]) -> Result<Vec<u8>, CompressionError> {
+    compress_nested(vectors, gzip_compress_view)
+}
 
-pub fn gzip_compress(vectors: &[Vec<f32>]) -> Vec<u8> {
-    let bytes: Vec<u8> = vectors.iter()
-        .flat_map(|v| v.iter().flat_map(|&f| f.to_le_bytes()))
-        .collect();
+pub fn gzip_compress_view<T: Element>(vectors: VectorsView<'_, T>) -> Result<Vec<u8>, CompressionError> {
+    let bytes = element_le_bytes(vectors.as_slice());
+    let payload = gzip_encode(&bytes, Compression::default())?;
+    container::wrap(CodecId::Gzip, T::TYPE, &[], vectors.n(), vectors.dim(), &payload)
+}
 
-    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
-    encoder.write_all(&bytes).unwrap();
-    encoder.finish().unwrap()
+pub fn gzip_decompress(compressed: &[u8]) -> Result<Vec<Vec<f32>>, CompressionError> {
+    container::decompress_as(compressed, CodecId::Gzip)
 }
 
-pub fn gzip_decompress(compressed: &[u8]) -> Vec<Vec<f32>> {
-    let mut decoder = GzDecoder::new(compressed);
-    let mut bytes = Vec::new();
-    decoder.read_to_end(&mut bytes).unwrap();
+pub(crate) fn decode_gzip_payload<T: Element>(
+    header: &ContainerHeader,
+    payload: &[u8],
+    out: &mut [T],
+) -> Result<(), CompressionError> {
+    if header.n == 0 {
+        return Ok(());
+    }
 
-    let floats: Vec<f32> = bytes.chunks_exact(4)
-        .map(|chunk| f32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]))
-        .collect();
+    match direct_bytes_mut(header.element, out) {
+        // Descomprimir directamente en el buffer de salida
+        Some(bytes) => gzip_decode_into(payload, bytes),
+        None => convert_le_bytes(&gzip_decode(payload)?, header.element, out),
+    }
+}
 
-    vec![floats] // Simplificado
+pub fn int8_compress(vectors: &[Vec<f32>]) -> Result<Vec<u8>, CompressionError> {
+    compress_nested(vectors, int8_compress_view)
 }
 
-pub fn int8_compress(vectors: &[Vec<f32>]) -> Vec<u8> {
+pub fn int8_compress_view<T: Element>(vectors: VectorsView<'_, T>) -> Result<Vec<u8>, CompressionError> {
     // Cuantizar a int8
-    let bytes: Vec<u8> = vectors.iter()
-        .flat_map(|v| v.iter().map(|&f| ((f * 127.0).clamp(-128.0, 127.0) as i8) as u8))
+    let bytes: Vec<u8> = vectors.as_slice().iter()
+        .map(|&f| ((f.to_f64() as f32 * 127.0).clamp(-128.0, 127.0) as i8) as u8)
         .collect();
 
-    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
-    encoder.write_all(&bytes).unwrap();
-    encoder.finish().unwrap()
+    let payload = gzip_encode(&bytes, Compression::default())?;
+    container::wrap(CodecId::Int8Gzip, T::TYPE, &[], vectors.n(), vectors.dim(), &payload)
 }
 
-pub fn int8_decompress(compressed: &[u8]) -> Vec<Vec<f32>> {
-    let mut decoder = GzDecoder::new(compressed);
-    let mut bytes = Vec::new();
-    decoder.read_to_end(&mut bytes).unwrap();
+pub fn int8_decompress(compressed: &[u8]) -> Result<Vec<Vec<f32>>, CompressionError> {
+    container::decompress_as(compressed, CodecId::Int8Gzip)
+}
 
-    let floats: Vec<f32> = bytes.iter()
-        .map(|&b| (b as i8) as f32 / 127.0)
-        .collect();
+pub(crate) fn decode_int8_payload<T: Element>(
+    header: &ContainerHeader,
+    payload: &[u8],
+    out: &mut [T],
+) -> Result<(), CompressionError> {
+    if header.n == 0 {
+        return Ok(());
+    }
 
-    vec![floats]
+    let bytes = gzip_decode(payload)?;
+    if bytes.len() != out.len() {
+        return Err(CompressionError::DimensionMismatch { expected: out.len(), found: bytes.len() });
+    }
+
+    for (value, &b) in out.iter_mut().zip(&bytes) {
+        *value = T::from_f64(((b as i8) as f32 / 127.0) as f64);
+    }
+    Ok(())
 }
 
-pub fn delta_compress(vectors: &[Vec<f32>]) -> Vec<u8> {
+pub fn delta_compress(vectors: &[Vec<f32>]) -> Result<Vec<u8>, CompressionError> {
+    compress_nested(vectors, delta_compress_view)
+}
+
+/// Primera fila en el tipo original + deltas (f32, o f64 para datos f64)
+pub fn delta_compress_view<T: Element>(vectors: VectorsView<'_, T>) -> Result<Vec<u8>, CompressionError> {
     if vectors.is_empty() {
-        return vec![];
+        return container::wrap(CodecId::DeltaGzip, T::TYPE, &[], 0, 0, &[]);
+    }
+    let element = T::TYPE;
+    let delta_type = element.delta_type();
+
+    let mut rows = vectors.rows();
+    let first = rows.next().unwrap_or_default();
+    let mut deltas = Vec::with_capacity(vectors.as_slice().len() * delta_type.size());
+
+    // Primera fila completa, sin convertir
+    deltas.extend(element_le_bytes(first).iter());
+
+    // Deltas del resto, contra el vector que reconstruirá el decodificador
+    // (así el redondeo no se acumula al integrar)
+    let mut reconstructed: Vec<f64> = first.iter().map(|x| x.to_f64()).collect();
+    for vector in rows {
+        for (&curr, prev) in vector.iter().zip(reconstructed.iter_mut()) {
+            let delta = delta_type.round(curr.to_f64() - *prev);
+            delta_type.write(delta, &mut deltas);
+            *prev = element.round(*prev + delta);
+        }
     }
 
-    let mut deltas = Vec::new();
+    let payload = gzip_encode(&deltas, Compression::best())?;
+    container::wrap(CodecId::DeltaGzip, element, &[], vectors.n(), vectors.dim(), &payload)
+}
 
-    // Primera fila completa
-    deltas.extend(vectors[0].iter().flat_map(|&f| f.to_le_bytes()));
+pub fn delta_decompress(compressed: &[u8]) -> Result<Vec<Vec<f32>>, CompressionError> {
+    container::decompress_as(compressed, CodecId::DeltaGzip)
+}
 
-    // Deltas del resto
-    for i in 1..vectors.len() {
-        for j in 0..vectors[i].len() {
-            let delta = vectors[i][j] - vectors[i-1][j];
-            deltas.extend(&delta.to_le_bytes());
-        }
+pub(crate) fn decode_delta_payload<T: Element>(
+    header: &ContainerHeader,
+    payload: &[u8],
+    out: &mut [T],
+) -> Result<(), CompressionError> {
+    let dim = header.dim;
+    if header.n == 0 || dim == 0 {
+        return Ok(());
     }
+    let element = header.element;
+    let delta_type = element.delta_type();
 
-    let mut encoder = GzEncoder::new(Vec::new(), Compression::best());
-    encoder.write_all(&deltas).unwrap();
-    encoder.finish().unwrap()
-}
+    let bytes = gzip_decode(payload)?;
+    let mut reader = ByteReader::payload(&bytes);
 
-pub fn delta_decompress(compressed: &[u8]) -> Vec<Vec<f32>> {
-    let mut decoder = GzDecoder::new(compressed);
-    let mut bytes = Vec::new();
-    decoder.read_to_end(&mut bytes).unwrap();
+    let mut prev = element.read_vec(&mut reader, dim)?;
+    check_count("n", header.n - 1, dim * delta_type.size(), reader.remaining())?;
 
-    let floats: Vec<f32> = bytes.chunks_exact(4)
-        .map(|chunk| f32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]))
-        .collect();
+    // Integrar deltas: v[i] = v[i-1] + delta[i]
+    let mut rows = out.chunks_exact_mut(dim);
+    if let Some(row) = rows.next() {
+        for (o, &p) in row.iter_mut().zip(&prev) {
+            *o = T::from_f64(p);
+        }
+    }
+    for row in rows {
+        for (o, p) in row.iter_mut().zip(prev.iter_mut()) {
+            *p = element.round(*p + delta_type.read(&mut reader)?);
+            *o = T::from_f64(*p);
+        }
+    }
 
-    vec![floats]
+    Ok(())
 }
 
-pub fn zstd_compress(vectors: &[Vec<f32>]) -> Vec<u8> {
-    let bytes: Vec<u8> = vectors.iter()
-        .flat_map(|v| v.iter().flat_map(|&f| f.to_le_bytes()))
-        .collect();
+pub fn zstd_compress(vectors: &[Vec<f32>]) -> Result<Vec<u8>, CompressionError> {
+    zstd_compress_with_level(vectors, 3)
+}
+
+/// Zstd con nivel de compresión configurable (guardado en los parámetros del contenedor)
+pub fn zstd_compress_with_level(vectors: &[Vec<f32>], level: i32) -> Result<Vec<u8>, CompressionError> {
+    compress_nested(vectors, |view| zstd_compress_view(view, level))
+}
 
-    zstd::encode_all(&bytes[..], 3).unwrap()
+pub fn zstd_compress_view<T: Element>(vectors: VectorsView<'_, T>, level: i32) -> Result<Vec<u8>, CompressionError> {
+    let bytes = element_le_bytes(vectors.as_slice());
+    let payload = zstd::encode_all(&bytes[..], level)
+        .map_err(|e| CompressionError::backend(Backend::Zstd, e))?;
+    container::wrap(CodecId::Zstd, T::TYPE, &level.to_le_bytes(), vectors.n(), vectors.dim(), &payload)
 }
 
-pub fn zstd_decompress(compressed: &[u8]) -> Vec<Vec<f32>> {
-    let bytes = zstd::decode_all(compressed).unwrap();
+pub fn zstd_decompress(compressed: &[u8]) -> Result<Vec<Vec<f32>>, CompressionError> {
+    container::decompress_as(compressed, CodecId::Zstd)
+}
 
-    let floats: Vec<f32> = bytes.chunks_exact(4)
-        .map(|chunk| f32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]))
-        .collect();
+pub(crate) fn decode_zstd_payload<T: Element>(
+    header: &ContainerHeader,
+    payload: &[u8],
+    out: &mut [T],
+) -> Result<(), CompressionError> {
+    if header.n == 0 {
+        return Ok(());
+    }
 
-    vec![floats]
+    let Some(bytes) = direct_bytes_mut(header.element, out) else {
+        let bytes = zstd::decode_all(payload).map_err(|e| CompressionError::backend(Backend::Zstd, e))?;
+        return convert_le_bytes(&bytes, header.element, out);
+    };
+
+    let expected = bytes.len();
+    let written = zstd::bulk::decompress_to_buffer(payload, bytes)
+        .map_err(|e| CompressionError::backend(Backend::Zstd, e))?;
+    if written != expected {
+        let size = header.element.size();
+        return Err(CompressionError::DimensionMismatch { expected: expected / size, found: written / size });
+    }
+    Ok(())
 }
 
 /// Convierte vector cartesiano a ángulos esféricos (hyperspherical coordinates)
 /// Para un vector n-dimensional, genera n-1 ángulos
-fn to_spherical_angles(vec: &[f32]) -> Vec<f32> {
+pub(crate) fn to_spherical_angles(vec: &[f32]) -> Vec<f32> {
     let n = vec.len();
     let mut angles = Vec::with_capacity(n - 1);
 
@@ -120,15 +273,15 @@ fn to_spherical_angles(vec: &[f32]) -> Vec<f32> {
     let mut r_squared: f64 = vec.iter().map(|&x| (x as f64) * (x as f64)).sum();
 
     // Primeros n-2 ángulos (coordenadas polares generalizadas)
-    for i in 0..n-2 {
+    for &x in &vec[..n - 2] {
         if r_squared > 1e-10 {
             let r = r_squared.sqrt();
-            let cos_theta = (vec[i] as f64) / r;
+            let cos_theta = (x as f64) / r;
             let theta = cos_theta.clamp(-1.0, 1.0).acos();
             angles.push(theta as f32);
 
             // Actualizar r² para siguiente iteración
-            r_squared -= (vec[i] as f64) * (vec[i] as f64);
+            r_squared -= (x as f64) * (x as f64);
         } else {
             angles.push(0.0);
         }
@@ -144,15 +297,15 @@ fn to_spherical_angles(vec: &[f32]) -> Vec<f32> {
 }
 
 /// Convierte ángulos esféricos de vuelta a vector cartesiano
-fn from_spherical_angles(angles: &[f32], magnitude: f32) -> Vec<f32> {
+pub(crate) fn from_spherical_angles(angles: &[f32], magnitude: f32) -> Vec<f32> {
     let n = angles.len() + 1; // n-1 ángulos → n dimensiones
     let mut vec = vec![0.0f32; n];
 
     let mut r = magnitude as f64;
 
     // Reconstruir usando producto de senos
-    for i in 0..n-2 {
-        let theta = angles[i] as f64;
+    for (i, &theta) in angles.iter().enumerate().take(n - 2) {
+        let theta = theta as f64;
         vec[i] = (r * theta.cos()) as f32;
         r *= theta.sin();
     }
@@ -167,30 +320,40 @@ fn from_spherical_angles(angles: &[f32], magnitude: f32) -> Vec<f32> {
     vec
 }
 
+fn write_elements<T: Element>(out: &mut [T], values: &[f32]) {
+    for (o, &v) in out.iter_mut().zip(values) {
+        *o = T::from_f64(v as f64);
+    }
+}
+
 /// Polar Delta Encoding: convierte a coordenadas esféricas y codifica deltas angulares
-pub fn polar_delta_compress(vectors: &[Vec<f32>]) -> Vec<u8> {
+pub fn polar_delta_compress(vectors: &[Vec<f32>]) -> Result<Vec<u8>, CompressionError> {
+    compress_nested(vectors, polar_delta_compress_view)
+}
+
+pub fn polar_delta_compress_view<T: Element>(vectors: VectorsView<'_, T>) -> Result<Vec<u8>, CompressionError> {
     if vectors.is_empty() {
-        return vec![];
+        return container::wrap(CodecId::PolarDelta, T::TYPE, &[], 0, 0, &[]);
+    }
+    let dim = vectors.dim();
+    if dim < 2 {
+        return Err(CompressionError::UnsupportedDimension { dim, min: 2 });
     }
 
-    let dim = vectors[0].len();
-    let n_vectors = vectors.len();
+    let n_vectors = vectors.n();
 
     // Convertir todos los vectores a representación polar
-    let polar_vecs: Vec<(f32, Vec<f32>)> = vectors.iter()
-        .map(|v| {
+    let polar_vecs: Vec<(f32, Vec<f32>)> = vectors.rows()
+        .map(|row| {
+            let v: Vec<f32> = row.iter().map(|x| x.to_f64() as f32).collect();
             let magnitude = v.iter().map(|&x| x * x).sum::<f32>().sqrt();
-            let angles = to_spherical_angles(v);
+            let angles = to_spherical_angles(&v);
             (magnitude, angles)
         })
         .collect();
 
     let mut data = Vec::new();
 
-    // Metadata: número de vectores y dimensiones
-    data.extend(&(n_vectors as u32).to_le_bytes());
-    data.extend(&(dim as u32).to_le_bytes());
-
     // Primer vector completo (magnitud + ángulos en float32)
     data.extend(&polar_vecs[0].0.to_le_bytes());
     for &angle in &polar_vecs[0].1 {
@@ -214,75 +377,132 @@ pub fn polar_delta_compress(vectors: &[Vec<f32>]) -> Vec<u8> {
     }
 
     // Comprimir con GZIP
-    let mut encoder = GzEncoder::new(Vec::new(), Compression::best());
-    encoder.write_all(&data).unwrap();
-    encoder.finish().unwrap()
+    let payload = gzip_encode(&data, Compression::best())?;
+    container::wrap(CodecId::PolarDelta, T::TYPE, &[], n_vectors, dim, &payload)
 }
 
 /// Descompresión de Polar Delta Encoding
-pub fn polar_delta_decompress(compressed: &[u8]) -> Vec<Vec<f32>> {
-    let mut decoder = GzDecoder::new(compressed);
-    let mut data = Vec::new();
-    decoder.read_to_end(&mut data).unwrap();
+pub fn polar_delta_decompress(compressed: &[u8]) -> Result<Vec<Vec<f32>>, CompressionError> {
+    container::decompress_as(compressed, CodecId::PolarDelta)
+}
 
-    if data.len() < 8 {
-        return vec![];
+pub(crate) fn decode_polar_delta_payload<T: Element>(
+    header: &ContainerHeader,
+    payload: &[u8],
+    out: &mut [T],
+) -> Result<(), CompressionError> {
+    let n_vectors = header.n;
+    let dim = header.dim;
+    if n_vectors == 0 {
+        return Ok(());
+    }
+    if dim < 2 {
+        return Err(CompressionError::UnsupportedDimension { dim, min: 2 });
     }
-
-    // Leer metadata
-    let n_vectors = u32::from_le_bytes([data[0], data[1], data[2], data[3]]) as usize;
-    let dim = u32::from_le_bytes([data[4], data[5], data[6], data[7]]) as usize;
     let n_angles = dim - 1;
 
-    let mut vectors = Vec::with_capacity(n_vectors);
-    let mut offset = 8;
+    let data = gzip_decode(payload)?;
+    let mut reader = ByteReader::payload(&data);
+
+    // Cada vector tras el primero ocupa: delta de magnitud (f32) + ángulos (i16)
+    check_count("n", n_vectors - 1, 4 + 2 * n_angles, reader.remaining())?;
+
+    let mut rows = out.chunks_exact_mut(dim);
 
     // Leer primer vector completo
-    let first_magnitude = f32::from_le_bytes([
-        data[offset], data[offset+1], data[offset+2], data[offset+3]
-    ]);
-    offset += 4;
-
-    let mut first_angles = Vec::with_capacity(n_angles);
-    for _ in 0..n_angles {
-        let angle = f32::from_le_bytes([
-            data[offset], data[offset+1], data[offset+2], data[offset+3]
-        ]);
-        first_angles.push(angle);
-        offset += 4;
-    }
+    let first_magnitude = reader.read_f32()?;
+    let first_angles = reader.read_f32_vec(n_angles)?;
 
-    vectors.push(from_spherical_angles(&first_angles, first_magnitude));
+    if let Some(row) = rows.next() {
+        write_elements(row, &from_spherical_angles(&first_angles, first_magnitude));
+    }
 
     // Reconstruir vectores desde deltas
     let mut prev_magnitude = first_magnitude;
     let mut prev_angles = first_angles;
 
-    for _ in 1..n_vectors {
+    for row in rows {
         // Leer delta de magnitud
-        let mag_delta = f32::from_le_bytes([
-            data[offset], data[offset+1], data[offset+2], data[offset+3]
-        ]);
-        offset += 4;
-
+        let mag_delta = reader.read_f32()?;
         let current_magnitude = prev_magnitude + mag_delta;
 
         // Leer deltas angulares y desquantizar
         let mut current_angles = Vec::with_capacity(n_angles);
-        for j in 0..n_angles {
-            let quantized = i16::from_le_bytes([data[offset], data[offset+1]]);
-            offset += 2;
+        for &prev_angle in prev_angles.iter().take(n_angles) {
+            let quantized = reader.read_i16()?;
 
             // Desescalar: ±32767 → ±π
             let angle_delta = (quantized as f32) * (std::f32::consts::PI / 32767.0);
-            current_angles.push(prev_angles[j] + angle_delta);
+            current_angles.push(prev_angle + angle_delta);
         }
 
-        vectors.push(from_spherical_angles(&current_angles, current_magnitude));
+        write_elements(row, &from_spherical_angles(&current_angles, current_magnitude));
 
         prev_magnitude = current_magnitude;
         prev_angles = current_angles;
     }
 
-    vectors
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn drifting_vectors(n: usize, dim: usize) -> Vec<Vec<f32>> {
+        (0..n)
+            .map(|i| (0..dim).map(|j| ((i as f32) * 0.01 + (j as f32) * 0.37).sin() * 0.9).collect())
+            .collect()
+    }
+
+    #[test]
+    fn test_gzip_roundtrip_preserves_shape() {
+        let vectors = drifting_vectors(50, 12);
+        let decompressed = gzip_decompress(&gzip_compress(&vectors).unwrap()).unwrap();
+        assert_eq!(decompressed, vectors);
+    }
+
+    #[test]
+    fn test_zstd_roundtrip_preserves_shape() {
+        let vectors = drifting_vectors(50, 12);
+        let decompressed = zstd_decompress(&zstd_compress_with_level(&vectors, 9).unwrap()).unwrap();
+        assert_eq!(decompressed, vectors);
+    }
+
+    #[test]
+    fn test_int8_roundtrip_preserves_shape() {
+        let vectors = drifting_vectors(50, 12);
+        let decompressed = int8_decompress(&int8_compress(&vectors).unwrap()).unwrap();
+
+        assert_eq!(decompressed.len(), vectors.len());
+        for (orig, decomp) in vectors.iter().zip(decompressed.iter()) {
+            assert_eq!(orig.len(), decomp.len());
+            for (o, d) in orig.iter().zip(decomp.iter()) {
+                // Error de cuantización: la conversión a int8 trunca, a lo sumo un paso de 1/127
+                assert!((o - d).abs() <= 1.0 / 127.0 + 1e-6, "Original: {}, Decomprimido: {}", o, d);
+            }
+        }
+    }
+
+    #[test]
+    fn test_delta_roundtrip_integrates_deltas() {
+        let vectors = drifting_vectors(2000, 16);
+        let decompressed = delta_decompress(&delta_compress(&vectors).unwrap()).unwrap();
+
+        assert_eq!(decompressed.len(), vectors.len());
+        for (orig, decomp) in vectors.iter().zip(decompressed.iter()) {
+            assert_eq!(orig.len(), decomp.len());
+            for (o, d) in orig.iter().zip(decomp.iter()) {
+                // Sin deriva: a lo sumo un redondeo de float32 por valor
+                assert!((o - d).abs() <= 1e-6, "Original: {}, Decomprimido: {}", o, d);
+            }
+        }
+    }
+
+    #[test]
+    fn test_empty_input_roundtrip() {
+        let empty: Vec<Vec<f32>> = Vec::new();
+        assert!(gzip_decompress(&gzip_compress(&empty).unwrap()).unwrap().is_empty());
+        assert!(delta_decompress(&delta_compress(&empty).unwrap()).unwrap().is_empty());
+    }
 }
@@ -0,0 +1,495 @@
+//! Análisis de componentes principales (PCA) sobre una matriz n × dim
+//!
+//! Dos métodos de cálculo:
+//! - Exacto: se descompone la matriz de covarianza (dim × dim) o, si hay
+//!   menos vectores que dimensiones, la matriz de Gram (n × n), que comparte
+//!   los autovalores no nulos y es mucho más pequeña.
+//! - Aleatorizado (Halko, Martinsson y Tropp, 2011): se estima el rango de
+//!   los datos centrados con k + p proyecciones gaussianas, refinadas con
+//!   iteraciones de potencia, y se resuelve una SVD pequeña en ese subespacio.
+//!   Nunca materializa la matriz centrada ni la covarianza: recorre los datos
+//!   por bloques de filas en paralelo.
+//!
+//! La base resultante es ortonormal y se ordena por varianza explicada
+//! descendente.
+
+use nalgebra::{DMatrix, SymmetricEigen};
+use ndarray::linalg::general_mat_mul;
+use ndarray::{Array1, Array2, ArrayView1, ArrayView2, Axis};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use rayon::prelude::*;
+
+use super::element::Element;
+
+/// Autovalores por debajo de esta fracción del mayor se consideran nulos
+const EIGEN_TOLERANCE: f64 = 1e-12;
+
+/// Filas por bloque en los productos paralelos
+const BLOCK_ROWS: usize = 1024;
+
+/// Método de cálculo de la base PCA
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PcaSolver {
+    /// Descomposición propia exacta (covarianza o Gram)
+    #[default]
+    Exact,
+
+    /// SVD aleatorizada, para matrices grandes (p. ej. 100k × 1024)
+    Randomized(RandomizedSvd),
+}
+
+/// Parámetros de la SVD aleatorizada
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RandomizedSvd {
+    /// Proyecciones adicionales sobre las k pedidas (p)
+    pub oversampling: usize,
+
+    /// Iteraciones de potencia (q); mejoran la precisión cuando el espectro
+    /// decae lentamente
+    pub power_iterations: usize,
+
+    /// Semilla de la matriz de prueba gaussiana
+    pub seed: u64,
+}
+
+impl Default for RandomizedSvd {
+    fn default() -> Self {
+        RandomizedSvd { oversampling: 10, power_iterations: 2, seed: 0 }
+    }
+}
+
+/// Cómo se elige el número de componentes principales
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ComponentCount {
+    /// Exactamente k componentes (menos si el rango de los datos es menor)
+    Fixed(usize),
+
+    /// Las mínimas que retengan esta fracción de la varianza (p. ej. 0.99)
+    ExplainedVariance(f64),
+
+    /// Las mínimas cuya pérdida coseno media de reconstrucción,
+    /// `1 - cos(x, x̂)`, no supere este valor (p. ej. 0.001)
+    MaxCosineLoss(f64),
+}
+
+/// Componentes con que empieza la búsqueda aleatorizada de un objetivo
+const RANDOMIZED_SEARCH_START: usize = 16;
+
+/// Base PCA ajustada
+#[derive(Debug, Clone)]
+pub(crate) struct Pca {
+    /// Media por dimensión
+    pub(crate) mean: Array1<f64>,
+
+    /// Componentes principales (k × dim), filas ortonormales
+    pub(crate) components: Array2<f64>,
+
+    /// Varianza explicada por cada componente (descendente)
+    pub(crate) variances: Vec<f64>,
+
+    /// Varianza total de los datos (traza de la covarianza)
+    pub(crate) total_variance: f64,
+}
+
+impl Pca {
+    /// Ajusta las `k` primeras componentes de `data` con el método indicado
+    ///
+    /// Devuelve menos de `k` componentes si el rango de los datos centrados
+    /// es menor.
+    pub(crate) fn fit<T: Element>(data: ArrayView2<'_, T>, k: usize, solver: PcaSolver) -> Pca {
+        match solver {
+            PcaSolver::Exact => fit_exact(data, k),
+            PcaSolver::Randomized(params) => fit_randomized(data, k, params),
+        }
+    }
+
+    /// Ajusta la base con el número de componentes que pide `count`
+    ///
+    /// El método exacto calcula el espectro completo y lo recorta; el
+    /// aleatorizado duplica las componentes estimadas hasta alcanzar el
+    /// objetivo o agotar el rango.
+    pub(crate) fn fit_count<T: Element>(data: ArrayView2<'_, T>, count: ComponentCount, solver: PcaSolver) -> Pca {
+        let rank_bound = data.nrows().min(data.ncols());
+        let mut k = match (count, solver) {
+            (ComponentCount::Fixed(k), _) => return Pca::fit(data, k, solver),
+            (_, PcaSolver::Exact) => rank_bound,
+            (_, PcaSolver::Randomized(_)) => RANDOMIZED_SEARCH_START.min(rank_bound),
+        };
+
+        loop {
+            let mut pca = Pca::fit(data, k, solver);
+            let chosen = match count {
+                ComponentCount::Fixed(k) => Some(k),
+                ComponentCount::ExplainedVariance(target) => pca.components_for_variance(target),
+                ComponentCount::MaxCosineLoss(max_loss) => pca.components_for_cosine_loss(data, max_loss),
+            };
+            if let Some(chosen) = chosen {
+                pca.truncate(chosen);
+                return pca;
+            }
+            if pca.n_components() < k || k >= rank_bound {
+                return pca;
+            }
+            k = (2 * k).min(rank_bound);
+        }
+    }
+
+    pub(crate) fn n_components(&self) -> usize {
+        self.components.nrows()
+    }
+
+    /// Fracción de la varianza total retenida por la base
+    pub(crate) fn explained_variance(&self) -> f64 {
+        if self.total_variance > 0.0 {
+            (self.variances.iter().sum::<f64>() / self.total_variance).min(1.0)
+        } else {
+            1.0
+        }
+    }
+
+    /// Conserva solo las `k` primeras componentes
+    fn truncate(&mut self, k: usize) {
+        let k = k.min(self.n_components());
+        self.components = self.components.slice(ndarray::s![..k, ..]).to_owned();
+        self.variances.truncate(k);
+    }
+
+    /// Mínimo k (≥ 1) cuya varianza acumulada alcanza `target`, si lo hay
+    fn components_for_variance(&self, target: f64) -> Option<usize> {
+        if self.total_variance <= 0.0 {
+            return Some(0);
+        }
+        let mut cumulative = 0.0;
+        for (k, &variance) in self.variances.iter().enumerate() {
+            cumulative += variance;
+            if cumulative >= target * self.total_variance {
+                return Some(k + 1);
+            }
+        }
+        None
+    }
+
+    /// Mínimo k (≥ 1) cuya pérdida coseno media no supera `max_loss`, si lo hay
+    ///
+    /// Con `z = (x - μ)·V` y base ortonormal, `x·x̂ₖ` y `‖x̂ₖ‖²` se acumulan
+    /// componente a componente sin reconstruir los vectores.
+    fn components_for_cosine_loss<T: Element>(&self, data: ArrayView2<'_, T>, max_loss: f64) -> Option<usize> {
+        let k_max = self.n_components();
+        if k_max == 0 {
+            return Some(0);
+        }
+
+        let z = self.project(data);
+        let mean_dot_v = self.components.dot(&self.mean);
+        let mean_norm2 = self.mean.dot(&self.mean);
+
+        let mut losses = vec![0.0f64; k_max];
+        for (row, z) in data.rows().into_iter().zip(z.rows()) {
+            let (x_norm2, x_dot_mean) = row.iter().zip(&self.mean).fold((0.0, 0.0), |(xx, xm), (&x, &m)| {
+                let x = x.to_f64();
+                (xx + x * x, xm + x * m)
+            });
+            let (mut dot, mut norm2) = (x_dot_mean, mean_norm2);
+            for (j, loss) in losses.iter_mut().enumerate() {
+                dot += z[j] * (z[j] + mean_dot_v[j]);
+                norm2 += 2.0 * z[j] * mean_dot_v[j] + z[j] * z[j];
+                let denominator = (x_norm2 * norm2).sqrt();
+                if denominator > 0.0 {
+                    *loss += 1.0 - dot / denominator;
+                }
+            }
+        }
+
+        let n = data.nrows() as f64;
+        losses.iter().position(|&loss| loss / n <= max_loss).map(|k| k + 1)
+    }
+
+    /// Redondea la base a f32, tal como se almacena en el blob
+    ///
+    /// El codificador proyecta con la base redondeada para que la
+    /// reconstrucción use exactamente los mismos vectores.
+    pub(crate) fn round_to_f32(&mut self) {
+        self.components.mapv_inplace(|x| x as f32 as f64);
+    }
+
+    /// Coordenadas (n × k) de `data` en la base
+    pub(crate) fn project<T: Element>(&self, data: ArrayView2<'_, T>) -> Array2<f64> {
+        centered_dot(data, self.mean.view(), self.components.t())
+    }
+}
+
+fn fit_exact<T: Element>(data: ArrayView2<'_, T>, k: usize) -> Pca {
+    let (n, dim) = data.dim();
+    let (x, mean) = centered(data);
+    let scale = 1.0 / n.max(1) as f64;
+
+    let total_variance = x.iter().map(|v| v * v).sum::<f64>() * scale;
+
+    // Autovectores de la matriz pequeña: covarianza si dim ≤ n, Gram si no
+    if n < dim {
+        let (components, variances) = basis_from_rows(&x, k, scale);
+        return Pca { mean, components, variances, total_variance };
+    }
+
+    let covariance = x.t().dot(&x) * scale;
+    let eigen = top_eigen(&covariance, k);
+    let mut components = Array2::<f64>::zeros((eigen.len(), dim));
+    for (mut row, (_, vector)) in components.rows_mut().into_iter().zip(&eigen) {
+        row.assign(vector);
+    }
+    let variances = eigen.iter().map(|(lambda, _)| *lambda).collect();
+    Pca { mean, components, variances, total_variance }
+}
+
+fn fit_randomized<T: Element>(data: ArrayView2<'_, T>, k: usize, params: RandomizedSvd) -> Pca {
+    let (n, dim) = data.dim();
+    let mean = column_mean(data);
+    let l = (k + params.oversampling).min(n).min(dim);
+
+    // 1. Matriz de prueba gaussiana Ω (dim × l) y rango Y = X̃·Ω
+    let mut rng = StdRng::seed_from_u64(params.seed);
+    let omega = Array2::from_shape_simple_fn((dim, l), || standard_normal(&mut rng));
+    let mut q = orthonormalize(centered_dot(data, mean.view(), omega.view()));
+
+    // 2. Iteraciones de potencia: Q ← orth(X̃·orth(X̃ᵀ·Q))
+    for _ in 0..params.power_iterations {
+        let z = orthonormalize(centered_t_dot(data, mean.view(), q.view()));
+        q = orthonormalize(centered_dot(data, mean.view(), z.view()));
+    }
+
+    // 3. B = Qᵀ·X̃ (l × dim) y SVD de B vía la matriz de Gram B·Bᵀ
+    let b = centered_t_dot(data, mean.view(), q.view()).reversed_axes();
+    let (components, variances) = basis_from_rows(&b, k, 1.0 / n.max(1) as f64);
+    let total_variance = centered_sum_squares(data, mean.view()) / n.max(1) as f64;
+    Pca { mean, components, variances, total_variance }
+}
+
+/// Direcciones principales de las filas de `rows` (m × dim) vía su Gram m × m
+///
+/// Con `rows·rowsᵀ·scale = U·Λ·Uᵀ`, cada componente es `rowsᵀ·u / ‖rowsᵀ·u‖`,
+/// con `‖rowsᵀ·u‖² = λ / scale`. Devuelve (componentes, autovalores λ).
+fn basis_from_rows(rows: &Array2<f64>, k: usize, scale: f64) -> (Array2<f64>, Vec<f64>) {
+    let gram = rows.dot(&rows.t()) * scale;
+    let eigen = top_eigen(&gram, k);
+    let mut components = Array2::<f64>::zeros((eigen.len(), rows.ncols()));
+    for (mut row, (lambda, u)) in components.rows_mut().into_iter().zip(&eigen) {
+        row.assign(&(rows.t().dot(u) / (lambda / scale).sqrt()));
+    }
+    (components, eigen.into_iter().map(|(lambda, _)| lambda).collect())
+}
+
+/// Hasta `k` pares (autovalor, autovector) de mayor autovalor, sin los nulos
+fn top_eigen(matrix: &Array2<f64>, k: usize) -> Vec<(f64, Array1<f64>)> {
+    let size = matrix.nrows();
+    let eigen = SymmetricEigen::new(DMatrix::from_fn(size, size, |i, j| matrix[[i, j]]));
+
+    let mut order: Vec<usize> = (0..size).collect();
+    order.sort_by(|&a, &b| eigen.eigenvalues[b].total_cmp(&eigen.eigenvalues[a]));
+
+    let largest = order.first().map_or(0.0, |&i| eigen.eigenvalues[i]).max(0.0);
+    order
+        .into_iter()
+        .take(k.min(size))
+        .filter(|&i| eigen.eigenvalues[i] > largest * EIGEN_TOLERANCE && eigen.eigenvalues[i] > 0.0)
+        .map(|i| (eigen.eigenvalues[i], Array1::from_iter(eigen.eigenvectors.column(i).iter().copied())))
+        .collect()
+}
+
+/// Base ortonormal (QR de Householder) de las columnas de `matrix`
+///
+/// Si las columnas son linealmente dependientes, las direcciones sobrantes
+/// son ortonormales arbitrarias: aportan valores singulares nulos que
+/// `top_eigen` descarta.
+fn orthonormalize(matrix: Array2<f64>) -> Array2<f64> {
+    let (rows, cols) = matrix.dim();
+    let q = DMatrix::from_fn(rows, cols, |i, j| matrix[[i, j]]).qr().q();
+    Array2::from_shape_fn((rows, cols.min(rows)), |(i, j)| q[(i, j)])
+}
+
+/// Centra `data` en f64; devuelve (datos centrados, media)
+fn centered<T: Element>(data: ArrayView2<'_, T>) -> (Array2<f64>, Array1<f64>) {
+    let mean = column_mean(data);
+    let x = centered_block(data, mean.view());
+    (x, mean)
+}
+
+fn centered_block<T: Element>(block: ArrayView2<'_, T>, mean: ArrayView1<'_, f64>) -> Array2<f64> {
+    let mut x = block.mapv(|v| v.to_f64());
+    x -= &mean.insert_axis(Axis(0));
+    x
+}
+
+/// Media por columna, acumulada en f64 por bloques de filas en paralelo
+fn column_mean<T: Element>(data: ArrayView2<'_, T>) -> Array1<f64> {
+    let (n, dim) = data.dim();
+    let partial: Vec<Array1<f64>> = data
+        .axis_chunks_iter(Axis(0), BLOCK_ROWS)
+        .into_par_iter()
+        .map(|block| block.mapv(|v| v.to_f64()).sum_axis(Axis(0)))
+        .collect();
+    sum_in_order(partial, Array1::zeros(dim)) / n.max(1) as f64
+}
+
+/// `(X - μ)·b` por bloques de filas en paralelo (n × cols)
+fn centered_dot<T: Element>(
+    data: ArrayView2<'_, T>,
+    mean: ArrayView1<'_, f64>,
+    b: ArrayView2<'_, f64>,
+) -> Array2<f64> {
+    let mut out = Array2::<f64>::zeros((data.nrows(), b.ncols()));
+    out.axis_chunks_iter_mut(Axis(0), BLOCK_ROWS)
+        .into_par_iter()
+        .zip(data.axis_chunks_iter(Axis(0), BLOCK_ROWS))
+        .for_each(|(mut out, block)| {
+            general_mat_mul(1.0, &centered_block(block, mean), &b, 0.0, &mut out);
+        });
+    out
+}
+
+/// `(X - μ)ᵀ·a` por bloques de filas en paralelo (dim × cols)
+fn centered_t_dot<T: Element>(
+    data: ArrayView2<'_, T>,
+    mean: ArrayView1<'_, f64>,
+    a: ArrayView2<'_, f64>,
+) -> Array2<f64> {
+    let partial: Vec<Array2<f64>> = data
+        .axis_chunks_iter(Axis(0), BLOCK_ROWS)
+        .into_par_iter()
+        .zip(a.axis_chunks_iter(Axis(0), BLOCK_ROWS))
+        .map(|(block, a)| centered_block(block, mean).t().dot(&a))
+        .collect();
+    sum_in_order(partial, Array2::zeros((data.ncols(), a.ncols())))
+}
+
+/// `‖X - μ‖²` (Frobenius) por bloques de filas en paralelo
+fn centered_sum_squares<T: Element>(data: ArrayView2<'_, T>, mean: ArrayView1<'_, f64>) -> f64 {
+    let partial: Vec<f64> = data
+        .axis_chunks_iter(Axis(0), BLOCK_ROWS)
+        .into_par_iter()
+        .map(|block| centered_block(block, mean).iter().map(|v| v * v).sum())
+        .collect();
+    partial.into_iter().sum()
+}
+
+/// Suma parciales por bloque en orden fijo, para que el resultado no
+/// dependa del reparto de trabajo entre hilos
+fn sum_in_order<D: ndarray::Dimension>(
+    partial: Vec<ndarray::Array<f64, D>>,
+    zero: ndarray::Array<f64, D>,
+) -> ndarray::Array<f64, D> {
+    partial.into_iter().fold(zero, |acc, x| acc + x)
+}
+
+/// Muestra de N(0, 1) por Box-Muller
+fn standard_normal(rng: &mut StdRng) -> f64 {
+    let u1: f64 = 1.0 - rng.gen::<f64>(); // (0, 1]
+    let u2: f64 = rng.gen();
+    (-2.0 * u1.ln()).sqrt() * (2.0 * std::f64::consts::PI * u2).cos()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::datasets::{generate_clustered_topics_with_rng, generate_conversational_drift_with_rng, generate_temporal_smoothing_with_rng};
+
+    /// Varianza de `data` capturada por la base (suma de varianzas proyectadas)
+    fn captured_variance(pca: &Pca, data: ArrayView2<'_, f32>) -> f64 {
+        let z = pca.project(data);
+        z.iter().map(|v| v * v).sum::<f64>() / data.nrows() as f64
+    }
+
+    fn to_array(vectors: Vec<Vec<f32>>) -> Array2<f32> {
+        let dim = vectors[0].len();
+        Array2::from_shape_vec((vectors.len(), dim), vectors.concat()).unwrap()
+    }
+
+    #[test]
+    fn test_pca_recovers_rotated_subspace() {
+        // Datos de rango 2 en una base rotada: ninguna coordenada cruda domina
+        let dim = 12;
+        let basis = Array2::from_shape_fn((2, dim), |(r, j)| ((r * dim + j) as f64 * 0.7).sin());
+        let data = Array2::from_shape_fn((40, dim), |(i, j)| {
+            let t = i as f64 * 0.3;
+            (t.cos() * basis[[0, j]] + 2.0 * t.sin() * basis[[1, j]]) as f32
+        });
+
+        let randomized = PcaSolver::Randomized(RandomizedSvd::default());
+        for view in [data.view(), data.slice(ndarray::s![..8, ..])] {
+            for solver in [PcaSolver::Exact, randomized] {
+                let pca = Pca::fit(view, 5, solver);
+                assert_eq!(pca.n_components(), 2);
+
+                // Base ortonormal
+                let gram = pca.components.dot(&pca.components.t());
+                for i in 0..2 {
+                    for j in 0..2 {
+                        let expected = if i == j { 1.0 } else { 0.0 };
+                        assert!((gram[[i, j]] - expected).abs() < 1e-9);
+                    }
+                }
+
+                // Dos componentes bastan para reconstruir los datos
+                let z = pca.project(view);
+                let reconstructed = z.dot(&pca.components) + pca.mean.view().insert_axis(Axis(0));
+                for (a, b) in reconstructed.iter().zip(view.iter()) {
+                    assert!((a - *b as f64).abs() < 1e-5);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_randomized_matches_exact_on_synthetic_datasets() {
+        let k = 8;
+        let mut rng = StdRng::seed_from_u64(31);
+        let accuracy = |data: &Array2<f32>, power_iterations: usize| {
+            let exact = Pca::fit(data.view(), k, PcaSolver::Exact);
+            let params = RandomizedSvd { power_iterations, ..RandomizedSvd::default() };
+            let randomized = Pca::fit(data.view(), k, PcaSolver::Randomized(params));
+            assert_eq!(randomized.n_components(), exact.n_components());
+
+            // (varianza capturada relativa a la exacta, coseno de la 1ª componente)
+            let ratio = captured_variance(&randomized, data.view()) / captured_variance(&exact, data.view());
+            let cosine = exact.components.row(0).dot(&randomized.components.row(0)).abs();
+            assert!(ratio <= 1.0 + 1e-9);
+            (ratio, cosine)
+        };
+
+        // Temas agrupados: espectro de rango casi bajo, basta una iteración
+        let clustered = to_array(generate_clustered_topics_with_rng(1500, 128, 100, &mut rng));
+        let (ratio, cosine) = accuracy(&clustered, 1);
+        assert!(ratio > 0.9999 && cosine > 0.9999, "clustered: ratio {}, coseno {}", ratio, cosine);
+
+        // Suavizado y deriva: espectro que decae lentamente; las iteraciones
+        // de potencia cierran la distancia con el método exacto
+        let datasets = [
+            ("smoothing", to_array(generate_temporal_smoothing_with_rng(1500, 128, 0.95, &mut rng))),
+            ("drift", to_array(generate_conversational_drift_with_rng(1500, 128, 0.05, &mut rng))),
+        ];
+        for (name, data) in &datasets {
+            let (coarse, _) = accuracy(data, 1);
+            let (ratio, cosine) = accuracy(data, 8);
+            assert!(ratio > coarse, "{}: q=8 {} no mejora q=1 {}", name, ratio, coarse);
+            assert!(ratio > 0.99 && cosine > 0.99, "{}: ratio {}, coseno {}", name, ratio, cosine);
+        }
+
+        // Misma semilla, misma base
+        let solver = PcaSolver::Randomized(RandomizedSvd::default());
+        assert_eq!(Pca::fit(clustered.view(), k, solver).components, Pca::fit(clustered.view(), k, solver).components);
+    }
+
+    #[test]
+    fn test_randomized_search_grows_past_initial_guess() {
+        // 40 direcciones de igual varianza: el objetivo exige más de las
+        // 16 componentes con que empieza la búsqueda aleatorizada
+        let mut rng = StdRng::seed_from_u64(3);
+        let data = Array2::from_shape_simple_fn((400, 40), || rng.gen::<f32>());
+
+        for solver in [PcaSolver::Exact, PcaSolver::Randomized(RandomizedSvd::default())] {
+            let pca = Pca::fit_count(data.view(), ComponentCount::ExplainedVariance(0.95), solver);
+            assert!(pca.n_components() > RANDOMIZED_SEARCH_START, "k = {}", pca.n_components());
+            assert!(pca.explained_variance() >= 0.95);
+        }
+    }
+}
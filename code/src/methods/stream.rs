@@ -0,0 +1,578 @@
+//! Codificación en streaming (append-only) para los métodos delta
+//!
+//! Pensado para logs de embeddings conversacionales: el encoder recibe un
+//! vector a la vez con `push`, y cada `flush` emite un frame autocontenido
+//! que el lector puede decodificar en cuanto llega.
+//!
+//! Cada frame es un blob del contenedor con `CodecId::Stream`, cuyos
+//! parámetros son el codec interno (u8) y el número de secuencia (u32). El
+//! frame 0 incluye el primer vector completo; los siguientes solo deltas
+//! respecto al último vector *reconstruido* del frame anterior (lazo
+//! cerrado), así que el error de cuantización no se acumula entre frames.
+//!
+//! Contenido según el codec interno:
+//! - `DeltaLossless`: [primer vector f32] + gzip(RLE de deltas exactos, como
+//!   diferencias de patrones de bits u32)
+//! - `DeltaAns`: escala f32 + [primer vector f32] + gzip(deltas int8)
+//! - `PolarDelta`: gzip([magnitud + ángulos f32] + por vector: delta de
+//!   magnitud f32 + deltas angulares i16)
+
+use flate2::Compression;
+use std::f32::consts::PI;
+
+use super::bytes::{check_count, gzip_decode, gzip_encode, ByteReader};
+use super::container::{self, CodecId};
+use super::delta_lossless::{rle_decode, rle_encode};
+use super::element::ElementType;
+use super::error::CompressionError;
+use super::{from_spherical_angles, to_spherical_angles};
+
+/// Escala de los deltas angulares: ±π rad → ±32767
+const ANGLE_SCALE: f32 = 32767.0 / PI;
+
+/// Dimensión mínima que admite cada codec de streaming
+fn min_dim(codec: CodecId) -> Result<usize, CompressionError> {
+    match codec {
+        CodecId::DeltaLossless | CodecId::DeltaAns => Ok(1),
+        CodecId::PolarDelta => Ok(2),
+        other => Err(CompressionError::UnsupportedStreamCodec(other)),
+    }
+}
+
+/// Coordenadas en las que el codec calcula deltas: el vector mismo, o
+/// magnitud + ángulos esféricos para `PolarDelta` (en ambos casos `dim` valores)
+fn to_coords(codec: CodecId, vector: &[f32]) -> Vec<f32> {
+    match codec {
+        CodecId::PolarDelta => {
+            let magnitude = vector.iter().map(|&x| x * x).sum::<f32>().sqrt();
+            let mut coords = Vec::with_capacity(vector.len());
+            coords.push(magnitude);
+            coords.extend(to_spherical_angles(vector));
+            coords
+        }
+        _ => vector.to_vec(),
+    }
+}
+
+fn from_coords(codec: CodecId, coords: &[f32]) -> Vec<f32> {
+    match codec {
+        CodecId::PolarDelta => from_spherical_angles(&coords[1..], coords[0]),
+        _ => coords.to_vec(),
+    }
+}
+
+fn write_f32s(out: &mut Vec<u8>, values: &[f32]) {
+    for &val in values {
+        out.extend(&val.to_le_bytes());
+    }
+}
+
+/// Encoder append-only para `DeltaLossless`, `DeltaAns` o `PolarDelta`
+pub struct StreamEncoder {
+    codec: CodecId,
+    dim: Option<usize>,
+    /// Último vector reconstruido (en coordenadas del codec), tal como lo verá el decoder
+    state: Option<Vec<f32>>,
+    /// Vectores recibidos desde el último flush (en coordenadas del codec)
+    pending: Vec<Vec<f32>>,
+    sequence: u32,
+}
+
+impl StreamEncoder {
+    pub fn new(codec: CodecId) -> Result<Self, CompressionError> {
+        min_dim(codec)?;
+        Ok(StreamEncoder { codec, dim: None, state: None, pending: Vec::new(), sequence: 0 })
+    }
+
+    pub fn codec(&self) -> CodecId {
+        self.codec
+    }
+
+    /// Vectores pendientes de emitir en el próximo frame
+    pub fn pending(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Frames emitidos hasta ahora
+    pub fn frames_written(&self) -> u32 {
+        self.sequence
+    }
+
+    /// Añade un vector; la dimensión queda fijada por el primero
+    pub fn push(&mut self, vector: &[f32]) -> Result<(), CompressionError> {
+        match self.dim {
+            Some(dim) if dim != vector.len() => {
+                return Err(CompressionError::DimensionMismatch { expected: dim, found: vector.len() });
+            }
+            Some(_) => {}
+            None => {
+                let min = min_dim(self.codec)?;
+                if vector.len() < min {
+                    return Err(CompressionError::UnsupportedDimension { dim: vector.len(), min });
+                }
+                self.dim = Some(vector.len());
+            }
+        }
+
+        self.pending.push(to_coords(self.codec, vector));
+        Ok(())
+    }
+
+    /// Emite los vectores pendientes como un frame (`None` si no hay ninguno)
+    pub fn flush(&mut self) -> Result<Option<Vec<u8>>, CompressionError> {
+        let Some(dim) = self.dim else {
+            return Ok(None);
+        };
+        if self.pending.is_empty() {
+            return Ok(None);
+        }
+
+        // El estado solo se actualiza si el frame se emitió correctamente
+        let (payload, last) = self.encode_pending()?;
+
+        let mut params = Vec::with_capacity(5);
+        params.push(self.codec as u8);
+        params.extend(&self.sequence.to_le_bytes());
+        let frame = container::wrap(CodecId::Stream, ElementType::F32, &params, self.pending.len(), dim, &payload)?;
+
+        self.pending.clear();
+        self.state = Some(last);
+        self.sequence += 1;
+        Ok(Some(frame))
+    }
+
+    /// Codifica los vectores pendientes; devuelve el contenido y el último vector reconstruido
+    fn encode_pending(&self) -> Result<(Vec<u8>, Vec<f32>), CompressionError> {
+        // Sin estado previo, el primer vector viaja completo
+        let (seed, steps) = match &self.state {
+            Some(_) => (None, &self.pending[..]),
+            None => (Some(&self.pending[0]), &self.pending[1..]),
+        };
+        let mut prev = self.state.clone().unwrap_or_else(|| self.pending[0].clone());
+        let mut payload = Vec::new();
+
+        match self.codec {
+            CodecId::DeltaLossless => {
+                if let Some(seed) = seed {
+                    write_f32s(&mut payload, seed);
+                }
+                let mut deltas = Vec::with_capacity(steps.len() * prev.len());
+                for coords in steps {
+                    for (p, &x) in prev.iter_mut().zip(coords) {
+                        deltas.push(ElementType::F32.bits_delta(*p as f64, x as f64));
+                        *p = x;
+                    }
+                }
+                payload.extend(gzip_encode(&rle_encode(&deltas, ElementType::F32), Compression::best())?);
+            }
+            CodecId::DeltaAns => {
+                // Escala por frame a partir de los deltas sin cuantizar
+                let mut max_abs_delta = 0.0f32;
+                let mut last = &prev;
+                for coords in steps {
+                    for (&p, &x) in last.iter().zip(coords) {
+                        max_abs_delta = max_abs_delta.max((x - p).abs());
+                    }
+                    last = coords;
+                }
+                let scale = if max_abs_delta > 1e-10 { max_abs_delta } else { 1.0 };
+
+                payload.extend(&scale.to_le_bytes());
+                if let Some(seed) = seed {
+                    write_f32s(&mut payload, seed);
+                }
+
+                let mut encoded = Vec::with_capacity(steps.len() * prev.len());
+                for coords in steps {
+                    for (p, &x) in prev.iter_mut().zip(coords) {
+                        let quantized = ((x - *p) / scale * 127.0).round().clamp(-127.0, 127.0) as i8;
+                        encoded.push((quantized as i16 + 128) as u8);
+                        *p += (quantized as f32 / 127.0) * scale;
+                    }
+                }
+                payload.extend(gzip_encode(&encoded, Compression::best())?);
+            }
+            CodecId::PolarDelta => {
+                let mut data = Vec::new();
+                if let Some(seed) = seed {
+                    write_f32s(&mut data, seed);
+                }
+                for coords in steps {
+                    let mag_delta = coords[0] - prev[0];
+                    data.extend(&mag_delta.to_le_bytes());
+                    prev[0] += mag_delta;
+
+                    for (p, &angle) in prev[1..].iter_mut().zip(&coords[1..]) {
+                        // El azimut salta de π a -π: el delta equivalente más corto evita saturar
+                        let delta = (angle - *p + PI).rem_euclid(2.0 * PI) - PI;
+                        let quantized = (delta * ANGLE_SCALE).round().clamp(-32768.0, 32767.0) as i16;
+                        data.extend(&quantized.to_le_bytes());
+                        *p += quantized as f32 / ANGLE_SCALE;
+                    }
+                }
+                payload.extend(gzip_encode(&data, Compression::best())?);
+            }
+            other => return Err(CompressionError::UnsupportedStreamCodec(other)),
+        }
+
+        Ok((payload, prev))
+    }
+}
+
+/// Tamaño máximo de un frame (cabecera + contenido) que acepta `feed`: acota
+/// el buffer aunque una cabecera declare un `payload_len` absurdo
+pub const MAX_FRAME_LEN: usize = 64 << 20;
+
+/// Decoder incremental de frames producidos por `StreamEncoder`
+#[derive(Default)]
+pub struct StreamDecoder {
+    codec: Option<CodecId>,
+    dim: Option<usize>,
+    state: Option<Vec<f32>>,
+    next_sequence: u32,
+    /// Bytes recibidos por `feed` que aún no completan un frame
+    buffer: Vec<u8>,
+    /// Error de un frame descartado, pendiente de informar
+    pending_error: Option<CompressionError>,
+}
+
+impl StreamDecoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Codec interno del stream (conocido tras el primer frame)
+    pub fn codec(&self) -> Option<CodecId> {
+        self.codec
+    }
+
+    /// Frames decodificados hasta ahora
+    pub fn frames_read(&self) -> u32 {
+        self.next_sequence
+    }
+
+    /// Bytes en espera de completar un frame
+    pub fn buffered(&self) -> usize {
+        self.buffer.len()
+    }
+
+    /// Acepta bytes arbitrarios del stream y devuelve los vectores de todos
+    /// los frames que quedan completos; el resto se guarda hasta el próximo `feed`
+    ///
+    /// Un frame completo que no se puede decodificar se descarta (el emisor
+    /// puede reenviarlo); una cabecera ilegible o mayor que `MAX_FRAME_LEN`
+    /// vacía el buffer. Si la misma llamada ya había decodificado otros
+    /// frames, se devuelven sus vectores y el error se informa en el
+    /// siguiente `feed`.
+    pub fn feed(&mut self, bytes: &[u8]) -> Result<Vec<Vec<f32>>, CompressionError> {
+        self.buffer.extend_from_slice(bytes);
+        if let Some(e) = self.pending_error.take() {
+            return Err(e);
+        }
+
+        let mut vectors = Vec::new();
+        let mut consumed = 0;
+        let result = loop {
+            let rest = &self.buffer[consumed..];
+            let len = match container::frame_len(rest) {
+                Ok(len) if len > MAX_FRAME_LEN => {
+                    consumed = self.buffer.len();
+                    break Err(CompressionError::BadLength { field: "payload_len", value: len });
+                }
+                Ok(len) if len <= rest.len() => len,
+                // Frame incompleto: esperar más bytes
+                Ok(_) | Err(CompressionError::TruncatedHeader { .. }) => break Ok(()),
+                // Sin cabecera válida no se sabe dónde empieza el siguiente frame
+                Err(e) => {
+                    consumed = self.buffer.len();
+                    break Err(e);
+                }
+            };
+
+            let frame = self.buffer[consumed..consumed + len].to_vec();
+            consumed += len;
+            match self.decode_frame(&frame) {
+                Ok(decoded) => vectors.extend(decoded),
+                Err(e) => break Err(e),
+            }
+        };
+
+        // Ni los frames decodificados ni el descartado se vuelven a leer
+        self.buffer.drain(..consumed);
+        match result {
+            Ok(()) => Ok(vectors),
+            Err(e) if vectors.is_empty() => Err(e),
+            Err(e) => {
+                self.pending_error = Some(e);
+                Ok(vectors)
+            }
+        }
+    }
+
+    /// Decodifica un frame completo (debe ser el siguiente en la secuencia)
+    pub fn decode_frame(&mut self, frame: &[u8]) -> Result<Vec<Vec<f32>>, CompressionError> {
+        let (header, payload) = container::open(frame, CodecId::Stream)?;
+
+        let mut params = ByteReader::header(&header.params);
+        let codec_byte = params.read_u8()?;
+        let codec = CodecId::from_u8(codec_byte).ok_or(CompressionError::UnknownCodecId(codec_byte))?;
+        let sequence = params.read_u32()?;
+
+        if sequence != self.next_sequence {
+            return Err(CompressionError::StreamOutOfOrder { expected: self.next_sequence, found: sequence });
+        }
+        if let Some(expected) = self.codec {
+            if expected != codec {
+                return Err(CompressionError::WrongCodec { expected, found: codec });
+            }
+        }
+        if let Some(expected) = self.dim {
+            if expected != header.dim {
+                return Err(CompressionError::DimensionMismatch { expected, found: header.dim });
+            }
+        }
+        let min = min_dim(codec)?;
+        if header.dim < min {
+            return Err(CompressionError::UnsupportedDimension { dim: header.dim, min });
+        }
+
+        let coords = decode_steps(codec, payload, header.n, header.dim, self.state.as_deref())?;
+
+        self.codec = Some(codec);
+        self.dim = Some(header.dim);
+        if let Some(last) = coords.last() {
+            self.state = Some(last.clone());
+        }
+        self.next_sequence += 1;
+
+        Ok(coords.iter().map(|c| from_coords(codec, c)).collect())
+    }
+}
+
+/// Reconstruye los `n` vectores de un frame (en coordenadas del codec)
+fn decode_steps(
+    codec: CodecId,
+    payload: &[u8],
+    n: usize,
+    dim: usize,
+    state: Option<&[f32]>,
+) -> Result<Vec<Vec<f32>>, CompressionError> {
+    if n == 0 {
+        return Ok(vec![]);
+    }
+    let n_steps = if state.is_some() { n } else { n - 1 };
+    let n_deltas = n_steps.checked_mul(dim)
+        .ok_or(CompressionError::BadLength { field: "n", value: n })?;
+
+    let polar_data;
+    let mut reader = match codec {
+        CodecId::PolarDelta => {
+            polar_data = gzip_decode(payload)?;
+            ByteReader::payload(&polar_data)
+        }
+        _ => ByteReader::payload(payload),
+    };
+
+    let scale = match codec {
+        CodecId::DeltaAns => reader.read_f32()?,
+        _ => 0.0,
+    };
+
+    let mut coords = Vec::with_capacity(n);
+    let mut prev = match state {
+        Some(state) => state.to_vec(),
+        None => {
+            let seed = reader.read_f32_vec(dim)?;
+            coords.push(seed.clone());
+            seed
+        }
+    };
+
+    match codec {
+        CodecId::DeltaLossless => {
+            let deltas = rle_decode(&gzip_decode(reader.rest())?, n_deltas, ElementType::F32)?;
+            for step in deltas.chunks_exact(dim) {
+                for (p, &delta) in prev.iter_mut().zip(step) {
+                    *p = ElementType::F32.apply_bits_delta(*p as f64, delta) as f32;
+                }
+                coords.push(prev.clone());
+            }
+        }
+        CodecId::DeltaAns => {
+            let encoded = gzip_decode(reader.rest())?;
+            if encoded.len() != n_deltas {
+                return Err(CompressionError::DimensionMismatch { expected: n_deltas, found: encoded.len() });
+            }
+            for step in encoded.chunks_exact(dim) {
+                for (p, &byte) in prev.iter_mut().zip(step) {
+                    let quantized = (byte as i16 - 128) as i8;
+                    *p += (quantized as f32 / 127.0) * scale;
+                }
+                coords.push(prev.clone());
+            }
+        }
+        CodecId::PolarDelta => {
+            // Cada vector ocupa: delta de magnitud (f32) + ángulos (i16)
+            check_count("n", n_steps, 4 + 2 * (dim - 1), reader.remaining())?;
+            for _ in 0..n_steps {
+                prev[0] += reader.read_f32()?;
+                for p in prev[1..].iter_mut() {
+                    *p += reader.read_i16()? as f32 / ANGLE_SCALE;
+                }
+                coords.push(prev.clone());
+            }
+        }
+        other => return Err(CompressionError::UnsupportedStreamCodec(other)),
+    }
+
+    Ok(coords)
+}
+
+/// Decodifica un stream completo: frames concatenados, sin bytes sobrantes
+pub(crate) fn decode_stream(blob: &[u8]) -> Result<Vec<Vec<f32>>, CompressionError> {
+    let mut decoder = StreamDecoder::new();
+    let mut vectors = Vec::new();
+    let mut rest = blob;
+
+    while !rest.is_empty() {
+        let len = container::frame_len(rest)?;
+        // `decode_frame` valida que el contenido esté completo antes de avanzar
+        vectors.extend(decoder.decode_frame(rest)?);
+        rest = &rest[len..];
+    }
+
+    Ok(vectors)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::methods::decompress_any;
+
+    fn conversation(n: usize, dim: usize) -> Vec<Vec<f32>> {
+        (0..n)
+            .map(|i| (0..dim).map(|j| ((i as f32) * 0.02 + (j as f32) * 0.41).sin() * 0.8).collect())
+            .collect()
+    }
+
+    fn encode_in_chunks(codec: CodecId, vectors: &[Vec<f32>], chunk: usize) -> Vec<Vec<u8>> {
+        let mut encoder = StreamEncoder::new(codec).unwrap();
+        let mut frames = Vec::new();
+        for (i, v) in vectors.iter().enumerate() {
+            encoder.push(v).unwrap();
+            if (i + 1) % chunk == 0 {
+                frames.extend(encoder.flush().unwrap());
+            }
+        }
+        frames.extend(encoder.flush().unwrap());
+        assert_eq!(encoder.pending(), 0);
+        frames
+    }
+
+    #[test]
+    fn test_stream_roundtrip_byte_by_byte() {
+        let vectors = conversation(130, 16);
+
+        for (codec, tolerance) in [
+            (CodecId::DeltaLossless, 0.0),
+            (CodecId::DeltaAns, 0.02),
+            (CodecId::PolarDelta, 2e-3),
+        ] {
+            let frames = encode_in_chunks(codec, &vectors, 25);
+            assert_eq!(frames.len(), 6);
+
+            // El lector recibe los bytes en trozos que no respetan los frames
+            let stream: Vec<u8> = frames.concat();
+            let mut decoder = StreamDecoder::new();
+            let mut decoded = Vec::new();
+            for piece in stream.chunks(7) {
+                decoded.extend(decoder.feed(piece).unwrap());
+            }
+            assert_eq!(decoder.buffered(), 0);
+            assert_eq!(decoder.frames_read(), 6);
+            assert_eq!(decoded.len(), vectors.len());
+
+            let max_error = vectors.iter().zip(&decoded)
+                .flat_map(|(o, d)| o.iter().zip(d).map(|(a, b)| (a - b).abs()))
+                .fold(0.0f32, f32::max);
+            assert!(max_error <= tolerance, "{:?}: error máximo {}", codec, max_error);
+            if codec == CodecId::DeltaLossless {
+                assert_eq!(decoded, vectors);
+            }
+
+            assert_eq!(decompress_any(&stream).unwrap(), decoded);
+        }
+    }
+
+    #[test]
+    fn test_frames_decode_as_they_arrive() {
+        let vectors = conversation(40, 8);
+        let frames = encode_in_chunks(CodecId::DeltaLossless, &vectors, 10);
+
+        let mut decoder = StreamDecoder::new();
+        for (i, frame) in frames.iter().enumerate() {
+            let decoded = decoder.decode_frame(frame).unwrap();
+            assert_eq!(decoded, vectors[i * 10..(i + 1) * 10].to_vec());
+        }
+    }
+
+    #[test]
+    fn test_stream_rejects_bad_input() {
+        assert!(matches!(
+            StreamEncoder::new(CodecId::Gzip),
+            Err(CompressionError::UnsupportedStreamCodec(CodecId::Gzip))
+        ));
+
+        let mut encoder = StreamEncoder::new(CodecId::DeltaAns).unwrap();
+        encoder.push(&[1.0, 2.0]).unwrap();
+        assert!(matches!(
+            encoder.push(&[1.0]),
+            Err(CompressionError::DimensionMismatch { expected: 2, found: 1 })
+        ));
+
+        let frames = encode_in_chunks(CodecId::DeltaAns, &conversation(20, 4), 10);
+        let mut decoder = StreamDecoder::new();
+        assert!(matches!(
+            decoder.decode_frame(&frames[1]),
+            Err(CompressionError::StreamOutOfOrder { expected: 0, found: 1 })
+        ));
+
+        // Un stream cortado a mitad de frame no se decodifica en bloque
+        let stream = frames.concat();
+        assert!(decompress_any(&stream[..stream.len() - 3]).is_err());
+    }
+
+    #[test]
+    fn test_feed_skips_corrupt_frames() {
+        let vectors = conversation(30, 4);
+        let frames = encode_in_chunks(CodecId::DeltaLossless, &vectors, 10);
+        let corrupt = |frame: &[u8]| {
+            let mut frame = frame.to_vec();
+            let last = frame.len() - 1;
+            frame[last] ^= 0xFF;
+            frame
+        };
+
+        // Frame corrupto seguido del mismo frame reenviado: se descarta y se sigue
+        let mut decoder = StreamDecoder::new();
+        assert!(matches!(decoder.feed(&corrupt(&frames[0])), Err(CompressionError::ChecksumMismatch { .. })));
+        assert_eq!(decoder.buffered(), 0);
+        assert_eq!(decoder.feed(&frames[0]).unwrap().len(), 10);
+
+        // Un frame bueno y uno corrupto en un solo `feed`: los vectores del
+        // bueno se devuelven y el error llega en la llamada siguiente
+        let decoded = decoder.feed(&[frames[1].clone(), corrupt(&frames[2])].concat()).unwrap();
+        assert_eq!((decoded.len(), decoder.frames_read(), decoder.buffered()), (10, 2, 0));
+        assert!(matches!(decoder.feed(&frames[2]), Err(CompressionError::ChecksumMismatch { .. })));
+        assert_eq!(decoder.feed(&[]).unwrap().len(), 10);
+        assert_eq!(decoder.frames_read(), 3);
+
+        // Un `payload_len` absurdo no deja el buffer esperando para siempre
+        let mut header = container::wrap(CodecId::Stream, ElementType::F32, &[7, 0, 0, 0, 0], 0, 4, &[]).unwrap();
+        let len_offset = header.len() - 8;
+        header[len_offset..len_offset + 4].copy_from_slice(&(MAX_FRAME_LEN as u32).to_le_bytes());
+        let mut decoder = StreamDecoder::new();
+        assert!(matches!(decoder.feed(&header), Err(CompressionError::BadLength { field: "payload_len", .. })));
+        assert_eq!(decoder.buffered(), 0);
+    }
+}
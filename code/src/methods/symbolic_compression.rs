@@ -0,0 +1,494 @@
+//! Compresión por dinámica simbólica
+//!
+//! Estrategia:
+//! 1. Reducir dimensión con PCA, como el codec de atractor
+//! 2. Partir el espacio reducido en celdas con k-means (semilla fija)
+//! 3. Almacenar: la secuencia de celdas visitadas, codificada por rango con
+//!    un modelo de contexto adaptativo de orden k, y el refinamiento de cada
+//!    punto respecto al centro de su celda (int16)
+//!
+//! Es el argumento clásico de la dinámica simbólica: con una partición
+//! generadora la secuencia de símbolos determina la órbita y su entropía por
+//! paso tiende a la de Kolmogorov-Sinai, muy por debajo de log2(celdas). El
+//! refinamiento lleva la precisión hasta el paso de cuantización pedido.
+//!
+//! Formato del contenido: media (tipo de elemento, × dim) + componentes
+//! principales (float32, k × dim) + centros de celda (float32, celdas × k) +
+//! longitud (u32) + símbolos (palabras u32 del codificador de rango) +
+//! GZIP(refinamientos int16 en orden de filas). Parámetros: k (u32) +
+//! varianza explicada (f32) + celdas (u32) + orden del contexto (u32) +
+//! refinamientos saturados (u32) + paso por componente (f32 × k) + bytes de
+//! símbolos (u32) + bytes de refinamientos (u32).
+
+use constriction::stream::model::DefaultContiguousCategoricalEntropyModel;
+use constriction::stream::queue::{DefaultRangeDecoder, DefaultRangeEncoder};
+use constriction::stream::{Decode, Encode};
+use flate2::Compression;
+use ndarray::{Array2, ArrayView2};
+
+use super::attractor_compression::{reconstruct_row, DeltaQuantization, ADAPTIVE_LEVELS, MAX_QUANTIZED_DELTA};
+use super::bytes::{gzip_decode, gzip_encode, ByteReader};
+use super::container::{self, CodecId, ContainerHeader};
+use super::element::Element;
+use super::error::CompressionError;
+use super::kmeans::KMeans;
+use super::pca::{ComponentCount, Pca, PcaSolver};
+use super::vectors::{compress_nested, VectorsView};
+
+/// Celdas máximas (los parámetros y los centros deben seguir siendo pequeños)
+pub const MAX_CELLS: usize = 4096;
+
+/// Orden máximo del modelo de contexto (la clave de un contexto, celdas^orden,
+/// debe caber en u64)
+pub const MAX_CONTEXT_ORDER: usize = 5;
+
+/// Configuración del codec simbólico
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SymbolicConfig {
+    /// Número de componentes principales del espacio reducido
+    pub components: ComponentCount,
+
+    /// Celdas de k-means (menos si hay menos vectores)
+    pub cells: usize,
+
+    /// Símbolos previos que forman el contexto
+    pub order: usize,
+
+    /// Pasadas máximas de Lloyd
+    pub iterations: usize,
+
+    /// Semilla de la inicialización k-means++
+    pub seed: u64,
+
+    /// Paso de cuantización de los refinamientos
+    pub quantization: DeltaQuantization,
+}
+
+impl Default for SymbolicConfig {
+    fn default() -> Self {
+        SymbolicConfig {
+            components: ComponentCount::Fixed(10),
+            cells: 64,
+            order: 3,
+            iterations: 25,
+            seed: 0,
+            quantization: DeltaQuantization::Adaptive,
+        }
+    }
+}
+
+/// Metadatos de un blob simbólico
+#[derive(Debug, Clone, PartialEq)]
+pub struct SymbolicInfo {
+    /// Componentes principales retenidas
+    pub n_components: usize,
+    /// Fracción de la varianza retenida
+    pub explained_variance: f64,
+    /// Celdas de la partición
+    pub cells: usize,
+    /// Orden del modelo de contexto
+    pub order: usize,
+    /// Refinamientos saturados al rango de int16 al codificar
+    pub clipped: usize,
+    /// Paso de cuantización de cada componente
+    pub steps: Vec<f32>,
+    /// Bytes de la secuencia de símbolos codificada
+    pub symbol_bytes: usize,
+    /// Bytes de los refinamientos comprimidos
+    pub refinement_bytes: usize,
+}
+
+/// Compresión simbólica con la configuración por defecto
+pub fn symbolic_compress(vectors: &[Vec<f32>]) -> Result<Vec<u8>, CompressionError> {
+    symbolic_compress_with_config(vectors, &SymbolicConfig::default())
+}
+
+/// Compresión simbólica con configuración completa
+pub fn symbolic_compress_with_config(vectors: &[Vec<f32>], config: &SymbolicConfig) -> Result<Vec<u8>, CompressionError> {
+    compress_nested(vectors, |view| symbolic_compress_view(view, config))
+}
+
+/// Compresión simbólica sobre una vista plana
+pub fn symbolic_compress_view<T: Element>(
+    vectors: VectorsView<'_, T>,
+    config: &SymbolicConfig,
+) -> Result<Vec<u8>, CompressionError> {
+    if config.cells == 0 || config.cells > MAX_CELLS {
+        return Err(CompressionError::InvalidParameter { name: "cells", value: config.cells as f64 });
+    }
+    if config.order > MAX_CONTEXT_ORDER {
+        return Err(CompressionError::InvalidParameter { name: "context_order", value: config.order as f64 });
+    }
+    if let DeltaQuantization::Fixed(step) | DeltaQuantization::TargetDistortion(step) = config.quantization {
+        if !(step.is_finite() && step > 0.0) {
+            return Err(CompressionError::InvalidParameter { name: "delta_step", value: step });
+        }
+    }
+    if vectors.is_empty() {
+        let info = SymbolicInfo {
+            n_components: 0,
+            explained_variance: 1.0,
+            cells: 0,
+            order: config.order,
+            clipped: 0,
+            steps: Vec::new(),
+            symbol_bytes: 0,
+            refinement_bytes: 0,
+        };
+        return container::wrap(CodecId::Symbolic, T::TYPE, &encode_params(&info), 0, 0, &[]);
+    }
+
+    let n = vectors.n();
+    let dim = vectors.dim();
+    let data = ArrayView2::from_shape((n, dim), vectors.as_slice())
+        .map_err(|_| CompressionError::BadLength { field: "n", value: n })?;
+
+    // 1. Espacio reducido (base y media tal como se almacenan)
+    let mut pca = Pca::fit_count(data, config.components, PcaSolver::Exact);
+    let explained_variance = pca.explained_variance() as f32 as f64;
+    pca.round_to_f32();
+    pca.mean.mapv_inplace(|m| T::TYPE.round(m));
+    let k = pca.n_components();
+    let projected = pca.project(data);
+
+    // 2. Partición en celdas (centros en float32, tal como se guardan)
+    let mut kmeans = KMeans::fit(projected.view(), config.cells, config.iterations, config.seed);
+    kmeans.centers.mapv_inplace(|c| c as f32 as f64);
+    let cells = kmeans.centers.nrows();
+    let symbols = &kmeans.assignments;
+
+    // 3. Secuencia de símbolos con el modelo de contexto
+    let words = encode_symbols(symbols, cells, config.order)?;
+
+    // 4. Refinamientos respecto al centro de cada celda
+    let steps = refinement_steps(&projected, &kmeans, config.quantization);
+    let mut refinements = Vec::with_capacity(n * k * 2);
+    let mut clipped = 0;
+    for (z, &s) in projected.rows().into_iter().zip(symbols) {
+        for ((&z, &c), &step) in z.iter().zip(kmeans.centers.row(s)).zip(&steps) {
+            let q = ((z - c) / step as f64).round();
+            if q.abs() > i16::MAX as f64 {
+                clipped += 1;
+            }
+            refinements.extend(&(q.clamp(i16::MIN as f64, i16::MAX as f64) as i16).to_le_bytes());
+        }
+    }
+    let refinements = gzip_encode(&refinements, Compression::best())?;
+
+    // 5. Serializar
+    let mut payload = Vec::new();
+    for &val in pca.mean.iter() {
+        T::TYPE.write(val, &mut payload);
+    }
+    for &val in pca.components.iter() {
+        payload.extend(&(val as f32).to_le_bytes());
+    }
+    for &val in kmeans.centers.iter() {
+        payload.extend(&(val as f32).to_le_bytes());
+    }
+    payload.extend(&((words.len() * 4) as u32).to_le_bytes());
+    for word in &words {
+        payload.extend(&word.to_le_bytes());
+    }
+    payload.extend(&refinements);
+
+    let info = SymbolicInfo {
+        n_components: k,
+        explained_variance,
+        cells,
+        order: config.order,
+        clipped,
+        steps,
+        symbol_bytes: words.len() * 4,
+        refinement_bytes: refinements.len(),
+    };
+    container::wrap(CodecId::Symbolic, T::TYPE, &encode_params(&info), n, dim, &payload)
+}
+
+/// Paso por componente: la resolución sigue a la escala de los deltas, como
+/// en el codec de atractor (misma precisión con la misma configuración),
+/// ampliado si hace falta para que el mayor refinamiento quepa en int16
+fn refinement_steps(projected: &Array2<f64>, kmeans: &KMeans, quantization: DeltaQuantization) -> Vec<f32> {
+    let n = projected.nrows();
+    projected
+        .columns()
+        .into_iter()
+        .enumerate()
+        .map(|(j, z)| {
+            let step = match quantization {
+                DeltaQuantization::Fixed(step) => step,
+                DeltaQuantization::TargetDistortion(d) => d * 12f64.sqrt(),
+                DeltaQuantization::Adaptive => {
+                    let sum_sq = z.windows(2).into_iter().map(|w| (w[1] - w[0]).powi(2)).sum::<f64>();
+                    let sigma = (sum_sq / (n.max(2) - 1) as f64).sqrt();
+                    let max = z.iter().zip(&kmeans.assignments)
+                        .fold(0.0f64, |m, (&z, &s)| m.max((z - kmeans.centers[[s, j]]).abs()));
+                    (sigma / ADAPTIVE_LEVELS).max(max / MAX_QUANTIZED_DELTA)
+                }
+            } as f32;
+            if step.is_normal() { step } else { 1.0 }
+        })
+        .collect()
+}
+
+/// Modelo de contexto adaptativo de orden k
+///
+/// Mezcla los conteos de los contextos de longitud 0..=k, de menor a mayor,
+/// con la estimación de Witten-Bell: p(s) = (c(s) + u·p_menor(s)) / (n + u),
+/// con n las apariciones del contexto y u los símbolos distintos vistos en
+/// él. Sin historia queda la distribución uniforme. Codificador y
+/// decodificador lo actualizan igual tras cada símbolo.
+struct ContextModel {
+    symbols: usize,
+    order: usize,
+    /// Por longitud de contexto: clave del contexto → conteos (símbolo, veces)
+    counts: Vec<std::collections::HashMap<u64, Vec<(usize, u32)>>>,
+}
+
+impl ContextModel {
+    fn new(symbols: usize, order: usize) -> Self {
+        ContextModel { symbols, order, counts: vec![Default::default(); order + 1] }
+    }
+
+    /// Clave de los últimos `len` símbolos de `history`
+    fn key(&self, history: &[usize], len: usize) -> Option<u64> {
+        (history.len() >= len).then(|| {
+            history[history.len() - len..].iter().fold(0u64, |key, &s| key * self.symbols as u64 + s as u64)
+        })
+    }
+
+    fn probabilities(&self, history: &[usize]) -> Vec<f64> {
+        let mut p = vec![1.0 / self.symbols as f64; self.symbols];
+        for (len, table) in self.counts.iter().enumerate() {
+            let Some(counts) = self.key(history, len).and_then(|key| table.get(&key)) else {
+                break;
+            };
+            let total: u32 = counts.iter().map(|&(_, c)| c).sum();
+            let distinct = counts.len() as f64;
+            let norm = total as f64 + distinct;
+            for p in p.iter_mut() {
+                *p *= distinct / norm;
+            }
+            for &(s, c) in counts {
+                p[s] += c as f64 / norm;
+            }
+        }
+        p
+    }
+
+    fn update(&mut self, history: &[usize], symbol: usize) {
+        for len in 0..=self.order.min(history.len()) {
+            let Some(key) = self.key(history, len) else { break };
+            let counts = self.counts[len].entry(key).or_default();
+            match counts.iter_mut().find(|(s, _)| *s == symbol) {
+                Some((_, c)) => *c += 1,
+                None => counts.push((symbol, 1)),
+            }
+        }
+    }
+
+    /// Modelo de entropía para el siguiente símbolo
+    fn entropy_model(&self, history: &[usize]) -> Result<DefaultContiguousCategoricalEntropyModel, CompressionError> {
+        DefaultContiguousCategoricalEntropyModel::from_floating_point_probabilities(&self.probabilities(history))
+            .map_err(|()| CompressionError::ans("distribución de contexto no representable"))
+    }
+}
+
+/// Codificación por rango de la secuencia de símbolos (una sola celda no
+/// necesita bits)
+fn encode_symbols(symbols: &[usize], cells: usize, order: usize) -> Result<Vec<u32>, CompressionError> {
+    if cells < 2 {
+        return Ok(Vec::new());
+    }
+    let mut model = ContextModel::new(cells, order);
+    let mut encoder = DefaultRangeEncoder::new();
+    for (t, &s) in symbols.iter().enumerate() {
+        let history = &symbols[t.saturating_sub(order)..t];
+        encoder.encode_symbol(s, &model.entropy_model(history)?).map_err(CompressionError::ans)?;
+        model.update(history, s);
+    }
+    encoder.into_compressed().map_err(CompressionError::ans)
+}
+
+fn decode_symbols(words: Vec<u32>, n: usize, cells: usize, order: usize) -> Result<Vec<usize>, CompressionError> {
+    if cells < 2 {
+        return Ok(vec![0; n]);
+    }
+    let mut model = ContextModel::new(cells, order);
+    let mut decoder = DefaultRangeDecoder::from_compressed(words).map_err(CompressionError::ans)?;
+    let mut symbols = Vec::with_capacity(n);
+    for t in 0..n {
+        let history = &symbols[t.saturating_sub(order)..t];
+        let s = decoder.decode_symbol(&model.entropy_model(history)?).map_err(CompressionError::ans)?;
+        model.update(history, s);
+        symbols.push(s);
+    }
+    Ok(symbols)
+}
+
+/// Parámetros del contenedor: k (u32) + varianza explicada (f32) + celdas
+/// (u32) + orden (u32) + saturados (u32) + pasos (f32 × k) + bytes de
+/// símbolos (u32) + bytes de refinamientos (u32)
+fn encode_params(info: &SymbolicInfo) -> Vec<u8> {
+    let mut params = (info.n_components as u32).to_le_bytes().to_vec();
+    params.extend(&(info.explained_variance as f32).to_le_bytes());
+    params.extend(&(info.cells as u32).to_le_bytes());
+    params.extend(&(info.order as u32).to_le_bytes());
+    params.extend(&(info.clipped.min(u32::MAX as usize) as u32).to_le_bytes());
+    for step in &info.steps {
+        params.extend(&step.to_le_bytes());
+    }
+    params.extend(&(info.symbol_bytes as u32).to_le_bytes());
+    params.extend(&(info.refinement_bytes as u32).to_le_bytes());
+    params
+}
+
+fn decode_params(params: &[u8]) -> Result<SymbolicInfo, CompressionError> {
+    let mut params = ByteReader::header(params);
+    let n_components = params.read_len()?;
+    let explained_variance = params.read_f32()? as f64;
+    let cells = params.read_len()?;
+    if cells > MAX_CELLS {
+        return Err(CompressionError::BadLength { field: "cells", value: cells });
+    }
+    let order = params.read_len()?;
+    if order > MAX_CONTEXT_ORDER {
+        return Err(CompressionError::BadLength { field: "context_order", value: order });
+    }
+    let clipped = params.read_len()?;
+    let steps = params.read_f32_vec(n_components)?;
+    let symbol_bytes = params.read_len()?;
+    let refinement_bytes = params.read_len()?;
+    Ok(SymbolicInfo { n_components, explained_variance, cells, order, clipped, steps, symbol_bytes, refinement_bytes })
+}
+
+/// Metadatos de un blob simbólico (de su cabecera)
+pub fn symbolic_info(compressed: &[u8]) -> Result<SymbolicInfo, CompressionError> {
+    let header = container::read_header(compressed)?.0;
+    if header.codec != CodecId::Symbolic {
+        return Err(CompressionError::WrongCodec { expected: CodecId::Symbolic, found: header.codec });
+    }
+    decode_params(&header.params)
+}
+
+/// Descompresión simbólica
+pub fn symbolic_decompress(compressed: &[u8]) -> Result<Vec<Vec<f32>>, CompressionError> {
+    container::decompress_as(compressed, CodecId::Symbolic)
+}
+
+pub(crate) fn decode_payload<T: Element>(
+    header: &ContainerHeader,
+    payload: &[u8],
+    out: &mut [T],
+) -> Result<(), CompressionError> {
+    let n = header.n;
+    let dim = header.dim;
+    if n == 0 {
+        return Ok(());
+    }
+    if dim == 0 {
+        return Err(CompressionError::UnsupportedDimension { dim, min: 1 });
+    }
+
+    let info = decode_params(&header.params)?;
+    let k = info.n_components;
+    if k > dim {
+        return Err(CompressionError::DimensionMismatch { expected: dim, found: k });
+    }
+    if info.cells == 0 {
+        return Err(CompressionError::BadLength { field: "cells", value: 0 });
+    }
+
+    let mut reader = ByteReader::payload(payload);
+    let mean = header.element.read_vec(&mut reader, dim)?;
+    let components: Vec<f64> = reader.read_f32_vec(k * dim)?.into_iter().map(|v| v as f64).collect();
+    let centers: Vec<f64> = reader.read_f32_vec(info.cells * k)?.into_iter().map(|v| v as f64).collect();
+
+    let symbols_len = reader.read_len()?;
+    let words = reader.take(symbols_len)?.chunks_exact(4).map(|w| u32::from_le_bytes([w[0], w[1], w[2], w[3]])).collect();
+    let symbols = decode_symbols(words, n, info.cells, info.order)?;
+
+    let refinements = gzip_decode(reader.rest())?;
+    let mut refinements = ByteReader::payload(&refinements);
+
+    let mut row = vec![0.0f64; dim];
+    let mut z = vec![0.0f64; k];
+    for (vec, &s) in out.chunks_exact_mut(dim).zip(&symbols) {
+        let center = centers.chunks_exact(k.max(1)).nth(s).unwrap_or_default();
+        for ((z, &c), &step) in z.iter_mut().zip(center).zip(&info.steps) {
+            *z = c + refinements.read_i16()? as f64 * step as f64;
+        }
+        reconstruct_row(&mean, &components, z.iter().copied(), &mut row);
+        for (o, &x) in vec.iter_mut().zip(&row) {
+            *o = T::from_f64(x);
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::methods::{attractor_compress_with_config, attractor_decompress, AttractorConfig};
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    fn max_error(a: &[Vec<f32>], b: &[Vec<f32>]) -> f32 {
+        a.iter().flatten().zip(b.iter().flatten()).map(|(x, y)| (x - y).abs()).fold(0.0f32, f32::max)
+    }
+
+    #[test]
+    fn test_symbolic_against_attractor_on_lifted_lorenz() {
+        // Misma precisión latente en ambos: el codec simbólico paga la
+        // secuencia de celdas más refinamientos del tamaño de una celda; el de
+        // atractor, residuos del tamaño de un paso de la órbita
+        let (n, dim) = (3000, 128);
+        let vectors = crate::datasets::generate_lorenz_embedding(n, dim, 0.05, &mut StdRng::seed_from_u64(23));
+        let quantization = DeltaQuantization::TargetDistortion(1e-3);
+        let bound = 3.0 * 1e-3 * 12f32.sqrt() / 2.0 + 1e-4;
+
+        let config = SymbolicConfig { components: ComponentCount::Fixed(3), quantization, ..SymbolicConfig::default() };
+        let blob = symbolic_compress_with_config(&vectors, &config).unwrap();
+        let info = symbolic_info(&blob).unwrap();
+        assert_eq!((info.n_components, info.cells, info.order, info.clipped), (3, 64, 3, 0));
+        let error = max_error(&vectors, &symbolic_decompress(&blob).unwrap());
+        assert!(error <= bound, "max_error {} > {}", error, bound);
+
+        // El contexto aprende la dinámica: muy por debajo de log2(64) = 6 bits
+        let bits_per_symbol = info.symbol_bytes as f64 * 8.0 / n as f64;
+        let memoryless = SymbolicConfig { order: 0, ..config };
+        let memoryless_info = symbolic_info(&symbolic_compress_with_config(&vectors, &memoryless).unwrap()).unwrap();
+        let memoryless_bits = memoryless_info.symbol_bytes as f64 * 8.0 / n as f64;
+
+        let attractor_config = AttractorConfig { components: ComponentCount::Fixed(3), quantization, ..AttractorConfig::default() };
+        let attractor = attractor_compress_with_config(&vectors, &attractor_config).unwrap();
+        let attractor_error = max_error(&vectors, &attractor_decompress(&attractor).unwrap());
+        println!(
+            "simbólico: {} bytes (símbolos {} B = {:.2} bits/símbolo, orden 0 {:.2}; refinamientos {} B), error {:.2e}; atractor: {} bytes, error {:.2e}",
+            blob.len(), info.symbol_bytes, bits_per_symbol, memoryless_bits, info.refinement_bytes, error,
+            attractor.len(), attractor_error
+        );
+        // (sin contexto las 64 celdas se visitan casi por igual)
+        assert!(bits_per_symbol < 0.25 * 6.0 && bits_per_symbol < 0.5 * memoryless_bits, "{} vs {}", bits_per_symbol, memoryless_bits);
+    }
+
+    #[test]
+    fn test_symbolic_edge_cases() {
+        // Menos vectores que celdas, una sola celda y entrada vacía
+        let vectors: Vec<Vec<f32>> = (0..5).map(|i| vec![i as f32, 1.0 - i as f32, 0.5]).collect();
+        for cells in [64, 1] {
+            let config = SymbolicConfig { components: ComponentCount::Fixed(2), cells, ..SymbolicConfig::default() };
+            let blob = symbolic_compress_with_config(&vectors, &config).unwrap();
+            assert_eq!(symbolic_info(&blob).unwrap().cells, cells.min(5));
+            assert!(max_error(&vectors, &symbolic_decompress(&blob).unwrap()) < 1e-2);
+        }
+        assert!(symbolic_decompress(&symbolic_compress(&[]).unwrap()).unwrap().is_empty());
+
+        let config = SymbolicConfig { order: MAX_CONTEXT_ORDER + 1, ..SymbolicConfig::default() };
+        assert!(matches!(
+            symbolic_compress_with_config(&vectors, &config),
+            Err(CompressionError::InvalidParameter { name: "context_order", .. })
+        ));
+    }
+}
@@ -0,0 +1,147 @@
+//! Vistas planas (fila mayor) sobre conjuntos de vectores
+//!
+//! Los codecs trabajan sobre `VectorsView<T>`: un `&[T]` contiguo de
+//! `n × dim` valores de cualquier `Element` (f32, f16, bf16 o f64). Así un
+//! buffer de `ndarray` o un array de numpy se comprime sin copiarlo a miles
+//! de vectores pequeños, y la API anidada (`&[Vec<f32>]`) queda como
+//! adaptador que aplana una sola vez.
+
+use ndarray::{Array2, ArrayView2};
+
+use super::bytes::common_dim;
+use super::element::Element;
+use super::error::CompressionError;
+
+/// Vista de solo lectura de `n` vectores contiguos de dimensión `dim`
+///
+/// `T` es el tipo de elemento (f32 por defecto; también f16, bf16 y f64).
+#[derive(Debug)]
+pub struct VectorsView<'a, T = f32> {
+    data: &'a [T],
+    dim: usize,
+}
+
+impl<T> Clone for VectorsView<'_, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for VectorsView<'_, T> {}
+
+impl<'a, T: Element> VectorsView<'a, T> {
+    /// Interpreta `data` como vectores consecutivos de dimensión `dim`
+    pub fn new(data: &'a [T], dim: usize) -> Result<Self, CompressionError> {
+        if dim == 0 {
+            if !data.is_empty() {
+                return Err(CompressionError::UnsupportedDimension { dim, min: 1 });
+            }
+        } else if !data.len().is_multiple_of(dim) {
+            return Err(CompressionError::BadLength { field: "data", value: data.len() });
+        }
+        Ok(VectorsView { data, dim })
+    }
+
+    /// Vista sobre un `ArrayView2` en orden estándar (fila mayor, contiguo)
+    pub fn from_array(array: ArrayView2<'a, T>) -> Result<Self, CompressionError> {
+        let dim = array.ncols();
+        let data = array.to_slice().ok_or(CompressionError::NonContiguous)?;
+        VectorsView::new(data, dim)
+    }
+
+    /// Vista sobre bytes nativos de `T` (p. ej. un buffer mapeado de numpy)
+    ///
+    /// Requiere que `bytes` esté alineado al tamaño de `T`.
+    pub fn from_bytes(bytes: &'a [u8], dim: usize) -> Result<Self, CompressionError> {
+        let data = bytemuck::try_cast_slice(bytes)
+            .map_err(|_| CompressionError::BadLength { field: "bytes", value: bytes.len() })?;
+        VectorsView::new(data, dim)
+    }
+
+    pub fn n(&self) -> usize {
+        self.data.len().checked_div(self.dim).unwrap_or(0)
+    }
+
+    pub fn dim(&self) -> usize {
+        self.dim
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /// Valores planos, fila mayor
+    pub fn as_slice(&self) -> &'a [T] {
+        self.data
+    }
+
+    /// Vector `i`
+    pub fn row(&self, i: usize) -> &'a [T] {
+        &self.data[i * self.dim..(i + 1) * self.dim]
+    }
+
+    /// Iterador sobre los vectores, en orden
+    pub fn rows(&self) -> impl ExactSizeIterator<Item = &'a [T]> + 'a {
+        self.data.chunks_exact(self.dim.max(1))
+    }
+
+    pub fn to_vecs(&self) -> Vec<Vec<T>> {
+        self.rows().map(|row| row.to_vec()).collect()
+    }
+}
+
+/// Aplana vectores anidados en un buffer contiguo; devuelve (datos, dim)
+pub(crate) fn flatten(vectors: &[Vec<f32>]) -> Result<(Vec<f32>, usize), CompressionError> {
+    let dim = common_dim(vectors)?;
+    if dim == 0 && !vectors.is_empty() {
+        return Err(CompressionError::UnsupportedDimension { dim, min: 1 });
+    }
+    Ok((vectors.concat(), dim))
+}
+
+/// Reparte valores planos en `n` vectores de dimensión `dim`
+pub(crate) fn split_rows(values: &[f32], n: usize, dim: usize) -> Vec<Vec<f32>> {
+    if dim == 0 {
+        return vec![Vec::new(); n];
+    }
+    values.chunks_exact(dim).take(n).map(|chunk| chunk.to_vec()).collect()
+}
+
+/// Comprime vectores anidados a través de la implementación sobre vistas
+pub(crate) fn compress_nested<F>(vectors: &[Vec<f32>], compress: F) -> Result<Vec<u8>, CompressionError>
+where
+    F: FnOnce(VectorsView<'_>) -> Result<Vec<u8>, CompressionError>,
+{
+    let (data, dim) = flatten(vectors)?;
+    compress(VectorsView::new(&data, dim)?)
+}
+
+/// Convierte vectores decodificados en `Array2` (n × dim)
+pub(crate) fn to_array(values: Vec<f32>, n: usize, dim: usize) -> Result<Array2<f32>, CompressionError> {
+    Array2::from_shape_vec((n, dim), values)
+        .map_err(|_| CompressionError::BadLength { field: "n", value: n })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_view_from_array_and_bytes() {
+        let array = Array2::from_shape_fn((4, 3), |(i, j)| (i * 3 + j) as f32);
+        let view = VectorsView::from_array(array.view()).unwrap();
+        assert_eq!((view.n(), view.dim()), (4, 3));
+        assert_eq!(view.row(2), &[6.0, 7.0, 8.0]);
+
+        let bytes: &[u8] = bytemuck::cast_slice(array.as_slice().unwrap());
+        let from_bytes = VectorsView::<f32>::from_bytes(bytes, 3).unwrap();
+        assert_eq!(from_bytes.as_slice(), view.as_slice());
+
+        // Una vista transpuesta no es contigua en fila mayor
+        assert!(matches!(
+            VectorsView::from_array(array.t()),
+            Err(CompressionError::NonContiguous)
+        ));
+        assert!(VectorsView::new(&[1.0, 2.0, 3.0], 2).is_err());
+    }
+}